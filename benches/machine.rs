@@ -0,0 +1,78 @@
+//! Benchmark of the virtual machine. Run it with "cargo bench". Every
+//! program runs unoptimized, so the time is spent dispatching operations,
+//! and fully optimized, with and without a step limit, since a limit makes
+//! the machine count every step it executes.
+
+use std::time::{Duration, Instant};
+
+use brain_rusted::{MachineOptions, Op, OptLevel, OptimizeOptions, Program};
+
+/// Number of times each program runs. The fastest run is reported.
+const RUNS: usize = 5;
+
+/// Programs run by the benchmark: a name, the source code and the output
+/// it must write.
+const PROGRAMS: [(&str, &str, &[u8]); 2] = [
+    (
+        "countdown",
+        // Prints the alphabet backwards, clearing a cell 10^6 times between
+        // letters.
+        ">++[<+++++++++++++>-]<[[>+>+<<-]>[<+>-]++++++++[>++++++++<-]>.[-]<<\
+         >++++++++++[>++++++++++[>++++++++++[>++++++++++[>++++++++++[>++++++++++[-]<-]<-]<-]<-]<-]<-]\
+         ++++++++++.",
+        b"ZYXWVUTSRQPONMLKJIHGFEDCBA\n",
+    ),
+    (
+        "counters",
+        // Counts to 255^3 with three nested counters, none of which the
+        // optimizer can replace.
+        "-[>-[>-[>+[-]<-]<-]<-]++++++++++.",
+        b"\n",
+    ),
+];
+
+fn main() {
+    println!("{} bytes per operation", std::mem::size_of::<Op>());
+    for (name, source, output) in PROGRAMS {
+        let program = brain_rusted::compile(source).expect("valid program");
+        let options = MachineOptions::default();
+        let limited = MachineOptions {
+            max_steps: Some(u64::MAX),
+            ..MachineOptions::default()
+        };
+        let (unoptimized, _) = brain_rusted::optimize_with(
+            &program,
+            &OptimizeOptions {
+                level: OptLevel::O0,
+                ..OptimizeOptions::default()
+            },
+            &options,
+        );
+        let optimized = brain_rusted::optimize(&program, &options);
+
+        for (label, program, options) in [
+            ("-O0", &unoptimized, &options),
+            ("-O0, step limit", &unoptimized, &limited),
+            ("-O3", &optimized, &options),
+            ("-O3, step limit", &optimized, &limited),
+        ] {
+            let time = fastest(program, options, output);
+            println!("{:<10} {:<16} {:>8.3} s", name, label, time.as_secs_f64());
+        }
+    }
+}
+
+/// Returns the time of the fastest of several runs of the given program,
+/// checking the output of each one.
+fn fastest(program: &Program, options: &MachineOptions, output: &[u8]) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let written = brain_rusted::run_with(program, b"", options).expect("no error");
+            let time = start.elapsed();
+            assert_eq!(written, output);
+            time
+        })
+        .min()
+        .unwrap_or_default()
+}
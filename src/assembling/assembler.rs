@@ -0,0 +1,645 @@
+use std::fmt::Write;
+
+/// General purpose registers, numbered as x86-64 encodes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reg {
+    Rax = 0,
+    Rcx = 1,
+    Rdx = 2,
+    Rbx = 3,
+    Rsi = 6,
+    Rdi = 7,
+    R8 = 8,
+    R12 = 12,
+    R13 = 13,
+    R14 = 14,
+    R15 = 15,
+}
+
+impl Reg {
+    /// Returns the lowest three bits of the number of this register, which
+    /// go in the ModRM and SIB bytes.
+    fn low(self) -> u8 {
+        self as u8 & 7
+    }
+
+    /// Returns the name of the part of this register as wide as the given
+    /// number of bytes.
+    fn name(self, size: u8) -> &'static str {
+        let names = match self {
+            Reg::Rax => ["al", "ax", "eax", "rax"],
+            Reg::Rcx => ["cl", "cx", "ecx", "rcx"],
+            Reg::Rdx => ["dl", "dx", "edx", "rdx"],
+            Reg::Rbx => ["bl", "bx", "ebx", "rbx"],
+            Reg::Rsi => ["sil", "si", "esi", "rsi"],
+            Reg::Rdi => ["dil", "di", "edi", "rdi"],
+            Reg::R8 => ["r8b", "r8w", "r8d", "r8"],
+            Reg::R12 => ["r12b", "r12w", "r12d", "r12"],
+            Reg::R13 => ["r13b", "r13w", "r13d", "r13"],
+            Reg::R14 => ["r14b", "r14w", "r14d", "r14"],
+            Reg::R15 => ["r15b", "r15w", "r15d", "r15"],
+        };
+        names[size.trailing_zeros() as usize]
+    }
+}
+
+/// Conditions of the conditional jumps, numbered as x86-64 encodes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Condition {
+    /// Unsigned greater than or equal, which is also "no carry".
+    AboveOrEqual = 0x3,
+    Zero = 0x4,
+    NotZero = 0x5,
+    /// Signed less than.
+    Less = 0xC,
+    /// Signed less than or equal.
+    LessOrEqual = 0xE,
+}
+
+impl Condition {
+    /// Returns the suffix of the conditional jump on this condition.
+    fn suffix(self) -> &'static str {
+        match self {
+            Condition::AboveOrEqual => "ae",
+            Condition::Zero => "z",
+            Condition::NotZero => "nz",
+            Condition::Less => "l",
+            Condition::LessOrEqual => "le",
+        }
+    }
+}
+
+/// Position in the code, which jumps can target before it is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Label(usize);
+
+impl std::fmt::Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, ".L{}", self.0)
+    }
+}
+
+/// Memory operand: the address in a base register, plus an index register
+/// scaled by 1, 2, 4 or 8, plus a displacement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Memory {
+    base: Reg,
+    index: Option<(Reg, u8)>,
+    displacement: i32,
+}
+
+impl Memory {
+    /// Returns the memory at the address in the given register.
+    pub(crate) fn at(base: Reg) -> Self {
+        Self::offset(base, 0)
+    }
+
+    /// Returns the memory at the given distance from the address in the
+    /// given register.
+    pub(crate) fn offset(base: Reg, displacement: i32) -> Self {
+        Self {
+            base,
+            index: None,
+            displacement,
+        }
+    }
+
+    /// Returns the memory at the address in the given base register, plus
+    /// the given index register times the given scale.
+    pub(crate) fn indexed(base: Reg, index: Reg, scale: u8) -> Self {
+        Self {
+            base,
+            index: Some((index, scale)),
+            displacement: 0,
+        }
+    }
+
+    /// Returns this operand as the listing writes it, for an access as
+    /// wide as the given number of bytes, or for no access at all when
+    /// None.
+    fn text(&self, size: Option<u8>) -> String {
+        let mut text = match size {
+            Some(1) => "BYTE PTR [",
+            Some(2) => "WORD PTR [",
+            Some(4) => "DWORD PTR [",
+            Some(_) => "QWORD PTR [",
+            None => "[",
+        }
+        .to_string();
+        text.push_str(self.base.name(8));
+        if let Some((index, scale)) = self.index {
+            let _ = write!(text, " + {}*{}", index.name(8), scale);
+        }
+        if self.displacement != 0 {
+            let _ = write!(text, " + {}", self.displacement);
+        }
+        text.push(']');
+        text
+    }
+}
+
+/// Encoder of x86-64 instructions. It only knows the instructions the
+/// translation needs. Cells are always accessed through the base in RBX,
+/// indexed by a register scaled by the size of a cell. It may also keep a
+/// listing of every instruction in the syntax of the GNU assembler (with
+/// ".intel_syntax noprefix"), which describes the same program, although
+/// the assembler may pick other encodings for it.
+pub(crate) struct Assembler {
+    /// Machine code written so far.
+    code: Vec<u8>,
+    /// Position of every label, once it is bound.
+    labels: Vec<Option<usize>>,
+    /// Position of every 32-bit displacement relative to a label, and that
+    /// label.
+    fixups: Vec<(usize, Label)>,
+    /// Size of a cell, in bytes: 1, 2, 4 or 8.
+    cell_size: u8,
+    /// Listing written so far, if one is kept.
+    listing: Option<String>,
+}
+
+impl Assembler {
+    /// Returns an empty assembler, for cells of the given size in bytes.
+    pub(crate) fn new(cell_size: u8) -> Self {
+        Self {
+            code: vec![],
+            labels: vec![],
+            fixups: vec![],
+            cell_size,
+            listing: None,
+        }
+    }
+
+    /// Returns an empty assembler, for cells of the given size in bytes,
+    /// that keeps a listing.
+    pub(crate) fn with_listing(cell_size: u8) -> Self {
+        Self {
+            listing: Some(String::new()),
+            ..Self::new(cell_size)
+        }
+    }
+
+    /// Returns whether or not this assembler keeps a listing.
+    pub(crate) fn is_listing(&self) -> bool {
+        self.listing.is_some()
+    }
+
+    /// Returns the machine code, with every displacement pointing at its
+    /// label. Every label must be bound by then.
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        for (position, label) in std::mem::take(&mut self.fixups) {
+            let target = self.labels[label.0].expect("every label is bound");
+            let displacement = target as i64 - (position as i64 + 4);
+            self.code[position..position + 4].copy_from_slice(&(displacement as i32).to_le_bytes());
+        }
+        self.code
+    }
+
+    /// Returns the listing, which is empty if none was kept.
+    pub(crate) fn into_listing(self) -> String {
+        self.listing.unwrap_or_default()
+    }
+
+    /// Adds the given line to the listing, if one is kept.
+    fn list(&mut self, line: std::fmt::Arguments) {
+        if let Some(listing) = &mut self.listing {
+            let _ = writeln!(listing, "{}", line);
+        }
+    }
+
+    /// Adds the given instruction to the listing, if one is kept.
+    fn instruction(&mut self, text: std::fmt::Arguments) {
+        self.list(format_args!("\t{}", text));
+    }
+
+    /// Adds the given comment to the listing, if one is kept.
+    pub(crate) fn comment(&mut self, text: &str) {
+        self.list(format_args!("\t# {}", text));
+    }
+
+    /// Returns a new label, not bound to any position yet.
+    pub(crate) fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Binds the given label to the current position.
+    pub(crate) fn bind(&mut self, label: Label) {
+        self.labels[label.0] = Some(self.code.len());
+        self.list(format_args!("{}:", label));
+    }
+
+    /// Writes the given bytes as they are, as data the code refers to.
+    pub(crate) fn data(&mut self, bytes: &[u8]) {
+        self.bytes(bytes);
+        let text: String = bytes
+            .iter()
+            .map(|byte| match byte {
+                b'"' | b'\\' => format!("\\{}", *byte as char),
+                b' '..=b'~' => (*byte as char).to_string(),
+                _ => format!("\\{:03o}", byte),
+            })
+            .collect();
+        self.instruction(format_args!(".ascii \"{}\"", text));
+    }
+
+    /// Writes the given bytes of an instruction.
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.code.extend_from_slice(bytes);
+    }
+
+    /// Writes a REX prefix, if needed, for an instruction of the given
+    /// operand size whose ModRM byte refers to the given registers. Byte
+    /// registers after BL need one even when it is empty.
+    fn rex(&mut self, wide: bool, byte: bool, reg: u8, index: u8, base: u8) {
+        let rex = 0x40 | (wide as u8) << 3 | (reg >> 3) << 2 | (index >> 3) << 1 | base >> 3;
+        if rex != 0x40 || (byte && (4..8).contains(&reg)) {
+            self.code.push(rex);
+        }
+    }
+
+    /// Writes an instruction between two registers: the first one in the
+    /// "reg" field of its ModRM byte and the second one in "rm".
+    fn register_op(&mut self, wide: bool, opcode: &[u8], reg: u8, rm: Reg) {
+        self.rex(wide, false, reg, 0, rm as u8);
+        self.bytes(opcode);
+        self.code.push(0xC0 | (reg & 7) << 3 | rm.low());
+    }
+
+    /// Writes an instruction that accesses the given memory, whose operand
+    /// is as wide as the given number of bytes. The "reg" field of its
+    /// ModRM byte takes the given value.
+    fn memory_op(&mut self, size: u8, opcode: &[u8], reg: u8, memory: Memory) {
+        if size == 2 {
+            self.code.push(0x66);
+        }
+        let index = memory.index.map_or(0, |(index, _)| index as u8);
+        self.rex(size == 8, size == 1, reg, index, memory.base as u8);
+        self.bytes(opcode);
+
+        // RBP and R13 can only be a base with a displacement.
+        let mode = match memory.displacement {
+            0 if memory.base.low() != 0b101 => 0b00,
+            displacement if i8::try_from(displacement).is_ok() => 0b01,
+            _ => 0b10,
+        };
+        // RSP and R12 can only be a base with a SIB byte, whose index 0b100
+        // means no index.
+        let sib = match memory.index {
+            Some((index, scale)) => Some((scale.trailing_zeros() as u8) << 6 | index.low() << 3),
+            None if memory.base.low() == 0b100 => Some(0b100 << 3),
+            None => None,
+        };
+        match sib {
+            Some(sib) => {
+                self.code.push(mode << 6 | (reg & 7) << 3 | 0b100);
+                self.code.push(sib | memory.base.low());
+            }
+            None => self
+                .code
+                .push(mode << 6 | (reg & 7) << 3 | memory.base.low()),
+        }
+        match mode {
+            0b01 => self.code.push(memory.displacement as u8),
+            0b10 => self.bytes(&memory.displacement.to_le_bytes()),
+            _ => (),
+        }
+    }
+
+    /// Returns the cell at the index in the given register.
+    fn cell(&self, index: Reg) -> Memory {
+        Memory::indexed(Reg::Rbx, index, self.cell_size)
+    }
+
+    /// Writes the given value, truncated to the given number of bytes.
+    fn immediate(&mut self, size: u8, value: i64) {
+        self.bytes(&value.to_le_bytes()[..size as usize]);
+    }
+
+    /// Returns the given value truncated to the given number of bytes, as
+    /// the signed number the listing writes.
+    fn truncated(size: u8, value: i64) -> i64 {
+        match size {
+            1 => value as i8 as i64,
+            2 => value as i16 as i64,
+            4 => value as i32 as i64,
+            _ => value,
+        }
+    }
+
+    /// push reg
+    pub(crate) fn push(&mut self, reg: Reg) {
+        self.rex(false, false, 0, 0, reg as u8);
+        self.code.push(0x50 + reg.low());
+        self.instruction(format_args!("push {}", reg.name(8)));
+    }
+
+    /// pop reg
+    pub(crate) fn pop(&mut self, reg: Reg) {
+        self.rex(false, false, 0, 0, reg as u8);
+        self.code.push(0x58 + reg.low());
+        self.instruction(format_args!("pop {}", reg.name(8)));
+    }
+
+    /// ret
+    pub(crate) fn ret(&mut self) {
+        self.code.push(0xC3);
+        self.instruction(format_args!("ret"));
+    }
+
+    /// syscall
+    pub(crate) fn syscall(&mut self) {
+        self.bytes(&[0x0F, 0x05]);
+        self.instruction(format_args!("syscall"));
+    }
+
+    /// mov destination, source
+    pub(crate) fn mov(&mut self, destination: Reg, source: Reg) {
+        self.register_op(true, &[0x89], source as u8, destination);
+        self.instruction(format_args!(
+            "mov {}, {}",
+            destination.name(8),
+            source.name(8)
+        ));
+    }
+
+    /// mov destination, value, with the shortest encoding for the value.
+    pub(crate) fn mov_immediate(&mut self, destination: Reg, value: i64) {
+        self.encode_mov_immediate(destination, value);
+        match u32::try_from(value) {
+            Ok(value) => self.instruction(format_args!("mov {}, {}", destination.name(4), value)),
+            Err(_) => self.instruction(format_args!("mov {}, {}", destination.name(8), value)),
+        }
+    }
+
+    /// mov destination, address, where the listing names the address with
+    /// the given symbol (which may be followed by an offset), and leaves
+    /// the address to the linker.
+    pub(crate) fn mov_address(&mut self, destination: Reg, address: u64, symbol: &str) {
+        self.encode_mov_immediate(destination, address as i64);
+        self.instruction(format_args!(
+            "mov {}, OFFSET {}",
+            destination.name(8),
+            symbol
+        ));
+    }
+
+    /// Writes the shortest encoding of mov destination, value.
+    fn encode_mov_immediate(&mut self, destination: Reg, value: i64) {
+        if let Ok(value) = u32::try_from(value) {
+            self.rex(false, false, 0, 0, destination as u8);
+            self.code.push(0xB8 + destination.low());
+            self.bytes(&value.to_le_bytes());
+        } else if let Ok(value) = i32::try_from(value) {
+            self.register_op(true, &[0xC7], 0, destination);
+            self.bytes(&value.to_le_bytes());
+        } else {
+            self.rex(true, false, 0, 0, destination as u8);
+            self.code.push(0xB8 + destination.low());
+            self.bytes(&value.to_le_bytes());
+        }
+    }
+
+    /// mov memory, source, storing as many bytes of the source as given.
+    pub(crate) fn store(&mut self, size: u8, memory: Memory, source: Reg) {
+        match size {
+            1 => self.memory_op(1, &[0x88], source as u8, memory),
+            _ => self.memory_op(size, &[0x89], source as u8, memory),
+        }
+        self.instruction(format_args!(
+            "mov {}, {}",
+            memory.text(Some(size)),
+            source.name(size)
+        ));
+    }
+
+    /// movzx destination, byte memory
+    pub(crate) fn load_byte(&mut self, destination: Reg, memory: Memory) {
+        self.memory_op(4, &[0x0F, 0xB6], destination as u8, memory);
+        self.instruction(format_args!(
+            "movzx {}, {}",
+            destination.name(4),
+            memory.text(Some(1))
+        ));
+    }
+
+    /// lea destination, [rip + label]
+    pub(crate) fn address_of(&mut self, destination: Reg, label: Label) {
+        self.rex(true, false, destination as u8, 0, 0);
+        self.code.push(0x8D);
+        self.code.push((destination.low()) << 3 | 0b101);
+        self.displacement(label);
+        self.instruction(format_args!(
+            "lea {}, [rip + {}]",
+            destination.name(8),
+            label
+        ));
+    }
+
+    /// add destination, value
+    pub(crate) fn add_immediate(&mut self, destination: Reg, value: i32) {
+        self.register_op(true, &[0x81], 0, destination);
+        self.bytes(&value.to_le_bytes());
+        self.instruction(format_args!("add {}, {}", destination.name(8), value));
+    }
+
+    /// Writes an instruction between two 64-bit registers, the first one in
+    /// the "rm" field of its ModRM byte, with the given mnemonic.
+    fn binary_op(&mut self, mnemonic: &str, opcode: &[u8], rm: Reg, reg: Reg) {
+        self.register_op(true, opcode, reg as u8, rm);
+        self.instruction(format_args!("{} {}, {}", mnemonic, rm.name(8), reg.name(8)));
+    }
+
+    /// add destination, source
+    pub(crate) fn add(&mut self, destination: Reg, source: Reg) {
+        self.binary_op("add", &[0x01], destination, source);
+    }
+
+    /// sub destination, source
+    pub(crate) fn sub(&mut self, destination: Reg, source: Reg) {
+        self.binary_op("sub", &[0x29], destination, source);
+    }
+
+    /// cmovae destination, source
+    pub(crate) fn cmov_above_or_equal(&mut self, destination: Reg, source: Reg) {
+        self.register_op(true, &[0x0F, 0x43], destination as u8, source);
+        self.instruction(format_args!(
+            "cmovae {}, {}",
+            destination.name(8),
+            source.name(8)
+        ));
+    }
+
+    /// cmp left, right
+    pub(crate) fn cmp(&mut self, left: Reg, right: Reg) {
+        self.binary_op("cmp", &[0x39], left, right);
+    }
+
+    /// test left, right
+    pub(crate) fn test(&mut self, left: Reg, right: Reg) {
+        self.binary_op("test", &[0x85], left, right);
+    }
+
+    /// imul destination, source, value
+    pub(crate) fn imul_immediate(&mut self, destination: Reg, source: Reg, value: i32) {
+        self.register_op(true, &[0x69], destination as u8, source);
+        self.bytes(&value.to_le_bytes());
+        self.instruction(format_args!(
+            "imul {}, {}, {}",
+            destination.name(8),
+            source.name(8),
+            value
+        ));
+    }
+
+    /// imul destination, source
+    pub(crate) fn imul(&mut self, destination: Reg, source: Reg) {
+        self.register_op(true, &[0x0F, 0xAF], destination as u8, source);
+        self.instruction(format_args!(
+            "imul {}, {}",
+            destination.name(8),
+            source.name(8)
+        ));
+    }
+
+    /// call reg
+    pub(crate) fn call(&mut self, target: Reg) {
+        self.register_op(false, &[0xFF], 2, target);
+        self.instruction(format_args!("call {}", target.name(8)));
+    }
+
+    /// call label
+    pub(crate) fn call_label(&mut self, label: Label) {
+        self.code.push(0xE8);
+        self.displacement(label);
+        self.instruction(format_args!("call {}", label));
+    }
+
+    /// jmp label
+    pub(crate) fn jump(&mut self, label: Label) {
+        self.code.push(0xE9);
+        self.displacement(label);
+        self.instruction(format_args!("jmp {}", label));
+    }
+
+    /// jcc label
+    pub(crate) fn jump_if(&mut self, condition: Condition, label: Label) {
+        self.bytes(&[0x0F, 0x80 | condition as u8]);
+        self.displacement(label);
+        self.instruction(format_args!("j{} {}", condition.suffix(), label));
+    }
+
+    /// Writes a 32-bit displacement to the given label, relative to the end
+    /// of the displacement, filled in later. It must end the instruction.
+    fn displacement(&mut self, label: Label) {
+        self.fixups.push((self.code.len(), label));
+        self.bytes(&[0; 4]);
+    }
+
+    /// add cell [index], amount, truncated to the size of a cell.
+    pub(crate) fn add_to_cell(&mut self, index: Reg, amount: i64) {
+        let (size, cell) = (self.cell_size, self.cell(index));
+        match size {
+            1 => self.memory_op(1, &[0x80], 0, cell),
+            8 if i32::try_from(amount).is_err() => {
+                self.mov_immediate(Reg::Rcx, amount);
+                self.memory_op(8, &[0x01], Reg::Rcx as u8, cell);
+                return self.instruction(format_args!("add {}, rcx", cell.text(Some(8))));
+            }
+            _ => self.memory_op(size, &[0x81], 0, cell),
+        }
+        self.immediate(size.min(4), amount);
+        self.instruction(format_args!(
+            "add {}, {}",
+            cell.text(Some(size)),
+            Self::truncated(size, amount)
+        ));
+    }
+
+    /// mov cell [index], value, truncated to the size of a cell.
+    pub(crate) fn set_cell(&mut self, index: Reg, value: i64) {
+        let (size, cell) = (self.cell_size, self.cell(index));
+        match size {
+            1 => self.memory_op(1, &[0xC6], 0, cell),
+            8 if i32::try_from(value).is_err() => {
+                self.mov_immediate(Reg::Rcx, value);
+                self.memory_op(8, &[0x89], Reg::Rcx as u8, cell);
+                return self.instruction(format_args!("mov {}, rcx", cell.text(Some(8))));
+            }
+            _ => self.memory_op(size, &[0xC7], 0, cell),
+        }
+        self.immediate(size.min(4), value);
+        self.instruction(format_args!(
+            "mov {}, {}",
+            cell.text(Some(size)),
+            Self::truncated(size, value)
+        ));
+    }
+
+    /// cmp cell [index], 0
+    pub(crate) fn compare_cell_to_zero(&mut self, index: Reg) {
+        let (size, cell) = (self.cell_size, self.cell(index));
+        match size {
+            1 => self.memory_op(1, &[0x80], 7, cell),
+            _ => self.memory_op(size, &[0x83], 7, cell),
+        }
+        self.code.push(0);
+        self.instruction(format_args!("cmp {}, 0", cell.text(Some(size))));
+    }
+
+    /// Loads the cell [index] into the given register, zero-extended.
+    pub(crate) fn load_cell(&mut self, destination: Reg, index: Reg) {
+        let (size, cell) = (self.cell_size, self.cell(index));
+        let mnemonic = match size {
+            1 => {
+                self.memory_op(4, &[0x0F, 0xB6], destination as u8, cell);
+                "movzx"
+            }
+            2 => {
+                self.memory_op(4, &[0x0F, 0xB7], destination as u8, cell);
+                "movzx"
+            }
+            _ => {
+                self.memory_op(size, &[0x8B], destination as u8, cell);
+                "mov"
+            }
+        };
+        self.instruction(format_args!(
+            "{} {}, {}",
+            mnemonic,
+            destination.name(size.max(4)),
+            cell.text(Some(size))
+        ));
+    }
+
+    /// Loads the lowest byte of the cell [index] into the given register,
+    /// zero-extended.
+    pub(crate) fn load_cell_byte(&mut self, destination: Reg, index: Reg) {
+        self.load_byte(destination, self.cell(index));
+    }
+
+    /// add cell [index], source, truncated to the size of a cell.
+    pub(crate) fn add_register_to_cell(&mut self, index: Reg, source: Reg) {
+        let (size, cell) = (self.cell_size, self.cell(index));
+        match size {
+            1 => self.memory_op(1, &[0x00], source as u8, cell),
+            _ => self.memory_op(size, &[0x01], source as u8, cell),
+        }
+        self.instruction(format_args!(
+            "add {}, {}",
+            cell.text(Some(size)),
+            source.name(size)
+        ));
+    }
+
+    /// lea destination, cell [index]
+    pub(crate) fn cell_address(&mut self, destination: Reg, index: Reg) {
+        let cell = self.cell(index);
+        self.memory_op(8, &[0x8D], destination as u8, cell);
+        self.instruction(format_args!(
+            "lea {}, {}",
+            destination.name(8),
+            cell.text(None)
+        ));
+    }
+}
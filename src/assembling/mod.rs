@@ -0,0 +1,16 @@
+//! The assembling module encapsulates the translation of programs into
+//! x86-64 machine code. The translation of the instructions is shared, and
+//! a runtime decides everything else: the JIT compiler of the virtual
+//! machine calls back into it for input and output, and the ELF backend
+//! makes system calls instead.
+
+/// This module defines the Assembler type, which encodes x86-64
+/// instructions.
+mod assembler;
+
+/// This module translates the instructions of a program into machine code,
+/// and defines the Runtime trait, which completes it.
+mod translation;
+
+pub(crate) use assembler::{Assembler, Condition, Label, Memory, Reg};
+pub(crate) use translation::{translate, Runtime, BASE, INDEX, LENGTH, POSITION};
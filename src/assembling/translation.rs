@@ -0,0 +1,232 @@
+use super::assembler::{Assembler, Condition, Label, Reg};
+use crate::parsing::{Instruction, InstructionKind, Program};
+use crate::virtual_machine::TapePolicy;
+
+/// Register that holds the address of the first cell of the tape.
+pub(crate) const BASE: Reg = Reg::Rbx;
+/// Register that holds the index of the current cell.
+pub(crate) const POSITION: Reg = Reg::R12;
+/// Register that holds the number of cells of the tape.
+pub(crate) const LENGTH: Reg = Reg::R14;
+/// Register that holds the index of a cell other than the current one.
+pub(crate) const INDEX: Reg = Reg::Rdx;
+
+/// Everything the machine code does beyond changing the tape: how it
+/// starts and ends, how it reads and writes, and how it fails. The code of
+/// a runtime may change RAX, RCX, RDX, RSI, RDI and R8 to R11, but must
+/// keep the other registers; R13 and R15 are left for it to use.
+pub(crate) trait Runtime {
+    /// Writes the code that runs before the program. It must load the
+    /// BASE, POSITION and LENGTH registers.
+    fn prologue(&mut self, asm: &mut Assembler);
+
+    /// Writes the code that shows the byte in RSI, for the instruction at
+    /// the given index.
+    fn output(&mut self, asm: &mut Assembler, index: usize);
+
+    /// Writes the code that reads a byte into the cell whose address is in
+    /// RSI, for the instruction at the given index.
+    fn input(&mut self, asm: &mut Assembler, index: usize);
+
+    /// Returns the label of the code that fails because the instruction at
+    /// the given index tried to reach the cell whose index is in INDEX,
+    /// which is out of the tape.
+    fn out_of_tape(&mut self, asm: &mut Assembler, index: usize) -> Label;
+
+    /// Writes the code that runs after the program, followed by any code
+    /// the other parts of the runtime jump to.
+    fn epilogue(&mut self, asm: &mut Assembler);
+}
+
+/// Writes, with the given assembler, the machine code that runs the given
+/// program on a tape of the given length and policy, with the given
+/// runtime. Only the wrap overflow policy is reproduced, and tapes must not
+/// grow. When the assembler keeps a listing, the code of every instruction
+/// is preceded by a comment naming it and the piece of source code it came
+/// from.
+pub(crate) fn translate(
+    program: &Program,
+    asm: &mut Assembler,
+    runtime: &mut impl Runtime,
+    policy: TapePolicy,
+    length: usize,
+) {
+    let mut translation = Translation {
+        asm,
+        runtime,
+        loops: vec![],
+        policy,
+        length,
+    };
+    translation.runtime.prologue(translation.asm);
+    for (index, instruction) in program.instructions().iter().enumerate() {
+        if translation.asm.is_listing() {
+            translation.asm.comment(&describe(instruction));
+        }
+        translation.instruction(index, instruction.kind);
+    }
+    translation.runtime.epilogue(translation.asm);
+}
+
+/// Returns the comment that precedes the code of the given instruction in
+/// a listing.
+fn describe(instruction: &Instruction) -> String {
+    let span = &instruction.span;
+    format!(
+        "{:?}, at line {}, column {} (bytes {}..{})",
+        instruction.kind,
+        span.line,
+        span.column,
+        span.offset,
+        span.end()
+    )
+}
+
+/// Translation of a program in progress.
+struct Translation<'a, R: Runtime> {
+    asm: &'a mut Assembler,
+    runtime: &'a mut R,
+    /// Start of the body and end of every loop open.
+    loops: Vec<(Label, Label)>,
+    /// What happens when the pointer goes past either end of the tape.
+    policy: TapePolicy,
+    /// Number of cells of the tape.
+    length: usize,
+}
+
+impl<R: Runtime> Translation<'_, R> {
+    /// Writes the code of the instruction at the given index.
+    fn instruction(&mut self, index: usize, kind: InstructionKind) {
+        match kind {
+            InstructionKind::Advance => self.moves(index, 1),
+            InstructionKind::Recede => self.moves(index, -1),
+            InstructionKind::Move(offset) => self.moves(index, offset),
+            InstructionKind::Increment => self.add(index, 0, 1),
+            InstructionKind::Decrement => self.add(index, 0, -1),
+            InstructionKind::Add(amount) => self.add(index, 0, amount),
+            InstructionKind::AddAt { offset, amount } => self.add(index, offset, amount),
+            InstructionKind::Show => self.output(index, 0),
+            InstructionKind::OutputAt { offset } => self.output(index, offset),
+            InstructionKind::Read => {
+                self.asm.cell_address(Reg::Rsi, POSITION);
+                self.runtime.input(self.asm, index);
+            }
+            InstructionKind::StartLoop => {
+                let (body, end) = (self.asm.label(), self.asm.label());
+                self.asm.compare_cell_to_zero(POSITION);
+                self.asm.jump_if(Condition::Zero, end);
+                self.asm.bind(body);
+                self.loops.push((body, end));
+            }
+            InstructionKind::StopLoop => {
+                if let Some((body, end)) = self.loops.pop() {
+                    self.asm.compare_cell_to_zero(POSITION);
+                    self.asm.jump_if(Condition::NotZero, body);
+                    self.asm.bind(end);
+                }
+            }
+            InstructionKind::SetZero => self.asm.set_cell(POSITION, 0),
+            InstructionKind::MulAdd { offset, factor } => self.mul_add(index, offset, factor),
+            InstructionKind::ScanRight => self.scan(index, 1),
+            InstructionKind::ScanLeft => self.scan(index, -1),
+            InstructionKind::Print(byte) => {
+                self.asm.mov_immediate(Reg::Rsi, byte as i64);
+                self.runtime.output(self.asm, index);
+            }
+            InstructionKind::SetAt { offset, value } => {
+                let cell = self.resolve(index, offset);
+                self.asm.set_cell(cell, value);
+            }
+        }
+    }
+
+    /// Writes the code that leaves the index of the cell at the given
+    /// distance from the current one in a register, and returns that
+    /// register. Fails as the instruction at the given index if the tape
+    /// does not reach that cell.
+    fn resolve(&mut self, index: usize, offset: isize) -> Reg {
+        if offset == 0 {
+            return POSITION;
+        }
+        let offset = match self.policy {
+            TapePolicy::Wrap => (offset as i64).rem_euclid(self.length as i64),
+            _ => offset as i64,
+        };
+        self.asm.mov(INDEX, POSITION);
+        match i32::try_from(offset) {
+            Ok(offset) => self.asm.add_immediate(INDEX, offset),
+            Err(_) => {
+                self.asm.mov_immediate(Reg::Rcx, offset);
+                self.asm.add(INDEX, Reg::Rcx);
+            }
+        }
+        if self.policy == TapePolicy::Wrap {
+            // Both the position and the offset are within the tape, so
+            // going around once is enough.
+            self.asm.mov(Reg::Rcx, INDEX);
+            self.asm.sub(Reg::Rcx, LENGTH);
+            self.asm.cmov_above_or_equal(INDEX, Reg::Rcx);
+        } else {
+            // Indexes before the tape are negative, so they are beyond its
+            // end when compared unsigned.
+            let failure = self.runtime.out_of_tape(self.asm, index);
+            self.asm.cmp(INDEX, LENGTH);
+            self.asm.jump_if(Condition::AboveOrEqual, failure);
+        }
+        INDEX
+    }
+
+    /// Writes the code that moves the pointer by the given offset.
+    fn moves(&mut self, index: usize, offset: isize) {
+        let cell = self.resolve(index, offset);
+        if cell != POSITION {
+            self.asm.mov(POSITION, cell);
+        }
+    }
+
+    /// Writes the code that adds the given amount to the cell at the given
+    /// offset.
+    fn add(&mut self, index: usize, offset: isize, amount: i64) {
+        let cell = self.resolve(index, offset);
+        self.asm.add_to_cell(cell, amount);
+    }
+
+    /// Writes the code that adds the current cell, multiplied by the given
+    /// factor, to the cell at the given offset, unless the current cell is
+    /// 0.
+    fn mul_add(&mut self, index: usize, offset: isize, factor: i64) {
+        let skip = self.asm.label();
+        self.asm.load_cell(Reg::Rax, POSITION);
+        self.asm.test(Reg::Rax, Reg::Rax);
+        self.asm.jump_if(Condition::Zero, skip);
+        let cell = self.resolve(index, offset);
+        match i32::try_from(factor) {
+            Ok(factor) => self.asm.imul_immediate(Reg::Rax, Reg::Rax, factor),
+            Err(_) => {
+                self.asm.mov_immediate(Reg::Rcx, factor);
+                self.asm.imul(Reg::Rax, Reg::Rcx);
+            }
+        }
+        self.asm.add_register_to_cell(cell, Reg::Rax);
+        self.asm.bind(skip);
+    }
+
+    /// Writes the code that moves the pointer by the given offset until the
+    /// current cell is 0.
+    fn scan(&mut self, index: usize, offset: isize) {
+        let (start, end) = (self.asm.label(), self.asm.label());
+        self.asm.bind(start);
+        self.asm.compare_cell_to_zero(POSITION);
+        self.asm.jump_if(Condition::Zero, end);
+        self.moves(index, offset);
+        self.asm.jump(start);
+        self.asm.bind(end);
+    }
+
+    /// Writes the code that shows the cell at the given offset.
+    fn output(&mut self, index: usize, offset: isize) {
+        let cell = self.resolve(index, offset);
+        self.asm.load_cell_byte(Reg::Rsi, cell);
+        self.runtime.output(self.asm, index);
+    }
+}
@@ -0,0 +1,54 @@
+use super::Op;
+use crate::diagnostics::Span;
+use crate::parsing::InstructionKind;
+
+/// Compiled form of a program: a flat array of operations, ready to be
+/// executed by the virtual machine, and the source location of each one,
+/// kept apart so the operations stay compact, just like the instructions
+/// whose operands are too large for an operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bytecode {
+    /// Operations of the program, in the order they appear.
+    ops: Vec<Op>,
+    /// Source location of each operation, at the same index.
+    spans: Vec<Span>,
+    /// Instructions whose operands are too large for an operation, in the
+    /// order they appear. Each one is executed by a wide operation.
+    wide: Vec<InstructionKind>,
+}
+
+impl Bytecode {
+    /// Returns a new bytecode with the given operations, located at the
+    /// spans at the same indexes, and the given wide instructions. Jump
+    /// targets must point inside the operations or right after the last
+    /// one, and wide operations must point inside the wide instructions.
+    pub(crate) fn new(ops: Vec<Op>, spans: Vec<Span>, wide: Vec<InstructionKind>) -> Self {
+        Self { ops, spans, wide }
+    }
+
+    /// Returns the operations of this bytecode.
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
+    /// Returns the source location of the operation at the given index.
+    pub fn span(&self, index: usize) -> &Span {
+        &self.spans[index]
+    }
+
+    /// Returns the wide instruction at the given index, as given by a wide
+    /// operation.
+    pub fn wide(&self, index: u32) -> InstructionKind {
+        self.wide[index as usize]
+    }
+
+    /// Returns the number of operations of this bytecode.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns whether or not this bytecode has no operations at all.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
@@ -0,0 +1,141 @@
+use super::{Bytecode, Op};
+use crate::parsing::{InstructionKind, Program};
+
+/// Static type that has the lowering logic and serves as a public
+/// interface.
+pub struct Compiler {}
+
+impl Compiler {
+    /// Returns the bytecode equivalent to the given program. Matching
+    /// brackets are paired with an explicit stack, so any nesting depth is
+    /// handled, and each jump gets the index of the operation it lands on.
+    /// Instructions whose operands do not fit in an operation are kept as
+    /// wide instructions.
+    pub fn compile(program: &Program) -> Bytecode {
+        let instructions = program.instructions();
+        let mut ops = Vec::with_capacity(instructions.len());
+        let mut spans = Vec::with_capacity(instructions.len());
+        let mut openings = Vec::new();
+        let mut wide = Vec::new();
+
+        for instruction in instructions {
+            let op = match instruction.kind {
+                InstructionKind::StartLoop => {
+                    openings.push(ops.len());
+                    Op::JumpIfZero(0)
+                }
+                InstructionKind::StopLoop => {
+                    let opening = openings.pop().expect("programs have balanced loops");
+                    ops[opening] = Op::JumpIfZero(Self::index(ops.len() + 1));
+                    Op::JumpIfNotZero(Self::index(opening + 1))
+                }
+                kind => Self::lower(kind).unwrap_or_else(|| {
+                    wide.push(kind);
+                    Op::Wide(Self::index(wide.len() - 1))
+                }),
+            };
+            ops.push(op);
+            spans.push(instruction.span);
+        }
+
+        Bytecode::new(ops, spans, wide)
+    }
+
+    /// Returns the operation equivalent to the given instruction, which is
+    /// not a loop bracket, or None if its operands do not fit in one.
+    fn lower(kind: InstructionKind) -> Option<Op> {
+        Some(match kind {
+            InstructionKind::Advance => Op::Advance,
+            InstructionKind::Recede => Op::Recede,
+            InstructionKind::Increment => Op::Increment,
+            InstructionKind::Decrement => Op::Decrement,
+            InstructionKind::Show => Op::Show,
+            InstructionKind::Read => Op::Read,
+            InstructionKind::Add(amount) => Op::Add(amount.try_into().ok()?),
+            InstructionKind::Move(offset) => Op::Move(offset.try_into().ok()?),
+            InstructionKind::SetZero => Op::SetZero,
+            InstructionKind::MulAdd { offset, factor } => Op::MulAdd {
+                offset: offset.try_into().ok()?,
+                factor: factor.try_into().ok()?,
+            },
+            InstructionKind::ScanRight => Op::ScanRight,
+            InstructionKind::ScanLeft => Op::ScanLeft,
+            InstructionKind::AddAt { offset, amount } => Op::AddAt {
+                offset: offset.try_into().ok()?,
+                amount: amount.try_into().ok()?,
+            },
+            InstructionKind::OutputAt { offset } => Op::OutputAt {
+                offset: offset.try_into().ok()?,
+            },
+            InstructionKind::Print(byte) => Op::Print(byte),
+            InstructionKind::SetAt { offset, value } => Op::SetAt {
+                offset: offset.try_into().ok()?,
+                value: value.try_into().ok()?,
+            },
+            InstructionKind::StartLoop | InstructionKind::StopLoop => return None,
+        })
+    }
+
+    /// Returns the given operation index as a jump target.
+    fn index(index: usize) -> u32 {
+        u32::try_from(index).expect("programs have less than 2^32 operations")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Span;
+    use crate::parsing::Instruction;
+
+    /// Returns a program made of the given instructions.
+    fn program(kinds: &[InstructionKind]) -> Program {
+        let span = Span::new(0, 1, 1, 1);
+        Program::new(
+            kinds
+                .iter()
+                .map(|&kind| Instruction::new(kind, span))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn operations_fit_in_eight_bytes() {
+        assert_eq!(std::mem::size_of::<Op>(), 8);
+    }
+
+    #[test]
+    fn jumps_land_right_after_the_matching_bracket() {
+        let bytecode = Compiler::compile(&crate::compile("+[>[-]<-]").expect("valid program"));
+
+        assert_eq!(bytecode.ops()[1], Op::JumpIfZero(9));
+        assert_eq!(bytecode.ops()[3], Op::JumpIfZero(6));
+        assert_eq!(bytecode.ops()[5], Op::JumpIfNotZero(4));
+        assert_eq!(bytecode.ops()[8], Op::JumpIfNotZero(2));
+    }
+
+    #[test]
+    fn large_operands_are_kept_as_wide_instructions() {
+        let large = InstructionKind::SetAt {
+            offset: 1,
+            value: 1 << 40,
+        };
+        let far = InstructionKind::AddAt {
+            offset: 1 << 20,
+            amount: 1,
+        };
+        let bytecode = Compiler::compile(&program(&[
+            InstructionKind::Add(-5),
+            large,
+            far,
+            InstructionKind::Move(-3),
+        ]));
+
+        assert_eq!(
+            bytecode.ops(),
+            &[Op::Add(-5), Op::Wide(0), Op::Wide(1), Op::Move(-3)]
+        );
+        assert_eq!(bytecode.wide(0), large);
+        assert_eq!(bytecode.wide(1), far);
+    }
+}
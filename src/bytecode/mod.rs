@@ -0,0 +1,19 @@
+//! The bytecode module encapsulates the last compilation step: lowering a
+//! parsed program into a flat, compact array of operations, with the target
+//! of every jump computed ahead of time. This is what the virtual machine
+//! actually executes.
+
+/// This module defines the Bytecode type, the compiled form of a program.
+pub mod code;
+
+/// This module defines the Compiler type, which lowers a parsed program
+/// into bytecode.
+pub mod compiler;
+
+/// This module defines the Op type, each of the operations of the
+/// bytecode.
+pub mod op;
+
+pub use code::Bytecode;
+pub use compiler::Compiler;
+pub use op::Op;
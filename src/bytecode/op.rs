@@ -0,0 +1,60 @@
+/// Represents a bytecode operation. Operations are small and copyable, so
+/// the virtual machine can go through them as fast as possible: operands are
+/// kept narrow, so every operation fits in 8 bytes, and the rare instruction
+/// whose operands do not fit is kept aside, as a wide instruction. Jump
+/// targets are indexes into the array of operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Advance,
+    Recede,
+    Increment,
+    Decrement,
+    Show,
+    Read,
+    /// Adds the given amount (which may be negative) to the current memory
+    /// slot.
+    Add(i32),
+    /// Moves the pointer by the given number of slots (leftwards when
+    /// negative).
+    Move(i32),
+    /// Sets the current memory slot to 0.
+    SetZero,
+    /// Adds the current memory slot, multiplied by the given factor, to the
+    /// slot at the given distance from the current one, unless the current
+    /// slot is 0.
+    MulAdd {
+        offset: i16,
+        factor: i32,
+    },
+    /// Moves the pointer rightwards until the current memory slot is 0.
+    ScanRight,
+    /// Moves the pointer leftwards until the current memory slot is 0.
+    ScanLeft,
+    /// Adds the given amount (which may be negative) to the memory slot at
+    /// the given distance from the current one.
+    AddAt {
+        offset: i16,
+        amount: i32,
+    },
+    /// Shows the memory slot at the given distance from the current one.
+    OutputAt {
+        offset: i32,
+    },
+    /// Writes the given byte to the output.
+    Print(u8),
+    /// Sets the memory slot at the given distance from the current one to
+    /// the value with the given bits.
+    SetAt {
+        offset: i16,
+        value: i32,
+    },
+    /// Jumps to the given operation if the current memory slot is 0. The
+    /// target is the operation right after the matching JumpIfNotZero.
+    JumpIfZero(u32),
+    /// Jumps to the given operation if the current memory slot is not 0.
+    /// The target is the operation right after the matching JumpIfZero.
+    JumpIfNotZero(u32),
+    /// Executes the wide instruction at the given index: an instruction
+    /// whose operands are too large for the operations above.
+    Wide(u32),
+}
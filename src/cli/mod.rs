@@ -0,0 +1,360 @@
+//! The cli module encapsulates the command line interface of the
+//! interpreter: it turns the process arguments into the options used to
+//! drive the library, and failures into process exit codes.
+
+/// This module defines the Status type, which maps each class of failure
+/// to a process exit code.
+mod status;
+
+use brain_rusted::{
+    CellType, CompileOptions, EofPolicy, MachineOptions, OptLevel, OptimizeOptions, OverflowPolicy,
+    Pass, TapePolicy, Target,
+};
+pub(crate) use status::Status;
+
+/// Usage message shown when the command line arguments are not valid.
+pub(crate) const USAGE: &str = "\
+Usage: brain-rusted [OPTIONS] <path>
+       brain-rusted build [OPTIONS] <path>
+
+The build command writes the program as a static Linux x86-64 executable,
+named after the program file unless -o is given. It is the same as
+--emit=elf.
+
+Programs compiled with --emit=bfc are run, or translated, without being
+compiled or optimized again, with the tape, cell, overflow and EOF settings
+they were compiled with.
+
+Options:
+  --strict             reject characters other than commands and whitespace
+  --emit=TARGET        translate the program instead of running it: c, rust,
+                       wat, wasm, elf, asm, llvm, js, python or bfc
+  -o PATH              write the translated program to PATH
+  --output=PATH        same as -o PATH
+  -O0, -O1, -O2, -O3   optimization level (-O0 runs the program as written)
+  --naive              same as -O0
+  --enable-pass=NAMES  run the given passes (comma separated)
+  --disable-pass=NAMES skip the given passes (comma separated)
+  --report             show what each optimization pass did
+  --jit                run the program as native code, where supported
+  --eval-budget=N      steps passes may run at compile time
+  --max-steps=N        stop after N steps
+  --eof=POLICY         end of input: zero, minus-one, unchanged or error
+  --tape-length=N      number of cells the tape starts with
+  --tape=POLICY        past the tape ends: wrap, error or grow
+  --max-tape=N         maximum number of cells of a growing tape
+  --cell=TYPE          cell type: u8, u16, u32, u64, i8, i16, i32 or i64
+  --overflow=POLICY    cell overflow: wrap, saturate or trap";
+
+/// Everything that can be configured from the command line.
+pub(crate) struct Arguments {
+    /// Path of the program file to be executed.
+    pub(crate) path: String,
+    /// Options used to compile the program file.
+    pub(crate) compile: CompileOptions,
+    /// Options used to run the program.
+    pub(crate) machine: MachineOptions,
+    /// Options used to optimize the program.
+    pub(crate) optimize: OptimizeOptions,
+    /// Whether or not to show what each optimization pass did.
+    pub(crate) report: bool,
+    /// Language to translate the program to, instead of running it.
+    pub(crate) emit: Option<Target>,
+    /// Path of the file the translated program is written to. It is
+    /// written to the standard output when None.
+    pub(crate) output: Option<String>,
+}
+
+impl Arguments {
+    /// Attempts to build the arguments from the given command line
+    /// arguments, not including the executable name. On failure, returns an
+    /// error message suitable for display by the program.
+    pub(crate) fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut path = None;
+        let mut compile = CompileOptions::default();
+        let mut machine = MachineOptions::default();
+        let mut optimize = OptimizeOptions::default();
+        let mut report = false;
+        let mut emit = None;
+        let mut output = None;
+        let mut args = args.peekable();
+        let build = args.next_if(|arg| arg == "build").is_some();
+        if build {
+            emit = Some(Target::Elf);
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.split_once('=') {
+                Some(("--max-steps", value)) => {
+                    machine.max_steps = Some(parse_number(&arg, value)?)
+                }
+                Some(("--eof", value)) => machine.eof = parse_eof(&arg, value)?,
+                Some(("--tape-length", value)) => machine.tape_length = parse_length(&arg, value)?,
+                Some(("--tape", value)) => machine.tape_policy = parse_tape(&arg, value)?,
+                Some(("--max-tape", value)) => {
+                    machine.max_tape_length = Some(parse_length(&arg, value)?)
+                }
+                Some(("--cell", value)) => machine.cell = parse_cell(&arg, value)?,
+                Some(("--overflow", value)) => machine.overflow = parse_overflow(&arg, value)?,
+                Some(("--enable-pass", value)) => {
+                    optimize.enabled.extend(parse_passes(&arg, value)?)
+                }
+                Some(("--emit", value)) => emit = Some(parse_target(&arg, value)?),
+                Some(("--output", value)) => output = Some(value.to_string()),
+                Some(("--eval-budget", value)) => {
+                    optimize.evaluation_budget = parse_number(&arg, value)?
+                }
+                Some(("--disable-pass", value)) => {
+                    optimize.disabled.extend(parse_passes(&arg, value)?)
+                }
+                _ => match arg.as_str() {
+                    "--strict" => compile.strict = true,
+                    "--report" => report = true,
+                    "--jit" => machine.jit = true,
+                    "-o" => output = Some(args.next().ok_or_else(|| error_missing_value(&arg))?),
+                    "-O0" | "--naive" => optimize.level = OptLevel::O0,
+                    "-O1" => optimize.level = OptLevel::O1,
+                    "-O2" => optimize.level = OptLevel::O2,
+                    "-O3" => optimize.level = OptLevel::O3,
+                    flag if flag.starts_with('-') => return Err(error_unknown_flag(flag)),
+                    _ if path.is_some() => return Err(error_unexpected_argument(&arg)),
+                    _ => path = Some(arg),
+                },
+            }
+        }
+
+        let path = path.ok_or_else(|| "No file found".to_string())?;
+        if emit.is_none() && output.is_some() {
+            return Err(error_output_without_target());
+        }
+        if build && output.is_none() {
+            output = Some(executable_path(&path));
+        }
+        Ok(Self {
+            path,
+            compile,
+            machine,
+            optimize,
+            report,
+            emit,
+            output,
+        })
+    }
+}
+
+/// Returns the path of the executable built from the program file at the
+/// given path: the same path without its extension, or with ".out" added
+/// when there is no extension to remove.
+fn executable_path(path: &str) -> String {
+    let stem = std::path::Path::new(path).with_extension("");
+    match stem.to_str() {
+        Some(stem) if stem != path && !stem.is_empty() => stem.to_string(),
+        _ => format!("{}.out", path),
+    }
+}
+
+/// Attempts to parse the value of the given flag as a number.
+fn parse_number(flag: &str, value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| error_invalid_value(flag))
+}
+
+/// Attempts to parse the value of the given flag as a number of memory
+/// slots, which must not be zero.
+fn parse_length(flag: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(length) if length > 0 => Ok(length),
+        _ => Err(error_invalid_value(flag)),
+    }
+}
+
+/// Attempts to parse the value of the given flag as a tape policy: "wrap",
+/// "error" or "grow".
+fn parse_tape(flag: &str, value: &str) -> Result<TapePolicy, String> {
+    match value {
+        "wrap" => Ok(TapePolicy::Wrap),
+        "error" => Ok(TapePolicy::Error),
+        "grow" => Ok(TapePolicy::Grow),
+        _ => Err(error_invalid_value(flag)),
+    }
+}
+
+/// Attempts to parse the value of the given flag as a cell type, named
+/// after the Rust integer type it stores.
+fn parse_cell(flag: &str, value: &str) -> Result<CellType, String> {
+    match value {
+        "u8" => Ok(CellType::U8),
+        "u16" => Ok(CellType::U16),
+        "u32" => Ok(CellType::U32),
+        "u64" => Ok(CellType::U64),
+        "i8" => Ok(CellType::I8),
+        "i16" => Ok(CellType::I16),
+        "i32" => Ok(CellType::I32),
+        "i64" => Ok(CellType::I64),
+        _ => Err(error_invalid_value(flag)),
+    }
+}
+
+/// Attempts to parse the value of the given flag as an overflow policy:
+/// "wrap", "saturate" or "trap".
+fn parse_overflow(flag: &str, value: &str) -> Result<OverflowPolicy, String> {
+    match value {
+        "wrap" => Ok(OverflowPolicy::Wrap),
+        "saturate" => Ok(OverflowPolicy::Saturate),
+        "trap" => Ok(OverflowPolicy::Trap),
+        _ => Err(error_invalid_value(flag)),
+    }
+}
+
+/// Attempts to parse the value of the given flag as an EOF policy: "zero"
+/// (or "0"), "minus-one" (or "-1" or "255"), "unchanged" or "error".
+fn parse_eof(flag: &str, value: &str) -> Result<EofPolicy, String> {
+    match value {
+        "zero" | "0" => Ok(EofPolicy::Zero),
+        "minus-one" | "-1" | "255" => Ok(EofPolicy::MinusOne),
+        "unchanged" => Ok(EofPolicy::Unchanged),
+        "error" => Ok(EofPolicy::Error),
+        _ => Err(error_invalid_value(flag)),
+    }
+}
+
+/// Attempts to parse the value of the given flag as a comma separated list
+/// of pass names.
+fn parse_passes(flag: &str, value: &str) -> Result<Vec<Pass>, String> {
+    value
+        .split(',')
+        .map(|name| Pass::from_name(name).ok_or_else(|| error_invalid_value(flag)))
+        .collect()
+}
+
+/// Attempts to parse the value of the given flag as the name of a target
+/// language.
+fn parse_target(flag: &str, value: &str) -> Result<Target, String> {
+    Target::from_name(value).ok_or_else(|| error_invalid_value(flag))
+}
+
+/// Returns an error message stating that the given flag is not known.
+fn error_unknown_flag(flag: &str) -> String {
+    format!("Unknown flag: {}", flag)
+}
+
+/// Returns an error message stating that the value given to a flag is not
+/// valid.
+fn error_invalid_value(flag: &str) -> String {
+    format!("Invalid value: {}", flag)
+}
+
+/// Returns an error message stating that the given flag must be followed by
+/// a value.
+fn error_missing_value(flag: &str) -> String {
+    format!("Missing value: {}", flag)
+}
+
+/// Returns an error message stating that an output path was given for a
+/// program that is run, not translated.
+fn error_output_without_target() -> String {
+    "An output path needs --emit or build".to_string()
+}
+
+/// Returns an error message stating that an argument was given after the
+/// program path.
+fn error_unexpected_argument(arg: &str) -> String {
+    format!("Unexpected argument: {}", arg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the given command line arguments, not including the
+    /// executable name.
+    fn parse(args: &[&str]) -> Result<Arguments, String> {
+        Arguments::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    /// Returns the passes run for the given command line arguments.
+    fn passes(args: &[&str]) -> Vec<Pass> {
+        parse(args).expect("valid arguments").optimize.passes()
+    }
+
+    #[test]
+    fn every_level_runs_the_passes_of_the_levels_below() {
+        assert_eq!(passes(&["-O0", "a.bf"]), []);
+        assert_eq!(passes(&["-O1", "a.bf"]), [Pass::Folding]);
+        assert_eq!(passes(&["-O2", "a.bf"]), [Pass::Folding, Pass::Idioms]);
+        assert_eq!(
+            passes(&["-O3", "a.bf"]),
+            [Pass::Folding, Pass::Idioms, Pass::Fusion]
+        );
+        assert_eq!(passes(&["a.bf"]), passes(&["-O3", "a.bf"]));
+    }
+
+    #[test]
+    fn naive_is_the_same_as_o0() {
+        assert_eq!(passes(&["--naive", "a.bf"]), []);
+        assert_eq!(passes(&["-O2", "--naive", "a.bf"]), []);
+    }
+
+    #[test]
+    fn passes_are_enabled_and_disabled_by_name() {
+        assert_eq!(
+            passes(&["-O0", "--enable-pass=idioms,folding", "a.bf"]),
+            [Pass::Folding, Pass::Idioms]
+        );
+        assert_eq!(
+            passes(&[
+                "--disable-pass=fusion",
+                "--enable-pass=partial-evaluation",
+                "a.bf"
+            ]),
+            [Pass::Folding, Pass::Idioms, Pass::PartialEvaluation]
+        );
+        assert_eq!(
+            passes(&[
+                "--enable-pass=fusion",
+                "--disable-pass=fusion",
+                "-O1",
+                "a.bf"
+            ]),
+            [Pass::Folding]
+        );
+    }
+
+    #[test]
+    fn unknown_pass_names_are_invalid() {
+        for flag in [
+            "--enable-pass=folding,inlining",
+            "--disable-pass=",
+            "--enable-pass",
+        ] {
+            assert!(parse(&[flag, "a.bf"]).is_err(), "{flag} accepted");
+        }
+        assert_eq!(
+            parse(&["--disable-pass=unrolling", "a.bf"]).err(),
+            Some("Invalid value: --disable-pass=unrolling".to_string())
+        );
+    }
+
+    #[test]
+    fn report_is_off_by_default() {
+        assert!(!parse(&["a.bf"]).expect("valid arguments").report);
+        assert!(
+            parse(&["--report", "a.bf"])
+                .expect("valid arguments")
+                .report
+        );
+    }
+
+    #[test]
+    fn output_paths_need_a_target() {
+        for args in [&["-o", "out", "a.bf"][..], &["--output=out", "a.bf"]] {
+            assert_eq!(
+                parse(args).err(),
+                Some("An output path needs --emit or build".to_string())
+            );
+        }
+        let emitted = parse(&["--emit=c", "--output=out.c", "a.bf"]).expect("valid arguments");
+        assert_eq!(emitted.output.as_deref(), Some("out.c"));
+        let built = parse(&["build", "-o", "out", "a.bf"]).expect("valid arguments");
+        assert_eq!(built.output.as_deref(), Some("out"));
+    }
+}
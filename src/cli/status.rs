@@ -0,0 +1,39 @@
+use brain_rusted::{BrainError, EmitError};
+
+/// Classes of failure of the interpreter. Each one ends the process with
+/// its own exit code, so scripts can tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    /// The command line arguments are not valid.
+    Usage = 64,
+    /// The program has a lexical or syntactic error.
+    Syntax = 65,
+    /// The program file could not be loaded.
+    Load = 66,
+    /// The program failed while being executed.
+    Runtime = 70,
+    /// The output file could not be written.
+    CantCreate = 73,
+    /// The program went beyond one of the limits set for its execution.
+    LimitExceeded = 75,
+}
+
+impl Status {
+    /// Returns the class of failure the given error belongs to.
+    pub(crate) fn of(error: &BrainError) -> Self {
+        match error {
+            BrainError::Load(_) => Status::Load,
+            BrainError::Lex(_) | BrainError::Parse(_) => Status::Syntax,
+            BrainError::Runtime(e) if e.is_limit_exceeded() => Status::LimitExceeded,
+            BrainError::Runtime(_) => Status::Runtime,
+            BrainError::Emit(EmitError::Unsupported { .. }) => Status::Usage,
+            BrainError::Emit(EmitError::Io { .. }) => Status::CantCreate,
+        }
+    }
+}
+
+impl From<Status> for std::process::ExitCode {
+    fn from(status: Status) -> Self {
+        std::process::ExitCode::from(status as u8)
+    }
+}
@@ -0,0 +1,15 @@
+//! The diagnostics module encapsulates everything related to pointing at
+//! the source code of a program: the Span type, which locates a piece of
+//! the source, and the rendering of error messages that show the source
+//! line with a caret under the offending characters.
+
+/// This module defines the Span type, which locates a piece of the source
+/// code of a program.
+pub mod span;
+
+/// This module defines the Snippet type, which allows rendering an error
+/// message together with the source line it refers to, the way rustc does.
+pub mod snippet;
+
+pub use snippet::Snippet;
+pub use span::Span;
@@ -0,0 +1,151 @@
+use super::Span;
+
+/// Greatest number of characters of the source line kept on each side of
+/// the start of the span. Longer lines are cut, so a snippet stays small
+/// however long the line it points at is.
+const CONTEXT: usize = 40;
+
+/// Text shown in place of the part of a line that was cut.
+const ELLIPSIS: &str = "...";
+
+/// A span together with the part of the source line it points at. It
+/// allows rendering an error message without keeping the whole source
+/// code around.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Location of the piece of source code being pointed at.
+    span: Span,
+    /// Contents of the line where the span starts, without its line
+    /// terminator, cut to at most CONTEXT characters on each side of the
+    /// start of the span.
+    line: String,
+    /// Number of characters of "line" before the start of the span.
+    before: usize,
+}
+
+impl Snippet {
+    /// Returns a new snippet pointing at the given span of the given source
+    /// code. Only the characters around the start of the span are read, so
+    /// it takes the same time however long the line is.
+    pub fn new(source: &str, span: &Span) -> Self {
+        let offset = floor_char_boundary(source, span.offset);
+        let (head, tail) = source.split_at(offset);
+
+        let mut start = offset;
+        let mut cut_start = false;
+        for (count, (index, character)) in head.char_indices().rev().enumerate() {
+            if character == '\n' {
+                break;
+            }
+            if count == CONTEXT {
+                cut_start = true;
+                break;
+            }
+            start = index;
+        }
+
+        let mut end = offset;
+        let mut cut_end = false;
+        for (count, (index, character)) in tail.char_indices().enumerate() {
+            if character == '\n' || (character == '\r' && tail[index..].starts_with("\r\n")) {
+                break;
+            }
+            if count == CONTEXT {
+                cut_end = true;
+                break;
+            }
+            end = offset + index + character.len_utf8();
+        }
+
+        let mut line = String::new();
+        if cut_start {
+            line.push_str(ELLIPSIS);
+        }
+        line.push_str(&source[start..offset]);
+        let before = line.chars().count();
+        line.push_str(&source[offset..end]);
+        if cut_end {
+            line.push_str(ELLIPSIS);
+        }
+
+        Self {
+            span: *span,
+            line,
+            before,
+        }
+    }
+
+    /// Returns the location of the piece of source code being pointed at.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Returns the contents of the source line the span starts at, cut
+    /// around the span when the line is long, with "..." in place of the
+    /// parts cut.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Writes the given error message followed by the source line, with
+    /// carets under the characters covered by the span. Only the first
+    /// line of the span is shown.
+    ///
+    /// ```text
+    /// error: unclosed loop
+    ///  --> 3:5
+    ///   |
+    /// 3 | +++[>+
+    ///   |    ^
+    /// ```
+    pub(crate) fn render(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        message: std::fmt::Arguments<'_>,
+    ) -> std::fmt::Result {
+        let number = self.span.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        writeln!(f, "error: {}", message)?;
+        writeln!(f, "{}--> {}:{}", gutter, self.span.line, self.span.column)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", number, self.line)?;
+        write!(f, "{} | {}", gutter, self.marker())
+    }
+
+    /// Returns the line drawn under the source line, with carets under the
+    /// characters covered by the span. Tabs before the span are kept, so
+    /// the carets stay aligned with the source line.
+    fn marker(&self) -> String {
+        let column = self.before;
+        let padding: String = self
+            .line
+            .chars()
+            .take(column)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let covered = self
+            .line
+            .chars()
+            .skip(column)
+            .scan(0, |bytes, c| {
+                *bytes += c.len_utf8();
+                Some(*bytes)
+            })
+            .take_while(|bytes| *bytes <= self.span.length)
+            .count()
+            .max(1);
+
+        format!("{}{}", padding, "^".repeat(covered))
+    }
+}
+
+/// Returns the greatest index of the given text that is not past the given
+/// one and starts a character.
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
@@ -0,0 +1,41 @@
+/// Location of a piece of the source code of a program. It keeps both the
+/// byte offset (useful to slice the source) and the line and column (useful
+/// to show to the user) where the piece starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    /// Byte offset of the first character of the piece.
+    pub offset: usize,
+    /// Length, in bytes, of the piece.
+    pub length: usize,
+    /// Line (starting at 1) of the first character of the piece.
+    pub line: usize,
+    /// Column (starting at 1, counted in characters) of the first character
+    /// of the piece.
+    pub column: usize,
+}
+
+impl Span {
+    /// Returns a new span with the given location.
+    pub fn new(offset: usize, length: usize, line: usize, column: usize) -> Self {
+        Self {
+            offset,
+            length,
+            line,
+            column,
+        }
+    }
+
+    /// Returns a span that starts where this span starts and ends where the
+    /// given span ends.
+    pub fn to(&self, end: &Span) -> Self {
+        Self {
+            length: (end.offset + end.length).saturating_sub(self.offset),
+            ..*self
+        }
+    }
+
+    /// Returns the byte offset right after the last character of the piece.
+    pub fn end(&self) -> usize {
+        self.offset + self.length
+    }
+}
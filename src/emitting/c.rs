@@ -0,0 +1,264 @@
+use super::source::Source;
+use super::{EmitError, Target};
+use crate::parsing::{InstructionKind, Program};
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Helper that returns the index of the cell at the given distance from the
+/// pointer, on a tape that wraps around.
+const AT_WRAP: &str = "\
+static long long at(long long offset) {
+    long long index = (pointer + offset) % TAPE_LENGTH;
+    return index < 0 ? index + TAPE_LENGTH : index;
+}
+";
+
+/// Helper that returns the index of the cell at the given distance from the
+/// pointer, on a tape whose ends cannot be crossed.
+const AT_ERROR: &str = "\
+static long long at(long long offset) {
+    long long index = pointer + offset;
+    if (index < 0 || index >= TAPE_LENGTH) {
+        fflush(stdout);
+        fprintf(stderr, \"Pointer moved out of the tape, to cell %lld\\n\",
+                index < 0 ? -1 : TAPE_LENGTH);
+        exit(70);
+    }
+    return index;
+}
+";
+
+/// Helper that moves the pointer.
+const MOVE: &str = "\
+static void move(long long offset) {
+    pointer = at(offset);
+}
+";
+
+/// Helper that adds an amount to a cell, wrapping around.
+const ADD: &str = "\
+static void add(long long offset, long long amount) {
+    long long index = at(offset);
+    tape[index] = (cell)((uint64_t)tape[index] + (uint64_t)amount);
+}
+";
+
+/// Helper that adds the current cell, multiplied by a factor, to another
+/// cell, wrapping around.
+const MUL_ADD: &str = "\
+static void mul_add(long long offset, long long factor) {
+    if (tape[pointer]) {
+        long long index = at(offset);
+        tape[index] = (cell)((uint64_t)tape[index] + (uint64_t)tape[pointer] * (uint64_t)factor);
+    }
+}
+";
+
+/// Helper that sets a cell to a value.
+const SET: &str = "\
+static void set(long long offset, uint64_t value) {
+    tape[at(offset)] = (cell)value;
+}
+";
+
+/// Helper that writes the lowest byte of a cell to the output.
+const OUTPUT: &str = "\
+static void output(long long offset) {
+    putchar((unsigned char)tape[at(offset)]);
+}
+";
+
+/// Beginning of the helper that reads a byte from the input into the
+/// current cell. What happens at the end of the input comes after it.
+const INPUT: &str = "\
+static void input(void) {
+    int byte;
+    fflush(stdout);
+    byte = getchar();
+    if (byte != EOF) {
+        tape[pointer] = (cell)byte;
+        return;
+    }
+";
+
+/// Every helper a program may need.
+#[derive(Default)]
+struct Helpers {
+    at: bool,
+    moves: bool,
+    add: bool,
+    mul_add: bool,
+    set: bool,
+    output: bool,
+    input: bool,
+}
+
+/// Returns the given program as a self-contained C source file that
+/// behaves as the virtual machine would with the given options. Cells are
+/// stored unsigned, since the wrapping arithmetic of signed and unsigned
+/// cells only differs in how values are interpreted, never in the bits
+/// they hold. Fails for growing tapes and for overflow policies other than
+/// wrapping.
+pub(super) fn emit(program: &Program, options: &MachineOptions) -> Result<String, EmitError> {
+    if options.tape_policy == TapePolicy::Grow {
+        return Err(unsupported("growing tapes"));
+    }
+    if options.overflow != OverflowPolicy::Wrap {
+        return Err(unsupported("overflow policies other than wrap"));
+    }
+
+    let mut helpers = Helpers::default();
+    let mut body = Source::new("    ");
+    body.open("int main(void) {");
+    for instruction in program.instructions() {
+        statement(&mut body, &mut helpers, instruction.kind);
+    }
+    body.line("return 0;");
+    body.close("}");
+
+    let mut source = Source::new("    ");
+    source.raw(&prelude(options));
+    for (used, helper) in [
+        (helpers.at, at(options)),
+        (helpers.moves, MOVE),
+        (helpers.add, ADD),
+        (helpers.mul_add, MUL_ADD),
+        (helpers.set, SET),
+        (helpers.output, OUTPUT),
+    ] {
+        if used {
+            source.raw("\n");
+            source.raw(helper);
+        }
+    }
+    if helpers.input {
+        source.raw("\n");
+        source.raw(INPUT);
+        source.raw(end_of_input(options.eof));
+    }
+    source.raw("\n");
+    source.raw(&body.into_text());
+    Ok(source.into_text())
+}
+
+/// Returns the error for an option the C target does not support.
+fn unsupported(option: &'static str) -> EmitError {
+    EmitError::Unsupported {
+        target: Target::C,
+        option,
+    }
+}
+
+/// Returns the beginning of the file: a description of the options it was
+/// emitted for, the includes and the tape.
+fn prelude(options: &MachineOptions) -> String {
+    format!(
+        "/* Generated by brain-rusted: {} cells of type {:?}, {:?} at the tape ends,\n   \
+         {:?} at the end of the input. */\n\
+         #include <stdint.h>\n\
+         #include <stdio.h>\n\
+         #include <stdlib.h>\n\
+         \n\
+         #define TAPE_LENGTH {}LL\n\
+         \n\
+         typedef uint{}_t cell;\n\
+         \n\
+         static cell tape[TAPE_LENGTH];\n\
+         static long long pointer = 0;\n",
+        options.tape_length,
+        options.cell,
+        options.tape_policy,
+        options.eof,
+        options.tape_length,
+        options.cell.bits(),
+    )
+}
+
+/// Returns the helper that resolves cell indexes for the tape policy of the
+/// given options.
+fn at(options: &MachineOptions) -> &'static str {
+    match options.tape_policy {
+        TapePolicy::Error => AT_ERROR,
+        _ => AT_WRAP,
+    }
+}
+
+/// Returns the end of the helper that reads from the input: what it does
+/// at the end of the input, according to the given policy.
+fn end_of_input(eof: EofPolicy) -> &'static str {
+    match eof {
+        EofPolicy::Zero => "    tape[pointer] = 0;\n}\n",
+        EofPolicy::MinusOne => "    tape[pointer] = (cell)-1;\n}\n",
+        EofPolicy::Unchanged => "}\n",
+        EofPolicy::Error => {
+            "    fprintf(stderr, \"Unexpected end of input\\n\");\n    exit(70);\n}\n"
+        }
+    }
+}
+
+/// Writes the statement equivalent to the given instruction to the given
+/// source, taking note of the helpers it needs.
+fn statement(source: &mut Source, helpers: &mut Helpers, kind: InstructionKind) {
+    let line = match kind {
+        InstructionKind::Advance => moves(helpers, 1),
+        InstructionKind::Recede => moves(helpers, -1),
+        InstructionKind::Move(offset) => moves(helpers, offset),
+        InstructionKind::Increment => add(helpers, 0, 1),
+        InstructionKind::Decrement => add(helpers, 0, -1),
+        InstructionKind::Add(amount) => add(helpers, 0, amount),
+        InstructionKind::AddAt { offset, amount } => add(helpers, offset, amount),
+        InstructionKind::Show => output(helpers, 0),
+        InstructionKind::OutputAt { offset } => output(helpers, offset),
+        InstructionKind::Read => {
+            helpers.input = true;
+            "input();".to_string()
+        }
+        InstructionKind::StartLoop => return source.open("while (tape[pointer]) {"),
+        InstructionKind::StopLoop => return source.close("}"),
+        InstructionKind::SetZero => "tape[pointer] = 0;".to_string(),
+        InstructionKind::MulAdd { offset, factor } => {
+            helpers.at = true;
+            helpers.mul_add = true;
+            format!("mul_add({}, {});", offset, literal(factor))
+        }
+        InstructionKind::ScanRight => format!("while (tape[pointer]) {}", moves(helpers, 1)),
+        InstructionKind::ScanLeft => format!("while (tape[pointer]) {}", moves(helpers, -1)),
+        InstructionKind::Print(byte) => format!("putchar({});", byte),
+        InstructionKind::SetAt { offset, value } => {
+            helpers.at = true;
+            helpers.set = true;
+            format!("set({}, UINT64_C({:#x}));", offset, value as u64)
+        }
+    };
+    source.line(&line);
+}
+
+/// Returns the statement that moves the pointer by the given offset.
+fn moves(helpers: &mut Helpers, offset: isize) -> String {
+    helpers.at = true;
+    helpers.moves = true;
+    format!("move({});", offset)
+}
+
+/// Returns the statement that adds the given amount to the cell at the
+/// given offset.
+fn add(helpers: &mut Helpers, offset: isize, amount: i64) -> String {
+    helpers.at = true;
+    helpers.add = true;
+    format!("add({}, {});", offset, literal(amount))
+}
+
+/// Returns the statement that shows the cell at the given offset.
+fn output(helpers: &mut Helpers, offset: isize) -> String {
+    helpers.at = true;
+    helpers.output = true;
+    format!("output({});", offset)
+}
+
+/// Returns the given number as a C literal that fits a long long.
+fn literal(value: i64) -> String {
+    match value {
+        i64::MIN => "(-9223372036854775807LL - 1)".to_string(),
+        _ if i32::try_from(value).is_ok() => value.to_string(),
+        _ => format!("{}LL", value),
+    }
+}
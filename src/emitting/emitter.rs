@@ -0,0 +1,58 @@
+use super::{c, js, llvm, native, python, rust, wasm, EmitError, Target};
+use crate::loading::compiled;
+use crate::parsing::Program;
+use crate::virtual_machine::MachineOptions;
+
+/// Static type that has the emitting logic and serves as a public
+/// interface.
+pub struct Emitter {}
+
+impl Emitter {
+    /// Returns the given program translated to the given target, behaving
+    /// as the virtual machine would with the given options. Fails if the
+    /// target cannot reproduce the behaviour chosen by the options.
+    pub fn emit(
+        program: &Program,
+        target: Target,
+        options: &MachineOptions,
+    ) -> Result<Vec<u8>, EmitError> {
+        let text = match target {
+            Target::C => c::emit(program, options)?,
+            Target::Rust => rust::emit(program, options),
+            Target::Wat => wasm::emit_text(program, options)?,
+            Target::Asm => native::emit_asm(program, options)?,
+            Target::Llvm => llvm::emit(program, options)?,
+            Target::Js => js::emit(program, options),
+            Target::Python => python::emit(program, options),
+            Target::Wasm => return wasm::emit_binary(program, options),
+            Target::Elf => return native::emit_elf(program, options),
+            Target::Bfc => return Ok(compiled::encode(program, options)),
+        };
+        Ok(text.into_bytes())
+    }
+
+    /// Writes the given emitted program to the file at the given path,
+    /// creating it if needed.
+    pub fn save(path: &str, emitted: &[u8]) -> Result<(), EmitError> {
+        std::fs::write(path, emitted).map_err(|source| EmitError::Io {
+            path: path.to_string(),
+            source,
+        })
+    }
+
+    /// Writes the given emitted executable to the file at the given path,
+    /// creating it if needed, and lets everyone run it.
+    pub fn save_executable(path: &str, emitted: &[u8]) -> Result<(), EmitError> {
+        Self::save(path, emitted)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let permissions = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(path, permissions).map_err(|source| EmitError::Io {
+                path: path.to_string(),
+                source,
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use super::Target;
+
+/// Errors that may happen while emitting a program.
+#[derive(Debug)]
+pub enum EmitError {
+    /// The target cannot reproduce the behaviour chosen by the given
+    /// option.
+    Unsupported {
+        target: Target,
+        option: &'static str,
+    },
+    /// The emitted program could not be written to the given path.
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for EmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitError::Unsupported { target, option } => {
+                write!(f, "The {} target does not support {}", target, option)
+            }
+            EmitError::Io { path, .. } => write!(f, "Failed to write file: {}", path),
+        }
+    }
+}
+
+impl std::error::Error for EmitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EmitError::Io { source, .. } => Some(source),
+            EmitError::Unsupported { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,536 @@
+use super::source::Source;
+use crate::parsing::{InstructionKind, Program};
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Range of the cells, when their arithmetic is done with Number.
+const RANGE_NUMBER: &str = "
+const MIN = SIGNED ? -(2 ** (BITS - 1)) : 0;
+const MAX = SIGNED ? 2 ** (BITS - 1) - 1 : 2 ** BITS - 1;
+";
+
+/// Range of the cells, when their arithmetic is done with BigInt.
+const RANGE_BIGINT: &str = "
+const MIN = SIGNED ? -(1n << (BITS - 1n)) : 0n;
+const MAX = SIGNED ? (1n << (BITS - 1n)) - 1n : (1n << BITS) - 1n;
+";
+
+/// Middle of the file, after the range of the cells. It defines the error
+/// thrown when the program fails, and a small machine with the same state
+/// as the virtual machine: a tape, a pointer to the current cell and the
+/// functions that read and write bytes. Cells are kept in a typed array.
+const MACHINE: &str = "
+/** Error thrown when the program fails, with the exit status it ends with. */
+class BrainError extends Error {
+  constructor(message, status) {
+    super(message);
+    this.name = \"BrainError\";
+    this.status = status;
+  }
+}
+
+class Machine {
+  constructor(read, write) {
+    this.tape = new Tape(Math.max(TAPE_LENGTH, 1));
+    this.pointer = 0;
+    this.origin = 0;
+    this.read = read;
+    this.write = write;
+  }
+
+  fail(message, status) {
+    throw new BrainError(message, status);
+  }
+
+  isNonzero() {
+    return this.tape[this.pointer] != 0;
+  }
+
+  advance(offset) {
+    this.pointer = this.at(offset);
+  }
+
+  set(offset, value) {
+    this.store(this.at(offset), value);
+  }
+
+  print(byte) {
+    this.write(byte);
+  }
+";
+
+/// Cell methods of the machine, when their arithmetic is done with Number.
+/// Every result is exact for cells of up to 32 bits, and typed arrays keep
+/// the lowest bits of what is stored in them.
+const CELLS_NUMBER: &str = "
+  add(offset, amount) {
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], amount, 1));
+  }
+
+  mulAdd(offset, factor) {
+    const value = this.tape[this.pointer];
+    if (value === 0) {
+      return;
+    }
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], value, factor));
+  }
+
+  store(index, value) {
+    if (value === null) {
+      this.fail(\"Cell overflow\", 70);
+    }
+    this.tape[index] = value;
+  }
+
+  output(offset) {
+    this.print(this.tape[this.at(offset)] & 255);
+  }
+
+  input() {
+    const byte = this.read();
+    if (byte === null || byte === undefined) {
+      this.endOfInput();
+    } else {
+      this.store(this.pointer, byte);
+    }
+  }
+";
+
+/// Cell methods of the machine, when their arithmetic is done with BigInt,
+/// for 64-bit cells. Every result is exact, and typed arrays keep the
+/// lowest bits of what is stored in them.
+const CELLS_BIGINT: &str = "
+  add(offset, amount) {
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], amount, 1n));
+  }
+
+  mulAdd(offset, factor) {
+    const value = this.tape[this.pointer];
+    if (value === 0n) {
+      return;
+    }
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], value, factor));
+  }
+
+  store(index, value) {
+    if (value === null) {
+      this.fail(\"Cell overflow\", 70);
+    }
+    this.tape[index] = value;
+  }
+
+  output(offset) {
+    this.print(Number(this.tape[this.at(offset)] & 255n));
+  }
+
+  input() {
+    const byte = this.read();
+    if (byte === null || byte === undefined) {
+      this.endOfInput();
+    } else {
+      this.store(this.pointer, BigInt(byte));
+    }
+  }
+";
+
+/// Helper that resolves cell indexes on a tape that wraps around.
+const AT_WRAP: &str = "
+  at(offset) {
+    const length = this.tape.length;
+    return (((this.pointer + offset) % length) + length) % length;
+  }
+";
+
+/// Helper that resolves cell indexes on a tape whose ends cannot be
+/// crossed.
+const AT_ERROR: &str = "
+  at(offset) {
+    const target = this.pointer + offset;
+    if (target < 0 || target >= this.tape.length) {
+      const cell = target < 0 ? -1 : this.tape.length;
+      this.fail(`Pointer moved out of the tape, to cell ${cell}`, 70);
+    }
+    return target;
+  }
+";
+
+/// Helper that resolves cell indexes on a tape that grows on demand, at
+/// least doubling, up to its maximum length.
+const AT_GROW: &str = "
+  at(offset) {
+    const target = this.pointer + offset;
+    if (target < 0) {
+      const extra = this.growth(-target);
+      const tape = new Tape(extra + this.tape.length);
+      tape.set(this.tape, extra);
+      this.tape = tape;
+      this.pointer += extra;
+      this.origin += extra;
+      return this.pointer + offset;
+    }
+    if (target >= this.tape.length) {
+      const extra = this.growth(target + 1 - this.tape.length);
+      const tape = new Tape(this.tape.length + extra);
+      tape.set(this.tape);
+      this.tape = tape;
+    }
+    return target;
+  }
+
+  growth(needed) {
+    const length = this.tape.length;
+    if (MAX_TAPE_LENGTH === null) {
+      return Math.max(needed, length);
+    }
+    if (length + needed > MAX_TAPE_LENGTH) {
+      this.fail(`Tape limit exceeded: ${MAX_TAPE_LENGTH} cells`, 75);
+    }
+    return Math.min(Math.max(needed, length), MAX_TAPE_LENGTH - length);
+  }
+";
+
+/// Cell arithmetic with Number for cells that wrap around. Only the lowest
+/// 32 bits of the result matter, so the product is taken modulo 2^32 and
+/// storing the result keeps the bits of the cell.
+const ADD_WRAP_NUMBER: &str = "
+function add(cell, value, factor) {
+  return (cell + Math.imul(value, factor)) | 0;
+}
+";
+
+/// Cell arithmetic with BigInt for cells that wrap around, which storing
+/// the result already does.
+const ADD_WRAP_BIGINT: &str = "
+function add(cell, value, factor) {
+  return cell + value * factor;
+}
+";
+
+/// Cell arithmetic for cells that stay at the ends of their range. The
+/// same text works with Number and BigInt: with Number, the result is
+/// exact whenever it is in the range of the cells.
+const ADD_SATURATE: &str = "
+function add(cell, value, factor) {
+  const sum = cell + value * factor;
+  return sum < MIN ? MIN : sum > MAX ? MAX : sum;
+}
+";
+
+/// Cell arithmetic for cells that stop the program when they overflow.
+const ADD_TRAP: &str = "
+function add(cell, value, factor) {
+  const sum = cell + value * factor;
+  return sum < MIN || sum > MAX ? null : sum;
+}
+";
+
+/// Documentation of the function that runs the program.
+const RUN: &str = "
+/**
+ * Runs the program. It calls read() whenever it needs a byte of input,
+ * which must return a number from 0 to 255, or null at the end of the
+ * input, and write(byte) for every byte of output. Throws a BrainError
+ * when the program fails.
+ */
+";
+
+/// End of the file. It exports the run function, and runs the program on
+/// the standard streams when the file is run by Node.js.
+const MAIN: &str = "
+if (typeof module !== \"undefined\") {
+  module.exports = { run, BrainError };
+}
+
+if (typeof require !== \"undefined\" && require.main === module) {
+  const fs = require(\"fs\");
+  let output = [];
+  const flush = () => {
+    const bytes = Uint8Array.from(output);
+    let written = 0;
+    while (written < bytes.length) {
+      written += fs.writeSync(1, bytes, written);
+    }
+    output = [];
+  };
+  const read = () => {
+    flush();
+    const byte = new Uint8Array(1);
+    return fs.readSync(0, byte, 0, 1, null) === 1 ? byte[0] : null;
+  };
+  const write = (byte) => {
+    output.push(byte);
+    if (output.length >= 4096) {
+      flush();
+    }
+  };
+  try {
+    run(read, write);
+    flush();
+  } catch (error) {
+    if (error instanceof BrainError) {
+      try {
+        flush();
+      } catch (_) {}
+      console.error(error.message);
+      process.exitCode = error.status;
+    } else {
+      console.error(\"Failed to read input or write output\");
+      process.exitCode = 70;
+    }
+  }
+}
+";
+
+/// Returns the given program as a JavaScript file that behaves as the
+/// virtual machine would with the given options: same tape length and
+/// policy, cell type, overflow policy and EOF policy. The program runs
+/// through a "run(read, write)" function, for browsers and other hosts,
+/// and on the standard streams when the file is run by Node.js, where
+/// runtime errors are written to the standard error and end the process
+/// with the same exit code as the interpreter. The step limit is not
+/// reproduced.
+pub(super) fn emit(program: &Program, options: &MachineOptions) -> String {
+    let mut source = Source::new("  ");
+    source.raw(&format!(
+        "// Generated by brain-rusted: {} cells of type {:?}, {:?} at the tape ends,\n\
+         // {:?} on overflow, {:?} at the end of the input.\n\
+         \"use strict\";\n\n",
+        options.tape_length, options.cell, options.tape_policy, options.overflow, options.eof,
+    ));
+    let numbers = Numbers::of(options);
+    source.raw(&format!(
+        "const TAPE_LENGTH = {};\n\
+         const MAX_TAPE_LENGTH = {};\n\
+         const Tape = {};\n\
+         const BITS = {};\n\
+         const SIGNED = {};\n",
+        options.tape_length,
+        options
+            .max_tape_length
+            .map_or("null".to_string(), |limit| limit.to_string()),
+        tape_type(options),
+        numbers.literal(options.cell.bits() as i64),
+        options.cell.is_signed(),
+    ));
+    source.raw(match numbers {
+        Numbers::BigInt => RANGE_BIGINT,
+        Numbers::Number { .. } => RANGE_NUMBER,
+    });
+    source.raw(MACHINE);
+    source.raw(match numbers {
+        Numbers::BigInt => CELLS_BIGINT,
+        Numbers::Number { .. } => CELLS_NUMBER,
+    });
+    source.raw(match options.tape_policy {
+        TapePolicy::Wrap => AT_WRAP,
+        TapePolicy::Error => AT_ERROR,
+        TapePolicy::Grow => AT_GROW,
+    });
+    source.raw(&format!(
+        "\n  endOfInput() {{\n    {}\n  }}\n}}\n",
+        end_of_input(options.eof, numbers)
+    ));
+    source.raw(match (options.overflow, numbers) {
+        (OverflowPolicy::Wrap, Numbers::BigInt) => ADD_WRAP_BIGINT,
+        (OverflowPolicy::Wrap, Numbers::Number { .. }) => ADD_WRAP_NUMBER,
+        (OverflowPolicy::Saturate, _) => ADD_SATURATE,
+        (OverflowPolicy::Trap, _) => ADD_TRAP,
+    });
+    source.raw(RUN);
+
+    source.open("function run(read, write) {");
+    source.line("const machine = new Machine(read, write);");
+    for instruction in program.instructions() {
+        statement(&mut source, instruction.kind, numbers);
+    }
+    source.close("}");
+    source.raw(MAIN);
+    source.into_text()
+}
+
+/// Returns the name of the typed array that holds the cells of the given
+/// options.
+fn tape_type(options: &MachineOptions) -> String {
+    let bits = options.cell.bits();
+    match (options.cell.is_signed(), bits) {
+        (true, 64) => "BigInt64Array".to_string(),
+        (false, 64) => "BigUint64Array".to_string(),
+        (true, _) => format!("Int{}Array", bits),
+        (false, _) => format!("Uint{}Array", bits),
+    }
+}
+
+/// Returns the body of the method that handles the end of the input,
+/// according to the given policy, for cells with the given arithmetic.
+fn end_of_input(eof: EofPolicy, numbers: Numbers) -> String {
+    match eof {
+        EofPolicy::Zero => format!("this.store(this.pointer, {});", numbers.literal(0)),
+        EofPolicy::MinusOne => format!("this.store(this.pointer, {});", numbers.literal(-1)),
+        EofPolicy::Unchanged => String::new(),
+        EofPolicy::Error => "this.fail(\"Unexpected end of input\", 70);".to_string(),
+    }
+}
+
+/// Writes the statement equivalent to the given instruction to the given
+/// source, for cells with the given arithmetic.
+fn statement(source: &mut Source, kind: InstructionKind, numbers: Numbers) {
+    let line = match kind {
+        InstructionKind::Advance => "machine.advance(1);".to_string(),
+        InstructionKind::Recede => "machine.advance(-1);".to_string(),
+        InstructionKind::Move(offset) => format!("machine.advance({});", offset),
+        InstructionKind::Increment => format!("machine.add(0, {});", numbers.amount(1)),
+        InstructionKind::Decrement => format!("machine.add(0, {});", numbers.amount(-1)),
+        InstructionKind::Add(amount) => format!("machine.add(0, {});", numbers.amount(amount)),
+        InstructionKind::AddAt { offset, amount } => {
+            format!("machine.add({}, {});", offset, numbers.amount(amount))
+        }
+        InstructionKind::Show => "machine.output(0);".to_string(),
+        InstructionKind::OutputAt { offset } => format!("machine.output({});", offset),
+        InstructionKind::Read => "machine.input();".to_string(),
+        InstructionKind::StartLoop => return source.open("while (machine.isNonzero()) {"),
+        InstructionKind::StopLoop => return source.close("}"),
+        InstructionKind::SetZero => format!("machine.set(0, {});", numbers.literal(0)),
+        InstructionKind::MulAdd { offset, factor } => {
+            format!("machine.mulAdd({}, {});", offset, numbers.amount(factor))
+        }
+        InstructionKind::ScanRight => "while (machine.isNonzero()) machine.advance(1);".to_string(),
+        InstructionKind::ScanLeft => "while (machine.isNonzero()) machine.advance(-1);".to_string(),
+        InstructionKind::Print(byte) => format!("machine.print({});", byte),
+        InstructionKind::SetAt { offset, value } => {
+            format!("machine.set({}, {});", offset, numbers.value(value))
+        }
+    };
+    source.line(&line);
+}
+
+/// How the emitted program does the arithmetic of its cells.
+#[derive(Debug, Clone, Copy)]
+enum Numbers {
+    /// With Number, for cells of up to 32 bits. When cells wrap around,
+    /// only the lowest 32 bits of amounts and factors matter.
+    Number { wrap: bool },
+    /// With BigInt, for 64-bit cells, which Number cannot hold exactly.
+    BigInt,
+}
+
+impl Numbers {
+    /// Returns the arithmetic used for the cells of the given options.
+    fn of(options: &MachineOptions) -> Self {
+        match options.cell.bits() {
+            64 => Numbers::BigInt,
+            _ => Numbers::Number {
+                wrap: options.overflow == OverflowPolicy::Wrap,
+            },
+        }
+    }
+
+    /// Returns the given number as a literal.
+    fn literal(self, number: i64) -> String {
+        match self {
+            Numbers::Number { .. } => number.to_string(),
+            Numbers::BigInt => format!("{}n", number),
+        }
+    }
+
+    /// Returns the given amount or factor as a literal.
+    fn amount(self, amount: i64) -> String {
+        match self {
+            Numbers::Number { wrap: true } => self.literal(amount as i32 as i64),
+            _ => self.literal(amount),
+        }
+    }
+
+    /// Returns the given value, which a cell is set to, as a literal.
+    /// Storing it keeps only the bits of the cell, so only the lowest 32
+    /// bits matter with Number.
+    fn value(self, value: i64) -> String {
+        match self {
+            Numbers::Number { .. } => self.literal(value as i32 as i64),
+            Numbers::BigInt => self.literal(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Span;
+    use crate::parsing::Instruction;
+    use crate::virtual_machine::CellType;
+
+    /// Returns the statements emitted for the given instructions with the
+    /// given options.
+    fn statements(kinds: &[InstructionKind], options: &MachineOptions) -> Vec<String> {
+        let span = Span::new(0, 1, 1, 1);
+        let program = Program::new(
+            kinds
+                .iter()
+                .map(|&kind| Instruction::new(kind, span))
+                .collect(),
+        );
+        let emitted = emit(&program, options);
+        let body = emitted
+            .split("const machine = new Machine(read, write);\n")
+            .nth(1)
+            .expect("a run function");
+        let end = body.find("\n}").expect("the end of the run function");
+        body[..end]
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn wrapping_cells_keep_the_lowest_32_bits_of_amounts() {
+        let kinds = [
+            InstructionKind::Add((1 << 40) - 1),
+            InstructionKind::MulAdd {
+                offset: 1,
+                factor: (1 << 32) + 3,
+            },
+            InstructionKind::SetAt {
+                offset: 2,
+                value: (1 << 33) + 65,
+            },
+        ];
+        let options = MachineOptions {
+            cell: CellType::U32,
+            ..MachineOptions::default()
+        };
+
+        assert_eq!(
+            statements(&kinds, &options),
+            [
+                "machine.add(0, -1);",
+                "machine.mulAdd(1, 3);",
+                "machine.set(2, 65);"
+            ]
+        );
+    }
+
+    #[test]
+    fn other_policies_and_wide_cells_keep_exact_amounts() {
+        let kinds = [InstructionKind::Add((1 << 40) - 1)];
+        let saturate = MachineOptions {
+            cell: CellType::U32,
+            overflow: OverflowPolicy::Saturate,
+            ..MachineOptions::default()
+        };
+        let wide = MachineOptions {
+            cell: CellType::I64,
+            ..MachineOptions::default()
+        };
+
+        assert_eq!(
+            statements(&kinds, &saturate),
+            ["machine.add(0, 1099511627775);"]
+        );
+        assert_eq!(
+            statements(&kinds, &wide),
+            ["machine.add(0, 1099511627775n);"]
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! The emitted modules use opaque pointers (`ptr`), so they need LLVM 15
+//! or later; LLVM 14 only reads them with `-opaque-pointers`.
+
+use super::source::Source;
+use super::{EmitError, Target};
+use crate::parsing::{InstructionKind, Program};
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// External functions every module declares: the C functions it reads,
+/// writes and fails with.
+const DECLARATIONS: &str = "\
+declare i32 @getchar()
+declare i32 @putchar(i32)
+declare i32 @fflush(ptr)
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32) noreturn
+";
+
+/// Helper that writes the given message to the standard error, after
+/// flushing the output, and exits with the status of runtime errors.
+const FAIL: &str = "\
+define internal void @fail(ptr %message, i64 %length) noreturn {
+  %flushed = call i32 @fflush(ptr null)
+  %written = call i64 @write(i32 2, ptr %message, i64 %length)
+  call void @exit(i32 70)
+  unreachable
+}
+";
+
+/// Helper that returns the index of the cell at the given distance from the
+/// pointer, on a tape that wraps around.
+const AT_WRAP: &str = "\
+define internal i64 @at(i64 %offset) {
+  %pointer = load i64, ptr @pointer
+  %sum = add i64 %pointer, %offset
+  %remainder = srem i64 %sum, TAPE_LENGTH
+  %negative = icmp slt i64 %remainder, 0
+  %wrapped = add i64 %remainder, TAPE_LENGTH
+  %index = select i1 %negative, i64 %wrapped, i64 %remainder
+  ret i64 %index
+}
+";
+
+/// Helper that returns the index of the cell at the given distance from the
+/// pointer, on a tape whose ends cannot be crossed. Indexes before the tape
+/// are negative, so they are beyond its end when compared unsigned.
+const AT_ERROR: &str = "\
+define internal i64 @at(i64 %offset) {
+entry:
+  %pointer = load i64, ptr @pointer
+  %index = add i64 %pointer, %offset
+  %outside = icmp uge i64 %index, TAPE_LENGTH
+  br i1 %outside, label %fail, label %inside
+fail:
+  call void @fail(ptr @out_of_tape, i64 MESSAGE_LENGTH)
+  unreachable
+inside:
+  ret i64 %index
+}
+";
+
+/// Helper that returns the address of the cell at the given distance from
+/// the pointer.
+const CELL: &str = "\
+define internal ptr @cell(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  %cell = getelementptr inbounds [TAPE_LENGTH x CELL], ptr @tape, i64 0, i64 %index
+  ret ptr %cell
+}
+";
+
+/// Helper that returns whether or not the current cell is not 0.
+const NONZERO: &str = "\
+define internal i1 @nonzero() {
+  %pointer = load i64, ptr @pointer
+  %cell = getelementptr inbounds [TAPE_LENGTH x CELL], ptr @tape, i64 0, i64 %pointer
+  %value = load CELL, ptr %cell
+  %nonzero = icmp ne CELL %value, 0
+  ret i1 %nonzero
+}
+";
+
+/// Helper that moves the pointer.
+const MOVE: &str = "\
+define internal void @move(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  store i64 %index, ptr @pointer
+  ret void
+}
+";
+
+/// Helper that adds an amount to a cell, wrapping around.
+const ADD: &str = "\
+define internal void @add(i64 %offset, CELL %amount) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load CELL, ptr %cell
+  %sum = add CELL %value, %amount
+  store CELL %sum, ptr %cell
+  ret void
+}
+";
+
+/// Helper that adds the current cell, multiplied by a factor, to another
+/// cell, wrapping around, unless the current cell is 0.
+const MUL_ADD: &str = "\
+define internal void @mul_add(i64 %offset, CELL %factor) {
+entry:
+  %current = call ptr @cell(i64 0)
+  %value = load CELL, ptr %current
+  %zero = icmp eq CELL %value, 0
+  br i1 %zero, label %done, label %add
+add:
+  %cell = call ptr @cell(i64 %offset)
+  %old = load CELL, ptr %cell
+  %product = mul CELL %value, %factor
+  %sum = add CELL %old, %product
+  store CELL %sum, ptr %cell
+  br label %done
+done:
+  ret void
+}
+";
+
+/// Helper that sets a cell to a value.
+const SET: &str = "\
+define internal void @set(i64 %offset, CELL %value) {
+  %cell = call ptr @cell(i64 %offset)
+  store CELL %value, ptr %cell
+  ret void
+}
+";
+
+/// Helper that writes the lowest byte of a cell to the output.
+const OUTPUT: &str = "\
+define internal void @output(i64 %offset) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load CELL, ptr %cell
+  %byte = CONVERT_TO_BYTE
+  %written = call i32 @putchar(i32 %byte)
+  ret void
+}
+";
+
+/// Beginning of the helper that reads a byte from the input into the
+/// current cell. What happens at the end of the input comes after it.
+const INPUT: &str = "\
+define internal void @input() {
+entry:
+  %flushed = call i32 @fflush(ptr null)
+  %byte = call i32 @getchar()
+  %cell = call ptr @cell(i64 0)
+  %end = icmp slt i32 %byte, 0
+  br i1 %end, label %eof, label %read
+read:
+  STORE_BYTE
+  ret void
+eof:
+";
+
+/// Message written when the pointer moves out of the tape.
+const OUT_OF_TAPE: &str = "Pointer moved out of the tape\n";
+
+/// Message written at the end of the input, when that is an error.
+const END_OF_INPUT: &str = "Unexpected end of input\n";
+
+/// Helpers a module needs, found while writing its main function.
+#[derive(Default)]
+struct Helpers {
+    moves: bool,
+    add: bool,
+    mul_add: bool,
+    set: bool,
+    output: bool,
+    input: bool,
+    nonzero: bool,
+}
+
+impl Helpers {
+    /// Returns whether or not any helper that finds cells is needed.
+    fn cell(&self) -> bool {
+        self.add || self.mul_add || self.set || self.output || self.input
+    }
+}
+
+/// Returns the given program as an LLVM IR module, in the textual format,
+/// that behaves as the virtual machine would with the given options. The
+/// tape is a global array, the pointer a global index, and the input and
+/// output go through "getchar" and "putchar". Cells are integers as wide as
+/// their type, since signed and unsigned wrapping arithmetic only differ in
+/// how values are interpreted. Fails for growing tapes and for overflow
+/// policies other than wrapping.
+pub(super) fn emit(program: &Program, options: &MachineOptions) -> Result<String, EmitError> {
+    if options.tape_policy == TapePolicy::Grow {
+        return Err(unsupported("growing tapes"));
+    }
+    if options.overflow != OverflowPolicy::Wrap {
+        return Err(unsupported("overflow policies other than wrap"));
+    }
+
+    let cell = format!("i{}", options.cell.bits());
+    let mut helpers = Helpers::default();
+    let mut body = Body {
+        source: Source::new("  "),
+        cell: &cell,
+        loops: vec![],
+        blocks: 0,
+    };
+    body.source.open("define i32 @main() {");
+    body.source.raw("entry:\n");
+    for instruction in program.instructions() {
+        body.statement(&mut helpers, instruction.kind);
+    }
+    body.source.line("ret i32 0");
+    body.source.close("}");
+
+    let used = helpers.moves || helpers.cell();
+    let out_of_tape = used && options.tape_policy == TapePolicy::Error;
+    let end_of_input = helpers.input && options.eof == EofPolicy::Error;
+    let mut source = Source::new("  ");
+    source.raw(&prelude(options, &cell));
+    if out_of_tape {
+        source.raw(&message("out_of_tape", OUT_OF_TAPE));
+    }
+    if end_of_input {
+        source.raw(&message("end_of_input", END_OF_INPUT));
+    }
+    source.raw("\n");
+    source.raw(DECLARATIONS);
+    for (needed, helper) in [
+        (out_of_tape || end_of_input, FAIL.to_string()),
+        (used, at(options.tape_policy)),
+        (helpers.cell(), CELL.to_string()),
+        (helpers.nonzero, NONZERO.to_string()),
+        (helpers.moves, MOVE.to_string()),
+        (helpers.add, ADD.to_string()),
+        (helpers.mul_add, MUL_ADD.to_string()),
+        (helpers.set, SET.to_string()),
+        (helpers.output, output(options.cell.bits())),
+        (helpers.input, input(options.cell.bits(), options.eof)),
+    ] {
+        if needed {
+            source.raw("\n");
+            source.raw(&helper);
+        }
+    }
+    source.raw("\n");
+    source.raw(&body.source.into_text());
+    // The helpers stand for the length of the tape and the type of a cell
+    // with placeholders, filled in once the whole module is written.
+    Ok(source
+        .into_text()
+        .replace("TAPE_LENGTH", &options.tape_length.to_string())
+        .replace("CELL", &cell))
+}
+
+/// Returns the error for an option the LLVM target does not support.
+fn unsupported(option: &'static str) -> EmitError {
+    EmitError::Unsupported {
+        target: Target::Llvm,
+        option,
+    }
+}
+
+/// Returns the beginning of the module: a description of the options it
+/// was emitted for, the tape and the pointer.
+fn prelude(options: &MachineOptions, cell: &str) -> String {
+    format!(
+        "; Generated by brain-rusted: {} cells of type {:?}, {:?} at the tape ends,\n\
+         ; {:?} at the end of the input.\n\
+         \n\
+         @tape = internal global [{} x {}] zeroinitializer\n\
+         @pointer = internal global i64 0\n",
+        options.tape_length,
+        options.cell,
+        options.tape_policy,
+        options.eof,
+        options.tape_length,
+        cell,
+    )
+}
+
+/// Returns the global constant with the given name that holds the given
+/// message.
+fn message(name: &str, text: &str) -> String {
+    let escaped: String = text
+        .bytes()
+        .map(|byte| match byte {
+            b'"' | b'\\' | b'\n' => format!("\\{:02X}", byte),
+            _ => (byte as char).to_string(),
+        })
+        .collect();
+    format!(
+        "@{} = private constant [{} x i8] c\"{}\"\n",
+        name,
+        text.len(),
+        escaped
+    )
+}
+
+/// Returns the helper that resolves cell indexes for the given tape
+/// policy.
+fn at(policy: TapePolicy) -> String {
+    match policy {
+        TapePolicy::Error => AT_ERROR.replace("MESSAGE_LENGTH", &OUT_OF_TAPE.len().to_string()),
+        _ => AT_WRAP.to_string(),
+    }
+}
+
+/// Returns the helper that writes a cell of the given width, in bits.
+fn output(bits: u32) -> String {
+    let byte = match bits {
+        8 => "%byte = zext i8 %value to i32".to_string(),
+        32 => "%byte = and i32 %value, 255".to_string(),
+        bits => format!(
+            "%wide = {} i{} %value to i32\n  %byte = and i32 %wide, 255",
+            if bits < 32 { "zext" } else { "trunc" },
+            bits
+        ),
+    };
+    OUTPUT.replace("%byte = CONVERT_TO_BYTE", &byte)
+}
+
+/// Returns the helper that reads into a cell of the given width, in bits,
+/// ending with what it does at the end of the input, according to the
+/// given policy.
+fn input(bits: u32, eof: EofPolicy) -> String {
+    let store = match bits {
+        32 => "store i32 %byte, ptr %cell".to_string(),
+        bits => format!(
+            "%value = {} i32 %byte to i{}\n  store i{} %value, ptr %cell",
+            if bits < 32 { "trunc" } else { "zext" },
+            bits,
+            bits
+        ),
+    };
+    let end = match eof {
+        EofPolicy::Zero => "  store CELL 0, ptr %cell\n  ret void\n".to_string(),
+        EofPolicy::MinusOne => "  store CELL -1, ptr %cell\n  ret void\n".to_string(),
+        EofPolicy::Unchanged => "  ret void\n".to_string(),
+        EofPolicy::Error => format!(
+            "  call void @fail(ptr @end_of_input, i64 {})\n  unreachable\n",
+            END_OF_INPUT.len()
+        ),
+    };
+    format!("{}{}}}\n", INPUT.replace("STORE_BYTE", &store), end)
+}
+
+/// Main function of a module in progress.
+struct Body<'c> {
+    source: Source,
+    /// Type of a cell.
+    cell: &'c str,
+    /// Number of every loop open.
+    loops: Vec<usize>,
+    /// Number of loops opened so far, which names their blocks and values.
+    blocks: usize,
+}
+
+impl Body<'_> {
+    /// Writes the code equivalent to the given instruction, taking note of
+    /// the helpers it needs.
+    fn statement(&mut self, helpers: &mut Helpers, kind: InstructionKind) {
+        match kind {
+            InstructionKind::Advance => self.moves(helpers, 1),
+            InstructionKind::Recede => self.moves(helpers, -1),
+            InstructionKind::Move(offset) => self.moves(helpers, offset),
+            InstructionKind::Increment => self.add(helpers, 0, 1),
+            InstructionKind::Decrement => self.add(helpers, 0, -1),
+            InstructionKind::Add(amount) => self.add(helpers, 0, amount),
+            InstructionKind::AddAt { offset, amount } => self.add(helpers, offset, amount),
+            InstructionKind::Show => self.output(helpers, 0),
+            InstructionKind::OutputAt { offset } => self.output(helpers, offset),
+            InstructionKind::Read => {
+                helpers.input = true;
+                self.source.line("call void @input()");
+            }
+            InstructionKind::StartLoop => self.open(helpers),
+            InstructionKind::StopLoop => self.close(),
+            InstructionKind::SetZero => self.set(helpers, 0, 0),
+            InstructionKind::MulAdd { offset, factor } => {
+                helpers.mul_add = true;
+                self.source.line(&format!(
+                    "call void @mul_add(i64 {}, {} {})",
+                    offset,
+                    self.cell,
+                    truncated(self.cell, factor)
+                ));
+            }
+            InstructionKind::ScanRight => self.scan(helpers, 1),
+            InstructionKind::ScanLeft => self.scan(helpers, -1),
+            InstructionKind::Print(byte) => self
+                .source
+                .line(&format!("call i32 @putchar(i32 {})", byte)),
+            InstructionKind::SetAt { offset, value } => self.set(helpers, offset, value),
+        }
+    }
+
+    /// Writes the start of a loop: the block that checks the current cell
+    /// and the block of its body.
+    fn open(&mut self, helpers: &mut Helpers) {
+        helpers.nonzero = true;
+        let block = self.blocks;
+        self.blocks += 1;
+        self.loops.push(block);
+        self.source.line(&format!("br label %loop{}", block));
+        self.source.raw(&format!("loop{}:\n", block));
+        self.source
+            .line(&format!("%nonzero{} = call i1 @nonzero()", block));
+        self.source.line(&format!(
+            "br i1 %nonzero{}, label %body{}, label %end{}",
+            block, block, block
+        ));
+        self.source.raw(&format!("body{}:\n", block));
+    }
+
+    /// Writes the end of the innermost loop open.
+    fn close(&mut self) {
+        if let Some(block) = self.loops.pop() {
+            self.source.line(&format!("br label %loop{}", block));
+            self.source.raw(&format!("end{}:\n", block));
+        }
+    }
+
+    /// Writes the code that moves the pointer by the given offset.
+    fn moves(&mut self, helpers: &mut Helpers, offset: isize) {
+        helpers.moves = true;
+        self.source
+            .line(&format!("call void @move(i64 {})", offset));
+    }
+
+    /// Writes the code that adds the given amount to the cell at the given
+    /// offset.
+    fn add(&mut self, helpers: &mut Helpers, offset: isize, amount: i64) {
+        helpers.add = true;
+        self.source.line(&format!(
+            "call void @add(i64 {}, {} {})",
+            offset,
+            self.cell,
+            truncated(self.cell, amount)
+        ));
+    }
+
+    /// Writes the code that sets the cell at the given offset to the given
+    /// value.
+    fn set(&mut self, helpers: &mut Helpers, offset: isize, value: i64) {
+        helpers.set = true;
+        self.source.line(&format!(
+            "call void @set(i64 {}, {} {})",
+            offset,
+            self.cell,
+            truncated(self.cell, value)
+        ));
+    }
+
+    /// Writes the code that shows the cell at the given offset.
+    fn output(&mut self, helpers: &mut Helpers, offset: isize) {
+        helpers.output = true;
+        self.source
+            .line(&format!("call void @output(i64 {})", offset));
+    }
+
+    /// Writes the loop that moves the pointer by the given offset until the
+    /// current cell is 0.
+    fn scan(&mut self, helpers: &mut Helpers, offset: isize) {
+        self.open(helpers);
+        self.moves(helpers, offset);
+        self.close();
+    }
+}
+
+/// Returns the given value truncated to the given integer type, as a
+/// signed literal that fits it.
+fn truncated(cell: &str, value: i64) -> i64 {
+    match cell {
+        "i8" => value as i8 as i64,
+        "i16" => value as i16 as i64,
+        "i32" => value as i32 as i64,
+        _ => value,
+    }
+}
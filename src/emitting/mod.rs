@@ -0,0 +1,47 @@
+//! The emitting module encapsulates the translation of a parsed (and
+//! usually optimized) program into other languages, so it can be built and
+//! run without the interpreter. Every backend reproduces the behaviour the
+//! virtual machine would have with the same options, or refuses to emit a
+//! program for options it cannot reproduce.
+
+/// This module defines the C backend.
+mod c;
+
+/// This module defines the Emitter type, which interfaces with every
+/// backend.
+pub mod emitter;
+
+/// This module defines the errors for problems that occur while emitting a
+/// program. These errors are suitable for display by the program.
+mod errors;
+
+/// This module defines the JavaScript backend.
+mod js;
+
+/// This module defines the LLVM IR backend.
+mod llvm;
+
+/// This module defines the native backends: ELF executables and their
+/// assembly listings.
+mod native;
+
+/// This module defines the Python backend.
+mod python;
+
+/// This module defines the Rust backend.
+mod rust;
+
+/// This module defines the Source type, which helps backends build the text
+/// of a source file.
+mod source;
+
+/// This module defines the Target type, which names each backend.
+pub mod target;
+
+/// This module defines the WebAssembly backend, for both the text and the
+/// binary formats.
+mod wasm;
+
+pub use emitter::Emitter;
+pub use errors::EmitError;
+pub use target::Target;
@@ -0,0 +1,30 @@
+use super::runtime::Area;
+use crate::virtual_machine::MachineOptions;
+
+/// Returns the assembly file with the given listing, emitted for the given
+/// options, whose tape has the given size in bytes. Its memory is left to
+/// the linker, in the ".bss" section.
+pub(super) fn write(listing: &str, tape_size: u64, options: &MachineOptions) -> String {
+    let mut text = format!(
+        "# Generated by brain-rusted: {} cells of type {:?}, {:?} at the tape ends,\n\
+         # {:?} at the end of the input. Every instruction of the program is\n\
+         # preceded by a comment naming it and where it came from.\n\
+         # Build with: as -o program.o program.s && ld -o program program.o\n\
+         \t.intel_syntax noprefix\n\
+         \t.section .note.GNU-stack, \"\", @progbits\n\
+         \t.text\n\
+         \t.globl _start\n\
+         _start:\n",
+        options.tape_length, options.cell, options.tape_policy, options.eof,
+    );
+    text.push_str(listing);
+    text.push_str("\t.bss\n\t.balign 64\n");
+    for area in Area::ALL {
+        text.push_str(&format!(
+            "{}:\n\t.zero {}\n",
+            area.symbol(),
+            area.size(tape_size)
+        ));
+    }
+    text
+}
@@ -0,0 +1,74 @@
+use super::runtime::{Area, DATA};
+
+/// Address the file is loaded at. The headers come first, then the code.
+const TEXT: u64 = 0x40_0000;
+
+/// Sizes, in bytes, of the ELF header and of a program header.
+const ELF_HEADER_SIZE: u64 = 64;
+const PROGRAM_HEADER_SIZE: u64 = 56;
+
+/// Number of program headers: the code, the memory written to and the
+/// stack, which is not executable.
+const PROGRAM_HEADERS: u64 = 3;
+
+/// Address of the first instruction.
+const ENTRY: u64 = TEXT + ELF_HEADER_SIZE + PROGRAM_HEADERS * PROGRAM_HEADER_SIZE;
+
+/// Returns the executable that runs the given machine code, whose tape has
+/// the given size in bytes.
+pub(super) fn write(code: &[u8], tape_size: u64) -> Vec<u8> {
+    let memory = Area::ALL.iter().map(|area| area.size(tape_size)).sum();
+    let mut file = header(code.len() as u64, memory);
+    file.extend_from_slice(code);
+    file
+}
+
+/// Returns the ELF header and the program headers of an executable with
+/// the given amount of code, in bytes, and of memory written to.
+fn header(code: u64, memory: u64) -> Vec<u8> {
+    let size = ENTRY - TEXT + code;
+    let mut bytes = vec![];
+    // Identification: 64-bit, little endian, current version, System V.
+    bytes.extend_from_slice(&[0x7F, b'E', b'L', b'F', 2, 1, 1, 0]);
+    bytes.extend_from_slice(&[0; 8]);
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // Executable file.
+    bytes.extend_from_slice(&0x3Eu16.to_le_bytes()); // x86-64.
+    bytes.extend_from_slice(&1u32.to_le_bytes()); // Current version.
+    bytes.extend_from_slice(&ENTRY.to_le_bytes());
+    bytes.extend_from_slice(&ELF_HEADER_SIZE.to_le_bytes()); // Program headers.
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // No section headers.
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // No flags.
+    bytes.extend_from_slice(&(ELF_HEADER_SIZE as u16).to_le_bytes());
+    bytes.extend_from_slice(&(PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+    bytes.extend_from_slice(&(PROGRAM_HEADERS as u16).to_le_bytes());
+    bytes.extend_from_slice(&[0; 6]); // No section headers nor names.
+
+    // The whole file, readable and executable.
+    program_header(&mut bytes, 1, 0b101, TEXT, size, size);
+    // The memory written to, readable and writable, all zeroes.
+    program_header(&mut bytes, 1, 0b110, DATA, 0, memory);
+    // The stack, readable and writable but not executable.
+    program_header(&mut bytes, 0x6474_E551, 0b110, 0, 0, 0);
+    bytes
+}
+
+/// Writes a program header of the given type and permissions, for a
+/// segment at the given address with the given sizes in the file and in
+/// memory. Loaded segments start at the beginning of the file.
+fn program_header(
+    bytes: &mut Vec<u8>,
+    kind: u32,
+    flags: u32,
+    address: u64,
+    file: u64,
+    memory: u64,
+) {
+    bytes.extend_from_slice(&kind.to_le_bytes());
+    bytes.extend_from_slice(&flags.to_le_bytes());
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // Offset in the file.
+    bytes.extend_from_slice(&address.to_le_bytes()); // Virtual address.
+    bytes.extend_from_slice(&address.to_le_bytes()); // Physical address.
+    bytes.extend_from_slice(&file.to_le_bytes());
+    bytes.extend_from_slice(&memory.to_le_bytes());
+    bytes.extend_from_slice(&0x1000u64.to_le_bytes()); // Alignment.
+}
@@ -0,0 +1,36 @@
+//! The native backends, which translate a program into x86-64 machine code
+//! for Linux with the same code the JIT compiler runs. The program makes
+//! its own system calls, so it needs neither a C library nor a linker. The
+//! ELF backend writes that machine code as an executable, and the assembly
+//! backend writes its listing, with the source of every instruction, for
+//! the GNU assembler.
+
+/// This module writes the listing of a program as an assembly file.
+mod asm;
+
+/// This module writes the machine code of a program as an ELF executable.
+mod elf;
+
+/// This module defines the runtime of the native programs, which reads
+/// and writes with system calls.
+mod runtime;
+
+use super::{EmitError, Target};
+use crate::assembling::Assembler;
+use crate::parsing::Program;
+use crate::virtual_machine::MachineOptions;
+
+/// Returns the given program as a static Linux x86-64 executable.
+pub(super) fn emit_elf(program: &Program, options: &MachineOptions) -> Result<Vec<u8>, EmitError> {
+    let mut asm = Assembler::new((options.cell.bits() / 8) as u8);
+    let tape_size = runtime::translate(program, options, Target::Elf, &mut asm)?;
+    Ok(elf::write(&asm.finish(), tape_size))
+}
+
+/// Returns the given program as an assembly file for the GNU assembler,
+/// which builds the same executable as the ELF backend writes.
+pub(super) fn emit_asm(program: &Program, options: &MachineOptions) -> Result<String, EmitError> {
+    let mut asm = Assembler::with_listing((options.cell.bits() / 8) as u8);
+    let tape_size = runtime::translate(program, options, Target::Asm, &mut asm)?;
+    Ok(asm::write(&asm.into_listing(), tape_size, options))
+}
@@ -0,0 +1,339 @@
+use crate::assembling::{
+    self, Assembler, Condition, Label, Memory, Reg, Runtime, BASE, LENGTH, POSITION,
+};
+use crate::emitting::{EmitError, Target};
+use crate::parsing::Program;
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Address of the memory the program writes to, which the executable does
+/// not store: the output buffer, then the input byte, then the tape. The
+/// ELF backend places it here, and the listing leaves it to the linker.
+pub(super) const DATA: u64 = 0x1_0000_0000;
+
+/// Size of the output buffer, in bytes. It is written out when it is full,
+/// before reading and before exiting.
+const BUFFER_SIZE: u64 = 4096;
+
+/// Size of the room for the byte read from the input, which keeps the tape
+/// aligned for every cell size.
+const INPUT_SIZE: u64 = 64;
+
+/// Largest tape, in bytes.
+const MAX_TAPE: u64 = 1 << 32;
+
+/// Numbers of the Linux system calls the program makes.
+const READ: i64 = 0;
+const WRITE: i64 = 1;
+const EXIT: i64 = 60;
+
+/// Exit status of runtime errors, as the interpreter uses.
+const RUNTIME_STATUS: i64 = 70;
+
+/// Message written when the program cannot read or write.
+const IO_FAILURE: &str = "Failed to read input or write output\n";
+
+/// Register that holds the address where the next output byte goes.
+const CURSOR: Reg = Reg::R15;
+
+/// Pieces of the memory the program writes to, in the order they follow
+/// each other from DATA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Area {
+    Buffer,
+    Input,
+    Tape,
+}
+
+impl Area {
+    /// Every area, in order.
+    pub(super) const ALL: [Area; 3] = [Area::Buffer, Area::Input, Area::Tape];
+
+    /// Returns the symbol that names this area in the listing.
+    pub(super) fn symbol(&self) -> &'static str {
+        match self {
+            Area::Buffer => "buffer",
+            Area::Input => "input",
+            Area::Tape => "tape",
+        }
+    }
+
+    /// Returns the size of this area, in bytes, for a tape of the given
+    /// size.
+    pub(super) fn size(&self, tape_size: u64) -> u64 {
+        match self {
+            Area::Buffer => BUFFER_SIZE,
+            Area::Input => INPUT_SIZE,
+            Area::Tape => tape_size,
+        }
+    }
+
+    /// Returns the address of this area.
+    fn address(&self) -> u64 {
+        let before = Self::ALL.iter().take_while(|area| *area != self);
+        DATA + before.map(|area| area.size(0)).sum::<u64>()
+    }
+
+    /// Writes the code that loads the address at the given distance from
+    /// this area into the given register.
+    fn load(&self, asm: &mut Assembler, destination: Reg, offset: u64) {
+        match offset {
+            0 => asm.mov_address(destination, self.address(), self.symbol()),
+            _ => asm.mov_address(
+                destination,
+                self.address() + offset,
+                &format!("{} + {}", self.symbol(), offset),
+            ),
+        }
+    }
+}
+
+/// Writes, with the given assembler, the code of a static Linux x86-64
+/// program that behaves as the virtual machine would with the given
+/// options, and returns the size of its tape in bytes. It makes its own
+/// system calls, so it needs neither a C library nor a linker. Fails, on
+/// behalf of the given target, for growing tapes, for overflow policies
+/// other than wrapping and for tapes larger than 4 GiB.
+pub(super) fn translate(
+    program: &Program,
+    options: &MachineOptions,
+    target: Target,
+    asm: &mut Assembler,
+) -> Result<u64, EmitError> {
+    let unsupported = |option| EmitError::Unsupported { target, option };
+    if options.tape_policy == TapePolicy::Grow {
+        return Err(unsupported("growing tapes"));
+    }
+    if options.overflow != OverflowPolicy::Wrap {
+        return Err(unsupported("overflow policies other than wrap"));
+    }
+    let cell_size = (options.cell.bits() / 8) as u8;
+    let tape_size = (options.tape_length as u64)
+        .checked_mul(cell_size as u64)
+        .filter(|size| *size <= MAX_TAPE)
+        .ok_or_else(|| unsupported("tapes larger than 4 GiB"))?;
+
+    let mut runtime = Syscalls {
+        program,
+        length: options.tape_length,
+        cell_size,
+        eof: options.eof,
+        routines: Routines::default(),
+        failures: vec![],
+    };
+    assembling::translate(
+        program,
+        asm,
+        &mut runtime,
+        options.tape_policy,
+        options.tape_length,
+    );
+    Ok(tape_size)
+}
+
+/// Labels of the routines every part of the program calls or jumps to,
+/// written after it.
+#[derive(Default)]
+struct Routines {
+    /// Appends the byte in RSI to the output buffer.
+    output: Option<Label>,
+    /// Writes the output buffer out.
+    flush: Option<Label>,
+    /// Reads a byte into the cell whose address is in RSI. Returns 1 in RAX
+    /// at the end of the input, when that is an error, or 0.
+    input: Option<Label>,
+    /// Fails because the program could not read or write.
+    io_failure: Option<Label>,
+    /// Writes the message whose address is in RSI and whose length is in
+    /// RDX to the standard error, and exits with the status of runtime
+    /// errors.
+    fail: Option<Label>,
+}
+
+/// Runtime of the executable: the tape is at a fixed address, output goes
+/// through a buffer, and everything else is a system call.
+struct Syscalls<'p> {
+    /// Program being translated, whose spans go in the error messages.
+    program: &'p Program,
+    /// Number of cells of the tape.
+    length: usize,
+    /// Size of a cell, in bytes.
+    cell_size: u8,
+    /// What happens at the end of the input.
+    eof: EofPolicy,
+    routines: Routines,
+    /// Label of the code that fails for every error the program checks for,
+    /// and the message it writes.
+    failures: Vec<(Label, String)>,
+}
+
+impl Syscalls<'_> {
+    /// Returns the label of the given routine, created the first time it is
+    /// asked for.
+    fn routine(asm: &mut Assembler, routine: &mut Option<Label>) -> Label {
+        *routine.get_or_insert_with(|| asm.label())
+    }
+
+    /// Returns the label of the code that fails with the given message,
+    /// followed by the position of the instruction at the given index.
+    fn failure(&mut self, asm: &mut Assembler, index: usize, message: &str) -> Label {
+        let span = &self.program.instructions()[index].span;
+        let label = asm.label();
+        self.failures.push((
+            label,
+            format!(
+                "{} at line {}, column {}\n",
+                message, span.line, span.column
+            ),
+        ));
+        label
+    }
+
+    /// Writes the code of the routines asked for, and of the routines they
+    /// call.
+    fn routines(&mut self, asm: &mut Assembler) {
+        if let Some(output) = self.routines.output {
+            let flush = Self::routine(asm, &mut self.routines.flush);
+            asm.bind(output);
+            asm.store(1, Memory::at(CURSOR), Reg::Rsi);
+            asm.add_immediate(CURSOR, 1);
+            Area::Buffer.load(asm, Reg::Rax, BUFFER_SIZE);
+            asm.cmp(CURSOR, Reg::Rax);
+            asm.jump_if(Condition::AboveOrEqual, flush);
+            asm.ret();
+        }
+        if let Some(input) = self.routines.input {
+            let flush = Self::routine(asm, &mut self.routines.flush);
+            let io_failure = Self::routine(asm, &mut self.routines.io_failure);
+            let end = asm.label();
+            asm.bind(input);
+            asm.mov(Reg::R8, Reg::Rsi);
+            asm.call_label(flush);
+            asm.mov_immediate(Reg::Rax, READ);
+            asm.mov_immediate(Reg::Rdi, 0);
+            Area::Input.load(asm, Reg::Rsi, 0);
+            asm.mov_immediate(Reg::Rdx, 1);
+            asm.syscall();
+            asm.test(Reg::Rax, Reg::Rax);
+            asm.jump_if(Condition::Less, io_failure);
+            asm.jump_if(Condition::Zero, end);
+            asm.load_byte(Reg::Rax, Memory::at(Reg::Rsi));
+            asm.store(self.cell_size, Memory::at(Reg::R8), Reg::Rax);
+            asm.mov_immediate(Reg::Rax, 0);
+            asm.ret();
+            asm.bind(end);
+            match self.eof {
+                EofPolicy::Zero => asm.store(self.cell_size, Memory::at(Reg::R8), Reg::Rax),
+                EofPolicy::MinusOne => {
+                    asm.mov_immediate(Reg::Rax, -1);
+                    asm.store(self.cell_size, Memory::at(Reg::R8), Reg::Rax);
+                    asm.mov_immediate(Reg::Rax, 0);
+                }
+                EofPolicy::Unchanged => {}
+                EofPolicy::Error => asm.mov_immediate(Reg::Rax, 1),
+            }
+            asm.ret();
+        }
+        if let Some(flush) = self.routines.flush {
+            let io_failure = Self::routine(asm, &mut self.routines.io_failure);
+            let (write, done) = (asm.label(), asm.label());
+            asm.bind(flush);
+            Area::Buffer.load(asm, Reg::Rsi, 0);
+            // Writes may be partial, so they are repeated until the whole
+            // buffer is out.
+            asm.bind(write);
+            asm.mov(Reg::Rdx, CURSOR);
+            asm.sub(Reg::Rdx, Reg::Rsi);
+            asm.jump_if(Condition::Zero, done);
+            asm.mov_immediate(Reg::Rax, WRITE);
+            asm.mov_immediate(Reg::Rdi, 1);
+            asm.syscall();
+            asm.test(Reg::Rax, Reg::Rax);
+            asm.jump_if(Condition::LessOrEqual, io_failure);
+            asm.add(Reg::Rsi, Reg::Rax);
+            asm.jump(write);
+            asm.bind(done);
+            Area::Buffer.load(asm, CURSOR, 0);
+            asm.ret();
+        }
+        if let Some(io_failure) = self.routines.io_failure {
+            let fail = Self::routine(asm, &mut self.routines.fail);
+            let message = asm.label();
+            asm.bind(io_failure);
+            asm.address_of(Reg::Rsi, message);
+            asm.mov_immediate(Reg::Rdx, IO_FAILURE.len() as i64);
+            asm.jump(fail);
+            self.failures.push((message, IO_FAILURE.to_string()));
+        }
+        if let Some(fail) = self.routines.fail {
+            asm.bind(fail);
+            asm.mov_immediate(Reg::Rax, WRITE);
+            asm.mov_immediate(Reg::Rdi, 2);
+            asm.syscall();
+            exit(asm, RUNTIME_STATUS);
+        }
+    }
+}
+
+/// Writes the code that exits with the given status.
+fn exit(asm: &mut Assembler, status: i64) {
+    asm.mov_immediate(Reg::Rax, EXIT);
+    asm.mov_immediate(Reg::Rdi, status);
+    asm.syscall();
+}
+
+impl Runtime for Syscalls<'_> {
+    /// Writes the code that points the registers at the tape and at the
+    /// output buffer. The stack is already set up by the kernel.
+    fn prologue(&mut self, asm: &mut Assembler) {
+        Area::Tape.load(asm, BASE, 0);
+        asm.mov_immediate(POSITION, 0);
+        asm.mov_immediate(LENGTH, self.length as i64);
+        Area::Buffer.load(asm, CURSOR, 0);
+    }
+
+    fn output(&mut self, asm: &mut Assembler, _index: usize) {
+        let output = Self::routine(asm, &mut self.routines.output);
+        asm.call_label(output);
+    }
+
+    fn input(&mut self, asm: &mut Assembler, index: usize) {
+        let input = Self::routine(asm, &mut self.routines.input);
+        asm.call_label(input);
+        if self.eof == EofPolicy::Error {
+            let failure = self.failure(asm, index, "Unexpected end of input");
+            asm.test(Reg::Rax, Reg::Rax);
+            asm.jump_if(Condition::NotZero, failure);
+        }
+    }
+
+    fn out_of_tape(&mut self, asm: &mut Assembler, index: usize) -> Label {
+        self.failure(asm, index, "Pointer moved out of the tape")
+    }
+
+    /// Writes the code that writes the output out and exits successfully,
+    /// followed by the code of every failure, the routines and the
+    /// messages.
+    fn epilogue(&mut self, asm: &mut Assembler) {
+        let flush = Self::routine(asm, &mut self.routines.flush);
+        asm.call_label(flush);
+        exit(asm, 0);
+
+        let mut messages = vec![];
+        for (label, message) in std::mem::take(&mut self.failures) {
+            let fail = Self::routine(asm, &mut self.routines.fail);
+            let text = asm.label();
+            asm.bind(label);
+            asm.call_label(flush);
+            asm.address_of(Reg::Rsi, text);
+            asm.mov_immediate(Reg::Rdx, message.len() as i64);
+            asm.jump(fail);
+            messages.push((text, message));
+        }
+        self.routines(asm);
+        messages.append(&mut self.failures);
+        for (label, message) in messages {
+            asm.bind(label);
+            asm.data(message.as_bytes());
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use super::source::Source;
+use crate::parsing::{InstructionKind, Program};
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Middle of the file, after the options. It defines the range of the
+/// cells, the error raised when the program fails, and a small machine with
+/// the same state as the virtual machine: a tape, a pointer to the current
+/// cell and the functions that read and write bytes. Cells are plain
+/// integers, so every result is exact before it is brought back to the
+/// range of the cells.
+const MACHINE: &str = "
+MIN = -(1 << (BITS - 1)) if SIGNED else 0
+MAX = (1 << (BITS - 1)) - 1 if SIGNED else (1 << BITS) - 1
+
+
+class BrainError(Exception):
+    \"\"\"Error raised when the program fails, with the exit status it ends with.\"\"\"
+
+    def __init__(self, message, status):
+        super().__init__(message)
+        self.status = status
+
+
+def wrap(value):
+    value &= (1 << BITS) - 1
+    if SIGNED and value > MAX:
+        value -= 1 << BITS
+    return value
+
+
+class Machine:
+    def __init__(self, read, write):
+        self.tape = [0] * max(TAPE_LENGTH, 1)
+        self.pointer = 0
+        self.origin = 0
+        self.read = read
+        self.write = write
+
+    def fail(self, message, status):
+        raise BrainError(message, status)
+
+    def is_nonzero(self):
+        return self.tape[self.pointer] != 0
+
+    def advance(self, offset):
+        self.pointer = self.at(offset)
+
+    def add(self, offset, amount):
+        index = self.at(offset)
+        self.store(index, add(self.tape[index], amount, 1))
+
+    def mul_add(self, offset, factor):
+        value = self.tape[self.pointer]
+        if value == 0:
+            return
+        index = self.at(offset)
+        self.store(index, add(self.tape[index], value, factor))
+
+    def set(self, offset, value):
+        self.store(self.at(offset), wrap(value))
+
+    def store(self, index, value):
+        if value is None:
+            self.fail(\"Cell overflow\", 70)
+        self.tape[index] = value
+
+    def output(self, offset):
+        index = self.at(offset)
+        self.print(self.tape[index] & 0xFF)
+
+    def print(self, byte):
+        self.write(byte)
+
+    def input(self):
+        byte = self.read()
+        if byte is None:
+            self.end_of_input()
+        else:
+            self.store(self.pointer, wrap(byte))
+";
+
+/// Helper that resolves cell indexes on a tape that wraps around.
+const AT_WRAP: &str = "
+    def at(self, offset):
+        return (self.pointer + offset) % len(self.tape)
+";
+
+/// Helper that resolves cell indexes on a tape whose ends cannot be
+/// crossed.
+const AT_ERROR: &str = "
+    def at(self, offset):
+        target = self.pointer + offset
+        if target < 0 or target >= len(self.tape):
+            cell = -1 if target < 0 else len(self.tape)
+            self.fail(f\"Pointer moved out of the tape, to cell {cell}\", 70)
+        return target
+";
+
+/// Helper that resolves cell indexes on a tape that grows on demand, at
+/// least doubling, up to its maximum length.
+const AT_GROW: &str = "
+    def at(self, offset):
+        target = self.pointer + offset
+        if target < 0:
+            extra = self.growth(-target)
+            self.tape[:0] = [0] * extra
+            self.pointer += extra
+            self.origin += extra
+            return self.pointer + offset
+        if target >= len(self.tape):
+            self.tape.extend([0] * self.growth(target + 1 - len(self.tape)))
+        return target
+
+    def growth(self, needed):
+        length = len(self.tape)
+        if MAX_TAPE_LENGTH is None:
+            return max(needed, length)
+        if length + needed > MAX_TAPE_LENGTH:
+            self.fail(f\"Tape limit exceeded: {MAX_TAPE_LENGTH} cells\", 75)
+        return min(max(needed, length), MAX_TAPE_LENGTH - length)
+";
+
+/// Cell arithmetic for cells that wrap around.
+const ADD_WRAP: &str = "
+
+def add(cell, value, factor):
+    return wrap(cell + value * factor)
+";
+
+/// Cell arithmetic for cells that stay at the ends of their range.
+const ADD_SATURATE: &str = "
+
+def add(cell, value, factor):
+    return min(max(cell + value * factor, MIN), MAX)
+";
+
+/// Cell arithmetic for cells that stop the program when they overflow.
+const ADD_TRAP: &str = "
+
+def add(cell, value, factor):
+    total = cell + value * factor
+    return total if MIN <= total <= MAX else None
+";
+
+/// Documentation of the function that runs the program.
+const RUN: &str = "    \"\"\"Runs the program. It calls read() whenever it needs a byte of input,
+    which must return an integer from 0 to 255, or None at the end of the
+    input, and write(byte) for every byte of output. Raises a BrainError
+    when the program fails.\"\"\"
+";
+
+/// End of the file. It runs the program on the standard streams when the
+/// file is run as a script.
+const MAIN: &str = "
+
+if __name__ == \"__main__\":
+    import sys
+
+    def read():
+        sys.stdout.buffer.flush()
+        byte = sys.stdin.buffer.read(1)
+        return byte[0] if byte else None
+
+    def write(byte):
+        sys.stdout.buffer.write(bytes((byte,)))
+
+    try:
+        run(read, write)
+        sys.stdout.buffer.flush()
+    except BrainError as error:
+        try:
+            sys.stdout.buffer.flush()
+        except OSError:
+            pass
+        print(error, file=sys.stderr)
+        sys.exit(error.status)
+    except OSError:
+        print(\"Failed to read input or write output\", file=sys.stderr)
+        sys.exit(70)
+";
+
+/// Number of loops nested in a function before the next one is moved to a
+/// function of its own. Python refuses to compile functions with more than
+/// 20 nested blocks.
+const NESTING: usize = 16;
+
+/// Returns the given program as a Python script that behaves as the
+/// virtual machine would with the given options: same tape length and
+/// policy, cell type, overflow policy and EOF policy. The program runs
+/// through a "run(read, write)" function, for notebooks and other hosts,
+/// and on the standard streams when the file is run as a script, where
+/// runtime errors are written to the standard error and end the process
+/// with the same exit code as the interpreter. The step limit is not
+/// reproduced.
+pub(super) fn emit(program: &Program, options: &MachineOptions) -> String {
+    let mut source = Source::new("    ");
+    source.raw(&format!(
+        "# Generated by brain-rusted: {} cells of type {:?}, {:?} at the tape ends,\n\
+         # {:?} on overflow, {:?} at the end of the input.\n\n",
+        options.tape_length, options.cell, options.tape_policy, options.overflow, options.eof,
+    ));
+    source.raw(&format!(
+        "TAPE_LENGTH = {}\n\
+         MAX_TAPE_LENGTH = {}\n\
+         BITS = {}\n\
+         SIGNED = {}\n",
+        options.tape_length,
+        options
+            .max_tape_length
+            .map_or("None".to_string(), |limit| limit.to_string()),
+        options.cell.bits(),
+        if options.cell.is_signed() {
+            "True"
+        } else {
+            "False"
+        },
+    ));
+    source.raw(MACHINE);
+    source.raw(match options.tape_policy {
+        TapePolicy::Wrap => AT_WRAP,
+        TapePolicy::Error => AT_ERROR,
+        TapePolicy::Grow => AT_GROW,
+    });
+    source.raw(&format!(
+        "\n    def end_of_input(self):\n        {}\n",
+        end_of_input(options.eof)
+    ));
+    source.raw(match options.overflow {
+        OverflowPolicy::Wrap => ADD_WRAP,
+        OverflowPolicy::Saturate => ADD_SATURATE,
+        OverflowPolicy::Trap => ADD_TRAP,
+    });
+
+    let mut main = Source::new("    ");
+    main.raw("\n\n");
+    main.open("def run(read, write):");
+    main.raw(RUN);
+    main.line("machine = Machine(read, write)");
+    let mut body = Body {
+        functions: vec![],
+        count: 0,
+        stack: vec![(0, main, 0)],
+        empty: false,
+    };
+    for instruction in program.instructions() {
+        body.statement(instruction.kind);
+    }
+    for (_, function, _) in body.stack {
+        source.raw(&function.into_text());
+    }
+    body.functions.sort();
+    for (_, function) in body.functions {
+        source.raw("\n\n");
+        source.raw(&function);
+    }
+    source.raw(MAIN);
+    source.into_text()
+}
+
+/// Returns the body of the method that handles the end of the input,
+/// according to the given policy.
+fn end_of_input(eof: EofPolicy) -> &'static str {
+    match eof {
+        EofPolicy::Zero => "self.store(self.pointer, 0)",
+        EofPolicy::MinusOne => "self.store(self.pointer, wrap(-1))",
+        EofPolicy::Unchanged => "pass",
+        EofPolicy::Error => "self.fail(\"Unexpected end of input\", 70)",
+    }
+}
+
+/// Statements of a program being written. Loops nested too deeply are moved
+/// to functions of their own, which take the machine and are written after
+/// "run".
+struct Body {
+    /// Number and text of every function finished.
+    functions: Vec<(usize, String)>,
+    /// Number of functions started, which numbers the next one.
+    count: usize,
+    /// Number and source of every function being written, from "run",
+    /// numbered 0, to the innermost one, with the number of loops open in
+    /// each.
+    stack: Vec<(usize, Source, usize)>,
+    /// Whether or not the last loop opened has no statements yet, which
+    /// Python does not allow.
+    empty: bool,
+}
+
+impl Body {
+    /// Writes the statement equivalent to the given instruction.
+    fn statement(&mut self, kind: InstructionKind) {
+        let line = match kind {
+            InstructionKind::Advance => "machine.advance(1)".to_string(),
+            InstructionKind::Recede => "machine.advance(-1)".to_string(),
+            InstructionKind::Move(offset) => format!("machine.advance({})", offset),
+            InstructionKind::Increment => "machine.add(0, 1)".to_string(),
+            InstructionKind::Decrement => "machine.add(0, -1)".to_string(),
+            InstructionKind::Add(amount) => format!("machine.add(0, {})", amount),
+            InstructionKind::AddAt { offset, amount } => {
+                format!("machine.add({}, {})", offset, amount)
+            }
+            InstructionKind::Show => "machine.output(0)".to_string(),
+            InstructionKind::OutputAt { offset } => format!("machine.output({})", offset),
+            InstructionKind::Read => "machine.input()".to_string(),
+            InstructionKind::StartLoop => return self.open(),
+            InstructionKind::StopLoop => return self.close(),
+            InstructionKind::SetZero => "machine.set(0, 0)".to_string(),
+            InstructionKind::MulAdd { offset, factor } => {
+                format!("machine.mul_add({}, {})", offset, factor)
+            }
+            InstructionKind::ScanRight => {
+                "while machine.is_nonzero(): machine.advance(1)".to_string()
+            }
+            InstructionKind::ScanLeft => {
+                "while machine.is_nonzero(): machine.advance(-1)".to_string()
+            }
+            InstructionKind::Print(byte) => format!("machine.print({})", byte),
+            InstructionKind::SetAt { offset, value } => {
+                format!("machine.set({}, {})", offset, value)
+            }
+        };
+        self.line(&line);
+    }
+
+    /// Writes the given line to the innermost function.
+    fn line(&mut self, line: &str) {
+        if let Some((_, source, _)) = self.stack.last_mut() {
+            source.line(line);
+        }
+        self.empty = false;
+    }
+
+    /// Opens a loop, in a new function if the innermost one has too many
+    /// loops open already.
+    fn open(&mut self) {
+        if self
+            .stack
+            .last()
+            .is_some_and(|(_, _, loops)| *loops == NESTING)
+        {
+            self.count += 1;
+            self.line(&format!("loop_{}(machine)", self.count));
+            let mut source = Source::new("    ");
+            source.open(&format!("def loop_{}(machine):", self.count));
+            self.stack.push((self.count, source, 0));
+        }
+        if let Some((_, source, loops)) = self.stack.last_mut() {
+            source.open("while machine.is_nonzero():");
+            *loops += 1;
+        }
+        self.empty = true;
+    }
+
+    /// Closes the innermost loop, and the function that holds it if it was
+    /// the only loop there.
+    fn close(&mut self) {
+        if self.empty {
+            self.line("pass");
+        }
+        if let Some((_, source, loops)) = self.stack.last_mut() {
+            source.end();
+            *loops -= 1;
+        }
+        if self.stack.len() > 1 && self.stack.last().is_some_and(|(_, _, loops)| *loops == 0) {
+            if let Some((number, source, _)) = self.stack.pop() {
+                self.functions.push((number, source.into_text()));
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use super::source::Source;
+use crate::parsing::{InstructionKind, Program};
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Beginning of the file, up to the options. It defines a small machine
+/// with the same state as the virtual machine: a tape, a pointer to the
+/// current cell and buffered input and output streams.
+const MACHINE: &str = "\
+use std::io::{BufWriter, Read, Stdin, Stdout, Write};
+
+struct Machine {
+    tape: Vec<Cell>,
+    pointer: usize,
+    origin: usize,
+    input: Stdin,
+    output: BufWriter<Stdout>,
+}
+
+impl Machine {
+    fn new() -> Self {
+        Self {
+            tape: vec![0; TAPE_LENGTH.max(1)],
+            pointer: 0,
+            origin: 0,
+            input: std::io::stdin(),
+            output: BufWriter::new(std::io::stdout()),
+        }
+    }
+
+    fn fail(&mut self, message: &str, status: i32) -> ! {
+        let _ = self.output.flush();
+        eprintln!(\"{}\", message);
+        std::process::exit(status)
+    }
+
+    fn is_nonzero(&self) -> bool {
+        self.tape[self.pointer] != 0
+    }
+
+    fn advance(&mut self, offset: isize) {
+        self.pointer = self.at(offset);
+    }
+
+    fn add(&mut self, offset: isize, amount: i64) {
+        let index = self.at(offset);
+        match add(self.tape[index], amount as i128, 1) {
+            Some(value) => self.tape[index] = value,
+            None => self.fail(\"Cell overflow\", 70),
+        }
+    }
+
+    fn mul_add(&mut self, offset: isize, factor: i64) {
+        let value = self.tape[self.pointer];
+        if value == 0 {
+            return;
+        }
+        let index = self.at(offset);
+        match add(self.tape[index], value as i128, factor as i128) {
+            Some(value) => self.tape[index] = value,
+            None => self.fail(\"Cell overflow\", 70),
+        }
+    }
+
+    fn set(&mut self, offset: isize, value: i64) {
+        let index = self.at(offset);
+        self.tape[index] = value as Cell;
+    }
+
+    fn output(&mut self, offset: isize) {
+        let index = self.at(offset);
+        self.print(self.tape[index] as u8);
+    }
+
+    fn print(&mut self, byte: u8) {
+        if self.output.write_all(&[byte]).is_err() {
+            self.fail(\"Failed to read input or write output\", 70);
+        }
+    }
+
+    fn input(&mut self) {
+        let mut byte = [0u8; 1];
+        if self.output.flush().is_err() {
+            self.fail(\"Failed to read input or write output\", 70);
+        }
+        match self.input.read_exact(&mut byte) {
+            Ok(()) => self.tape[self.pointer] = byte[0] as Cell,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => self.end_of_input(),
+            Err(_) => self.fail(\"Failed to read input or write output\", 70),
+        }
+    }
+";
+
+/// Helper that resolves cell indexes on a tape that wraps around.
+const AT_WRAP: &str = "
+    fn at(&mut self, offset: isize) -> usize {
+        (self.pointer as isize + offset).rem_euclid(self.tape.len() as isize) as usize
+    }
+";
+
+/// Helper that resolves cell indexes on a tape whose ends cannot be
+/// crossed.
+const AT_ERROR: &str = "
+    fn at(&mut self, offset: isize) -> usize {
+        let target = self.pointer as isize + offset;
+        if target < 0 || target >= self.tape.len() as isize {
+            let cell = target.clamp(-1, self.tape.len() as isize);
+            self.fail(&format!(\"Pointer moved out of the tape, to cell {}\", cell), 70);
+        }
+        target as usize
+    }
+";
+
+/// Helper that resolves cell indexes on a tape that grows on demand, at
+/// least doubling, up to its maximum length.
+const AT_GROW: &str = "
+    fn at(&mut self, offset: isize) -> usize {
+        let target = self.pointer as isize + offset;
+        if target < 0 {
+            let extra = self.growth(target.unsigned_abs());
+            let mut tape = vec![0; extra + self.tape.len()];
+            tape[extra..].copy_from_slice(&self.tape);
+            self.tape = tape;
+            self.pointer += extra;
+            self.origin += extra;
+            return (self.pointer as isize + offset) as usize;
+        }
+        if target as usize >= self.tape.len() {
+            let extra = self.growth(target as usize + 1 - self.tape.len());
+            self.tape.resize(self.tape.len() + extra, 0);
+        }
+        target as usize
+    }
+
+    fn growth(&mut self, needed: usize) -> usize {
+        let length = self.tape.len();
+        match MAX_TAPE_LENGTH {
+            Some(limit) if length + needed > limit => {
+                self.fail(&format!(\"Tape limit exceeded: {} cells\", limit), 75)
+            }
+            Some(limit) => needed.max(length).min(limit - length),
+            None => needed.max(length),
+        }
+    }
+";
+
+/// Cell arithmetic for cells that wrap around.
+const ADD_WRAP: &str = "
+fn add(cell: Cell, value: i128, factor: i128) -> Option<Cell> {
+    Some(value.wrapping_mul(factor).wrapping_add(cell as i128) as Cell)
+}
+";
+
+/// Cell arithmetic for cells that stay at the ends of their range.
+const ADD_SATURATE: &str = "
+fn add(cell: Cell, value: i128, factor: i128) -> Option<Cell> {
+    let sum = value.checked_mul(factor).and_then(|product| product.checked_add(cell as i128));
+    Some(match sum {
+        Some(sum) => sum.clamp(Cell::MIN as i128, Cell::MAX as i128) as Cell,
+        None if (value < 0) == (factor < 0) => Cell::MAX,
+        None => Cell::MIN,
+    })
+}
+";
+
+/// Cell arithmetic for cells that stop the program when they overflow.
+const ADD_TRAP: &str = "
+fn add(cell: Cell, value: i128, factor: i128) -> Option<Cell> {
+    let sum = value.checked_mul(factor).and_then(|product| product.checked_add(cell as i128));
+    sum.filter(|&sum| Cell::MIN as i128 <= sum && sum <= Cell::MAX as i128)
+        .map(|sum| sum as Cell)
+}
+";
+
+/// Returns the given program as a standalone Rust "main.rs" file that
+/// behaves as the virtual machine would with the given options: same tape
+/// length and policy, cell type, overflow policy and EOF policy. Runtime
+/// errors are written to the standard error, and end the process with the
+/// same exit code as the interpreter. The step limit is not reproduced.
+pub(super) fn emit(program: &Program, options: &MachineOptions) -> String {
+    let mut source = Source::new("    ");
+    source.raw(&format!(
+        "//! Generated by brain-rusted: {} cells of type {:?}, {:?} at the tape ends,\n\
+         //! {:?} on overflow, {:?} at the end of the input.\n\
+         #![allow(dead_code)]\n\n",
+        options.tape_length, options.cell, options.tape_policy, options.overflow, options.eof,
+    ));
+    source.raw(MACHINE);
+    source.raw(match options.tape_policy {
+        TapePolicy::Wrap => AT_WRAP,
+        TapePolicy::Error => AT_ERROR,
+        TapePolicy::Grow => AT_GROW,
+    });
+    source.raw(&format!(
+        "\n    fn end_of_input(&mut self) {{\n        {}\n    }}\n}}\n",
+        end_of_input(options.eof)
+    ));
+    source.raw(match options.overflow {
+        OverflowPolicy::Wrap => ADD_WRAP,
+        OverflowPolicy::Saturate => ADD_SATURATE,
+        OverflowPolicy::Trap => ADD_TRAP,
+    });
+    source.raw(&format!(
+        "\ntype Cell = {};\n\
+         const TAPE_LENGTH: usize = {};\n\
+         const MAX_TAPE_LENGTH: Option<usize> = {:?};\n\n",
+        cell_type(options),
+        options.tape_length,
+        options.max_tape_length,
+    ));
+
+    source.open("fn main() {");
+    source.line("let mut machine = Machine::new();");
+    for instruction in program.instructions() {
+        statement(&mut source, instruction.kind);
+    }
+    source.line("if machine.output.flush().is_err() {");
+    source.line("    machine.fail(\"Failed to read input or write output\", 70);");
+    source.line("}");
+    source.close("}");
+    source.into_text()
+}
+
+/// Returns the name of the Rust type of the cells of the given options.
+fn cell_type(options: &MachineOptions) -> String {
+    let prefix = if options.cell.is_signed() { 'i' } else { 'u' };
+    format!("{}{}", prefix, options.cell.bits())
+}
+
+/// Returns the body of the method that handles the end of the input,
+/// according to the given policy.
+fn end_of_input(eof: EofPolicy) -> &'static str {
+    match eof {
+        EofPolicy::Zero => "self.tape[self.pointer] = 0;",
+        EofPolicy::MinusOne => "self.tape[self.pointer] = -1i128 as Cell;",
+        EofPolicy::Unchanged => "",
+        EofPolicy::Error => "self.fail(\"Unexpected end of input\", 70);",
+    }
+}
+
+/// Writes the statement equivalent to the given instruction to the given
+/// source.
+fn statement(source: &mut Source, kind: InstructionKind) {
+    let line = match kind {
+        InstructionKind::Advance => "machine.advance(1);".to_string(),
+        InstructionKind::Recede => "machine.advance(-1);".to_string(),
+        InstructionKind::Move(offset) => format!("machine.advance({});", offset),
+        InstructionKind::Increment => "machine.add(0, 1);".to_string(),
+        InstructionKind::Decrement => "machine.add(0, -1);".to_string(),
+        InstructionKind::Add(amount) => format!("machine.add(0, {});", amount),
+        InstructionKind::AddAt { offset, amount } => {
+            format!("machine.add({}, {});", offset, amount)
+        }
+        InstructionKind::Show => "machine.output(0);".to_string(),
+        InstructionKind::OutputAt { offset } => format!("machine.output({});", offset),
+        InstructionKind::Read => "machine.input();".to_string(),
+        InstructionKind::StartLoop => return source.open("while machine.is_nonzero() {"),
+        InstructionKind::StopLoop => return source.close("}"),
+        InstructionKind::SetZero => "machine.set(0, 0);".to_string(),
+        InstructionKind::MulAdd { offset, factor } => {
+            format!("machine.mul_add({}, {});", offset, factor)
+        }
+        InstructionKind::ScanRight => {
+            "while machine.is_nonzero() { machine.advance(1); }".to_string()
+        }
+        InstructionKind::ScanLeft => {
+            "while machine.is_nonzero() { machine.advance(-1); }".to_string()
+        }
+        InstructionKind::Print(byte) => format!("machine.print({});", byte),
+        InstructionKind::SetAt { offset, value } => {
+            format!("machine.set({}, {});", offset, value)
+        }
+    };
+    source.line(&line);
+}
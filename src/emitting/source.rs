@@ -0,0 +1,60 @@
+/// Text of a generated source file, built line by line. Every line is
+/// indented according to how many blocks are open.
+pub(super) struct Source {
+    /// Text written so far.
+    text: String,
+    /// Number of blocks currently open.
+    depth: usize,
+    /// Text written once per open block before every line.
+    indent: &'static str,
+}
+
+impl Source {
+    /// Returns an empty source, indented with the given text.
+    pub(super) fn new(indent: &'static str) -> Self {
+        Self {
+            text: String::new(),
+            depth: 0,
+            indent,
+        }
+    }
+
+    /// Writes the given text as is, without indenting it.
+    pub(super) fn raw(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    /// Writes the given line, indented.
+    pub(super) fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.text.push_str(self.indent);
+        }
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
+
+    /// Writes the given line, which opens a block, so the following lines
+    /// are indented one more level.
+    pub(super) fn open(&mut self, line: &str) {
+        self.line(line);
+        self.depth += 1;
+    }
+
+    /// Writes the given line, which closes a block, one level less
+    /// indented than the previous lines.
+    pub(super) fn close(&mut self, line: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        self.line(line);
+    }
+
+    /// Closes a block without writing a line, for languages where blocks
+    /// end where their indentation does.
+    pub(super) fn end(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Returns the text written.
+    pub(super) fn into_text(self) -> String {
+        self.text
+    }
+}
@@ -0,0 +1,85 @@
+/// Every language a program can be emitted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A self-contained C source file.
+    C,
+    /// A standalone Rust "main.rs" file.
+    Rust,
+    /// A WebAssembly module for WASI, in the text format.
+    Wat,
+    /// A WebAssembly module for WASI, in the binary format.
+    Wasm,
+    /// A static Linux x86-64 executable.
+    Elf,
+    /// The listing of that executable, for the GNU assembler.
+    Asm,
+    /// An LLVM IR module, in the textual format.
+    Llvm,
+    /// A JavaScript file, for browsers and Node.js.
+    Js,
+    /// A Python script.
+    Python,
+    /// A compiled program file, which the interpreter runs without
+    /// compiling it again.
+    Bfc,
+}
+
+impl Target {
+    /// Every target.
+    pub const ALL: [Target; 10] = [
+        Target::C,
+        Target::Rust,
+        Target::Wat,
+        Target::Wasm,
+        Target::Elf,
+        Target::Asm,
+        Target::Llvm,
+        Target::Js,
+        Target::Python,
+        Target::Bfc,
+    ];
+
+    /// Returns the name of this target, used to choose it by name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::C => "c",
+            Target::Rust => "rust",
+            Target::Wat => "wat",
+            Target::Wasm => "wasm",
+            Target::Elf => "elf",
+            Target::Asm => "asm",
+            Target::Llvm => "llvm",
+            Target::Js => "js",
+            Target::Python => "python",
+            Target::Bfc => "bfc",
+        }
+    }
+
+    /// Returns the target with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<Target> {
+        Self::ALL.into_iter().find(|target| target.name() == name)
+    }
+
+    /// Returns the usual extension of files written for this target, which
+    /// is empty for executables.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Target::C => "c",
+            Target::Rust => "rs",
+            Target::Wat => "wat",
+            Target::Wasm => "wasm",
+            Target::Elf => "",
+            Target::Asm => "s",
+            Target::Llvm => "ll",
+            Target::Js => "js",
+            Target::Python => "py",
+            Target::Bfc => "bfc",
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,266 @@
+use super::code::Code;
+use super::module::{Function, Module, ValType};
+
+/// Bytes every module starts with: the magic number and the version.
+const HEADER: [u8; 8] = [0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00];
+
+/// Identifiers of the sections the backend writes, in the order they must
+/// appear.
+const TYPE_SECTION: u8 = 1;
+const IMPORT_SECTION: u8 = 2;
+const FUNCTION_SECTION: u8 = 3;
+const MEMORY_SECTION: u8 = 5;
+const GLOBAL_SECTION: u8 = 6;
+const EXPORT_SECTION: u8 = 7;
+const CODE_SECTION: u8 = 10;
+const DATA_SECTION: u8 = 11;
+
+/// Kinds of the things a module imports or exports.
+const FUNCTION_KIND: u8 = 0x00;
+const MEMORY_KIND: u8 = 0x02;
+
+/// Returns the given module in the binary format.
+pub(super) fn encode(module: &Module) -> Vec<u8> {
+    let mut types: Vec<(Vec<ValType>, Option<ValType>)> = vec![];
+    let mut type_indexes = vec![];
+    for function in &module.functions {
+        let signature = function.signature();
+        let index = match types.iter().position(|known| *known == signature) {
+            Some(index) => index,
+            None => {
+                types.push(signature);
+                types.len() - 1
+            }
+        };
+        type_indexes.push(index as u32);
+    }
+    let imports: Vec<_> = (0..module.functions.len())
+        .filter(|index| module.functions[*index].import.is_some())
+        .collect();
+    let defined: Vec<_> = (0..module.functions.len())
+        .filter(|index| module.functions[*index].import.is_none())
+        .collect();
+
+    let mut bytes = HEADER.to_vec();
+
+    let mut section = vector_length(types.len());
+    for (params, result) in &types {
+        section.push(0x60);
+        section.extend(vector_length(params.len()));
+        section.extend(params.iter().map(|kind| value_type(*kind)));
+        section.extend(vector_length(result.iter().count()));
+        section.extend(result.iter().map(|kind| value_type(*kind)));
+    }
+    push_section(&mut bytes, TYPE_SECTION, section);
+
+    let mut section = vector_length(imports.len());
+    for index in &imports {
+        if let Some((from, name)) = module.functions[*index].import {
+            section.extend(name_bytes(from));
+            section.extend(name_bytes(name));
+            section.push(FUNCTION_KIND);
+            section.extend(unsigned(type_indexes[*index] as u64));
+        }
+    }
+    push_section(&mut bytes, IMPORT_SECTION, section);
+
+    let mut section = vector_length(defined.len());
+    for index in &defined {
+        section.extend(unsigned(type_indexes[*index] as u64));
+    }
+    push_section(&mut bytes, FUNCTION_SECTION, section);
+
+    let mut section = vector_length(1);
+    section.push(0x00);
+    section.extend(unsigned(module.memory_pages as u64));
+    push_section(&mut bytes, MEMORY_SECTION, section);
+
+    let mut section = vector_length(module.globals.len());
+    for global in &module.globals {
+        section.extend([value_type(ValType::I32), 0x01]);
+        section.extend(instruction(&Code::I32Const(global.initial)));
+        section.extend(instruction(&Code::End));
+    }
+    push_section(&mut bytes, GLOBAL_SECTION, section);
+
+    let exports: Vec<_> = defined
+        .iter()
+        .filter_map(|index| module.functions[*index].export.map(|name| (name, *index)))
+        .collect();
+    let mut section = vector_length(exports.len() + 1);
+    section.extend(name_bytes(Module::MEMORY_EXPORT));
+    section.extend([MEMORY_KIND, 0x00]);
+    for (name, index) in exports {
+        section.extend(name_bytes(name));
+        section.push(FUNCTION_KIND);
+        section.extend(unsigned(index as u64));
+    }
+    push_section(&mut bytes, EXPORT_SECTION, section);
+
+    let mut section = vector_length(defined.len());
+    for index in &defined {
+        let body = function_body(&module.functions[*index]);
+        section.extend(vector_length(body.len()));
+        section.extend(body);
+    }
+    push_section(&mut bytes, CODE_SECTION, section);
+
+    let mut section = vector_length(module.data.len());
+    for data in &module.data {
+        section.push(0x00);
+        section.extend(instruction(&Code::I32Const(data.address as i32)));
+        section.extend(instruction(&Code::End));
+        section.extend(vector_length(data.bytes.len()));
+        section.extend(&data.bytes);
+    }
+    push_section(&mut bytes, DATA_SECTION, section);
+
+    bytes
+}
+
+/// Writes a section with the given identifier and contents to the given
+/// bytes, preceded by its size.
+fn push_section(bytes: &mut Vec<u8>, id: u8, contents: Vec<u8>) {
+    bytes.push(id);
+    bytes.extend(vector_length(contents.len()));
+    bytes.extend(contents);
+}
+
+/// Returns the body of the given function: its locals, grouped by type,
+/// and its instructions, followed by the end of the function.
+fn function_body(function: &Function) -> Vec<u8> {
+    let mut groups: Vec<(u32, ValType)> = vec![];
+    for (_, kind) in &function.locals {
+        match groups.last_mut() {
+            Some((count, last)) if last == kind => *count += 1,
+            _ => groups.push((1, *kind)),
+        }
+    }
+    let mut bytes = vector_length(groups.len());
+    for (count, kind) in groups {
+        bytes.extend(unsigned(count as u64));
+        bytes.push(value_type(kind));
+    }
+    for code in &function.body {
+        bytes.extend(instruction(code));
+    }
+    bytes.extend(instruction(&Code::End));
+    bytes
+}
+
+/// Returns the encoding of the given value type.
+fn value_type(kind: ValType) -> u8 {
+    match kind {
+        ValType::I32 => 0x7F,
+        ValType::I64 => 0x7E,
+    }
+}
+
+/// Returns the encoding of the given instruction.
+fn instruction(code: &Code) -> Vec<u8> {
+    // Blocks never take or leave values.
+    const EMPTY_BLOCK: u8 = 0x40;
+
+    let memory = |opcode: u8, bytes: u32| {
+        let mut encoded = vec![opcode];
+        encoded.extend(unsigned(bytes.trailing_zeros() as u64));
+        encoded.push(0x00);
+        encoded
+    };
+    let with_index = |opcode: u8, index: u32| {
+        let mut encoded = vec![opcode];
+        encoded.extend(unsigned(index as u64));
+        encoded
+    };
+    match code {
+        Code::Unreachable => vec![0x00],
+        Code::Block => vec![0x02, EMPTY_BLOCK],
+        Code::Loop => vec![0x03, EMPTY_BLOCK],
+        Code::If => vec![0x04, EMPTY_BLOCK],
+        Code::End => vec![0x0B],
+        Code::Br(depth) => with_index(0x0C, *depth),
+        Code::BrIf(depth) => with_index(0x0D, *depth),
+        Code::Return => vec![0x0F],
+        Code::Call(func) => with_index(0x10, func.index()),
+        Code::Drop => vec![0x1A],
+        Code::Select => vec![0x1B],
+        Code::LocalGet(index) => with_index(0x20, *index),
+        Code::LocalSet(index) => with_index(0x21, *index),
+        Code::LocalTee(index) => with_index(0x22, *index),
+        Code::GlobalGet(index) => with_index(0x23, *index),
+        Code::GlobalSet(index) => with_index(0x24, *index),
+        Code::I32Load => memory(0x28, 4),
+        Code::I32Load8U => memory(0x2D, 1),
+        Code::I32Store => memory(0x36, 4),
+        Code::I32Store8 => memory(0x3A, 1),
+        Code::I64Load(width) => {
+            let opcode = match width.bytes() {
+                1 => 0x31,
+                2 => 0x33,
+                4 => 0x35,
+                _ => 0x29,
+            };
+            memory(opcode, width.bytes())
+        }
+        Code::I64Store(width) => {
+            let opcode = match width.bytes() {
+                1 => 0x3C,
+                2 => 0x3D,
+                4 => 0x3E,
+                _ => 0x37,
+            };
+            memory(opcode, width.bytes())
+        }
+        Code::I32Const(value) => [vec![0x41], signed(*value as i64)].concat(),
+        Code::I64Const(value) => [vec![0x42], signed(*value)].concat(),
+        Code::I32Eqz => vec![0x45],
+        Code::I32GeU => vec![0x4F],
+        Code::I32Add => vec![0x6A],
+        Code::I32Sub => vec![0x6B],
+        Code::I32Mul => vec![0x6C],
+        Code::I64Eqz => vec![0x50],
+        Code::I64Add => vec![0x7C],
+        Code::I64Mul => vec![0x7E],
+    }
+}
+
+/// Returns the given name, preceded by its length.
+fn name_bytes(name: &str) -> Vec<u8> {
+    let mut bytes = vector_length(name.len());
+    bytes.extend(name.as_bytes());
+    bytes
+}
+
+/// Returns the length of a vector, as it precedes its elements.
+fn vector_length(length: usize) -> Vec<u8> {
+    unsigned(length as u64)
+}
+
+/// Returns the given number in the unsigned LEB128 encoding.
+fn unsigned(mut value: u64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Returns the given number in the signed LEB128 encoding.
+fn signed(mut value: i64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
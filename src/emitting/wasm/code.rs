@@ -0,0 +1,73 @@
+use super::module::Func;
+
+/// Width of a memory access, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Width {
+    W8,
+    W16,
+    W32,
+    W64,
+}
+
+impl Width {
+    /// Returns the width of the given number of bits.
+    pub(super) fn of_bits(bits: u32) -> Self {
+        match bits {
+            8 => Width::W8,
+            16 => Width::W16,
+            32 => Width::W32,
+            _ => Width::W64,
+        }
+    }
+
+    /// Returns the number of bytes of this width.
+    pub(super) fn bytes(&self) -> u32 {
+        match self {
+            Width::W8 => 1,
+            Width::W16 => 2,
+            Width::W32 => 4,
+            Width::W64 => 8,
+        }
+    }
+}
+
+/// WebAssembly instructions used by the backend. Control instructions that
+/// take a block type always take the empty one, and branches are relative
+/// to the enclosing blocks, as in WebAssembly itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Code {
+    Unreachable,
+    Block,
+    Loop,
+    If,
+    End,
+    Br(u32),
+    BrIf(u32),
+    Return,
+    Call(Func),
+    Drop,
+    Select,
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    I32Load,
+    I32Load8U,
+    I32Store,
+    I32Store8,
+    /// Loads an unsigned value of the given width, extended to 64 bits.
+    I64Load(Width),
+    /// Stores the lowest bits of a 64-bit value, of the given width.
+    I64Store(Width),
+    I32Const(i32),
+    I64Const(i64),
+    I32Eqz,
+    I32GeU,
+    I32Add,
+    I32Sub,
+    I32Mul,
+    I64Eqz,
+    I64Add,
+    I64Mul,
+}
@@ -0,0 +1,429 @@
+use super::code::{Code, Width};
+use super::module::{Data, Func, Function, Global, Module, ValType};
+use crate::emitting::{EmitError, Target};
+use crate::parsing::{InstructionKind, Program};
+use crate::virtual_machine::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Address of the I/O vector used to read and write one byte: its first
+/// four bytes hold the address of the buffer, and the next four its length.
+const BYTE_VECTOR: i32 = 0;
+
+/// Address where WASI writes how many bytes were read or written.
+const COUNT: i32 = 8;
+
+/// Address of the buffer of one byte used to read and write.
+const BUFFER: i32 = 12;
+
+/// Address of the I/O vector used to write error messages.
+const MESSAGE_VECTOR: i32 = 16;
+
+/// Address of the first error message. The others follow it.
+const MESSAGES: u32 = 32;
+
+/// Address of the first cell of the tape.
+const TAPE: u32 = 256;
+
+/// Size of a page of linear memory, in bytes.
+const PAGE: u64 = 65_536;
+
+/// Largest address the module can use, so every address fits a signed
+/// 32-bit value.
+const MAX_ADDRESS: u64 = 1 << 31;
+
+/// Name of the module WASI functions are imported from.
+const WASI: &str = "wasi_snapshot_preview1";
+
+/// Exit status of runtime errors, as the interpreter uses.
+const RUNTIME_STATUS: i32 = 70;
+
+/// Error messages, in the order they are stored in memory.
+const OUT_OF_TAPE: &str = "Pointer moved out of the tape\n";
+const END_OF_INPUT: &str = "Unexpected end of input\n";
+const IO_FAILURE: &str = "Failed to read input or write output\n";
+
+/// Index of the global that holds the index of the current cell.
+const POINTER: u32 = 0;
+
+/// Everything the helpers need to know about the options.
+struct Layout {
+    /// Number of cells of the tape.
+    length: i32,
+    /// Width of every cell.
+    width: Width,
+    /// What happens when the pointer goes past either end of the tape.
+    tape: TapePolicy,
+    /// What happens at the end of the input.
+    eof: EofPolicy,
+}
+
+impl Layout {
+    /// Returns the given offset as the module passes it to the helpers.
+    /// Offsets are reduced into the tape when it wraps around, and clamped
+    /// into a range that keeps them out of the tape otherwise.
+    fn offset(&self, offset: isize) -> i32 {
+        let length = self.length as i64;
+        match self.tape {
+            TapePolicy::Wrap => (offset as i64).rem_euclid(length) as i32,
+            _ => (offset as i64).clamp(-length, length) as i32,
+        }
+    }
+}
+
+/// Returns the module equivalent to the given program, behaving as the
+/// virtual machine would with the given options. Fails, on behalf of the
+/// given target, for growing tapes, for overflow policies other than
+/// wrapping and for tapes that do not fit in the linear memory.
+pub(super) fn lower(
+    program: &Program,
+    target: Target,
+    options: &MachineOptions,
+) -> Result<Module, EmitError> {
+    let unsupported = |option| EmitError::Unsupported { target, option };
+    if options.tape_policy == TapePolicy::Grow {
+        return Err(unsupported("growing tapes"));
+    }
+    if options.overflow != OverflowPolicy::Wrap {
+        return Err(unsupported("overflow policies other than wrap"));
+    }
+    let width = Width::of_bits(options.cell.bits());
+    let end = (options.tape_length as u64)
+        .checked_mul(width.bytes() as u64)
+        .and_then(|size| size.checked_add(TAPE as u64))
+        .filter(|end| *end <= MAX_ADDRESS)
+        .ok_or_else(|| unsupported("tapes larger than 2 GiB"))?;
+
+    let layout = Layout {
+        length: options.tape_length as i32,
+        width,
+        tape: options.tape_policy,
+        eof: options.eof,
+    };
+    let functions = Func::ALL
+        .into_iter()
+        .map(|func| function(func, &layout, program))
+        .collect();
+    Ok(Module {
+        functions,
+        memory_pages: end.div_ceil(PAGE).max(1) as u32,
+        globals: vec![Global {
+            name: "pointer",
+            initial: 0,
+        }],
+        data: vec![
+            Data {
+                address: BYTE_VECTOR as u32,
+                bytes: [BUFFER.to_le_bytes(), 1i32.to_le_bytes()].concat(),
+            },
+            Data {
+                address: MESSAGES,
+                bytes: [OUT_OF_TAPE, END_OF_INPUT, IO_FAILURE]
+                    .concat()
+                    .into_bytes(),
+            },
+        ],
+    })
+}
+
+/// Returns the code that fails with the given message.
+fn fail(message: &str) -> [Code; 4] {
+    let address = MESSAGES as usize
+        + match message {
+            OUT_OF_TAPE => 0,
+            END_OF_INPUT => OUT_OF_TAPE.len(),
+            _ => OUT_OF_TAPE.len() + END_OF_INPUT.len(),
+        };
+    [
+        Code::I32Const(address as i32),
+        Code::I32Const(message.len() as i32),
+        Code::I32Const(RUNTIME_STATUS),
+        Code::Call(Func::Fail),
+    ]
+}
+
+/// Returns the given function of the module.
+fn function(func: Func, layout: &Layout, program: &Program) -> Function {
+    use ValType::{I32, I64};
+
+    let mut function = Function {
+        func,
+        import: None,
+        export: None,
+        params: vec![],
+        result: None,
+        locals: vec![],
+        body: vec![],
+    };
+    let width = layout.width;
+    match func {
+        Func::FdWrite | Func::FdRead => {
+            function.import = Some((WASI, func.name()));
+            function.params = vec![("fd", I32), ("iovs", I32), ("length", I32), ("count", I32)];
+            function.result = Some(I32);
+        }
+        Func::ProcExit => {
+            function.import = Some((WASI, func.name()));
+            function.params = vec![("status", I32)];
+        }
+        Func::Fail => {
+            function.params = vec![("message", I32), ("length", I32), ("status", I32)];
+            function.body = vec![
+                Code::I32Const(MESSAGE_VECTOR),
+                Code::LocalGet(0),
+                Code::I32Store,
+                Code::I32Const(MESSAGE_VECTOR + 4),
+                Code::LocalGet(1),
+                Code::I32Store,
+                Code::I32Const(2),
+                Code::I32Const(MESSAGE_VECTOR),
+                Code::I32Const(1),
+                Code::I32Const(COUNT),
+                Code::Call(Func::FdWrite),
+                Code::Drop,
+                Code::LocalGet(2),
+                Code::Call(Func::ProcExit),
+                Code::Unreachable,
+            ];
+        }
+        Func::Put => {
+            function.params = vec![("byte", I32)];
+            function.body = vec![
+                Code::I32Const(BUFFER),
+                Code::LocalGet(0),
+                Code::I32Store8,
+                Code::I32Const(1),
+                Code::I32Const(BYTE_VECTOR),
+                Code::I32Const(1),
+                Code::I32Const(COUNT),
+                Code::Call(Func::FdWrite),
+                Code::If,
+            ];
+            function.body.extend(fail(IO_FAILURE));
+            function.body.push(Code::End);
+        }
+        Func::At => {
+            function.params = vec![("offset", I32)];
+            function.result = Some(I32);
+            function.locals = vec![("index", I32)];
+            function.body = vec![Code::GlobalGet(POINTER), Code::LocalGet(0), Code::I32Add];
+            if layout.tape == TapePolicy::Wrap {
+                function.body.extend([
+                    Code::LocalSet(1),
+                    Code::LocalGet(1),
+                    Code::I32Const(layout.length),
+                    Code::I32Sub,
+                    Code::LocalGet(1),
+                    Code::LocalGet(1),
+                    Code::I32Const(layout.length),
+                    Code::I32GeU,
+                    Code::Select,
+                ]);
+            } else {
+                function.body.extend([
+                    Code::LocalTee(1),
+                    Code::I32Const(layout.length),
+                    Code::I32GeU,
+                    Code::If,
+                ]);
+                function.body.extend(fail(OUT_OF_TAPE));
+                function.body.extend([Code::End, Code::LocalGet(1)]);
+            }
+        }
+        Func::Address => {
+            function.params = vec![("index", I32)];
+            function.result = Some(I32);
+            function.body = vec![
+                Code::LocalGet(0),
+                Code::I32Const(width.bytes() as i32),
+                Code::I32Mul,
+                Code::I32Const(TAPE as i32),
+                Code::I32Add,
+            ];
+        }
+        Func::IsZero => {
+            function.result = Some(I32);
+            function.body = vec![
+                Code::GlobalGet(POINTER),
+                Code::Call(Func::Address),
+                Code::I64Load(width),
+                Code::I64Eqz,
+            ];
+        }
+        Func::Move => {
+            function.params = vec![("offset", I32)];
+            function.body = vec![
+                Code::LocalGet(0),
+                Code::Call(Func::At),
+                Code::GlobalSet(POINTER),
+            ];
+        }
+        Func::Add => {
+            function.params = vec![("offset", I32), ("amount", I64)];
+            function.locals = vec![("address", I32)];
+            function.body = vec![
+                Code::LocalGet(0),
+                Code::Call(Func::At),
+                Code::Call(Func::Address),
+                Code::LocalTee(2),
+                Code::LocalGet(2),
+                Code::I64Load(width),
+                Code::LocalGet(1),
+                Code::I64Add,
+                Code::I64Store(width),
+            ];
+        }
+        Func::MulAdd => {
+            function.params = vec![("offset", I32), ("factor", I64)];
+            function.locals = vec![("address", I32)];
+            function.body = vec![
+                Code::Call(Func::IsZero),
+                Code::If,
+                Code::Return,
+                Code::End,
+                Code::LocalGet(0),
+                Code::Call(Func::At),
+                Code::Call(Func::Address),
+                Code::LocalTee(2),
+                Code::LocalGet(2),
+                Code::I64Load(width),
+                Code::GlobalGet(POINTER),
+                Code::Call(Func::Address),
+                Code::I64Load(width),
+                Code::LocalGet(1),
+                Code::I64Mul,
+                Code::I64Add,
+                Code::I64Store(width),
+            ];
+        }
+        Func::Set => {
+            function.params = vec![("offset", I32), ("value", I64)];
+            function.body = vec![
+                Code::LocalGet(0),
+                Code::Call(Func::At),
+                Code::Call(Func::Address),
+                Code::LocalGet(1),
+                Code::I64Store(width),
+            ];
+        }
+        Func::Output => {
+            // Cells are little endian, so their lowest byte comes first.
+            function.params = vec![("offset", I32)];
+            function.body = vec![
+                Code::LocalGet(0),
+                Code::Call(Func::At),
+                Code::Call(Func::Address),
+                Code::I32Load8U,
+                Code::Call(Func::Put),
+            ];
+        }
+        Func::Input => {
+            function.body = vec![
+                Code::I32Const(0),
+                Code::I32Const(BYTE_VECTOR),
+                Code::I32Const(1),
+                Code::I32Const(COUNT),
+                Code::Call(Func::FdRead),
+                Code::If,
+            ];
+            function.body.extend(fail(IO_FAILURE));
+            function.body.extend([
+                Code::End,
+                Code::I32Const(COUNT),
+                Code::I32Load,
+                Code::I32Eqz,
+                Code::If,
+            ]);
+            function.body.extend(end_of_input(layout));
+            function.body.extend([
+                Code::Return,
+                Code::End,
+                Code::GlobalGet(POINTER),
+                Code::Call(Func::Address),
+                Code::I32Const(BUFFER),
+                Code::I64Load(Width::W8),
+                Code::I64Store(width),
+            ]);
+        }
+        Func::Start => {
+            function.export = Some("_start");
+            for instruction in program.instructions() {
+                lower_instruction(&mut function.body, layout, instruction.kind);
+            }
+        }
+    }
+    function
+}
+
+/// Returns the code that runs at the end of the input, according to the
+/// policy of the given layout.
+fn end_of_input(layout: &Layout) -> Vec<Code> {
+    let store = |value| {
+        vec![
+            Code::GlobalGet(POINTER),
+            Code::Call(Func::Address),
+            Code::I64Const(value),
+            Code::I64Store(layout.width),
+        ]
+    };
+    match layout.eof {
+        EofPolicy::Zero => store(0),
+        EofPolicy::MinusOne => store(-1),
+        EofPolicy::Unchanged => vec![],
+        EofPolicy::Error => fail(END_OF_INPUT).to_vec(),
+    }
+}
+
+/// Writes the code equivalent to the given instruction to the given body.
+fn lower_instruction(body: &mut Vec<Code>, layout: &Layout, kind: InstructionKind) {
+    let call = |offset, func| [Code::I32Const(layout.offset(offset)), Code::Call(func)];
+    let call_with = |offset, value, func| {
+        [
+            Code::I32Const(layout.offset(offset)),
+            Code::I64Const(value),
+            Code::Call(func),
+        ]
+    };
+    let scan = |offset| {
+        let mut code = vec![
+            Code::Block,
+            Code::Loop,
+            Code::Call(Func::IsZero),
+            Code::BrIf(1),
+        ];
+        code.extend(call(offset, Func::Move));
+        code.extend([Code::Br(0), Code::End, Code::End]);
+        code
+    };
+    match kind {
+        InstructionKind::Advance => body.extend(call(1, Func::Move)),
+        InstructionKind::Recede => body.extend(call(-1, Func::Move)),
+        InstructionKind::Move(offset) => body.extend(call(offset, Func::Move)),
+        InstructionKind::Increment => body.extend(call_with(0, 1, Func::Add)),
+        InstructionKind::Decrement => body.extend(call_with(0, -1, Func::Add)),
+        InstructionKind::Add(amount) => body.extend(call_with(0, amount, Func::Add)),
+        InstructionKind::AddAt { offset, amount } => {
+            body.extend(call_with(offset, amount, Func::Add))
+        }
+        InstructionKind::Show => body.extend(call(0, Func::Output)),
+        InstructionKind::OutputAt { offset } => body.extend(call(offset, Func::Output)),
+        InstructionKind::Read => body.push(Code::Call(Func::Input)),
+        InstructionKind::StartLoop => body.extend([
+            Code::Block,
+            Code::Loop,
+            Code::Call(Func::IsZero),
+            Code::BrIf(1),
+        ]),
+        InstructionKind::StopLoop => body.extend([Code::Br(0), Code::End, Code::End]),
+        InstructionKind::SetZero => body.extend(call_with(0, 0, Func::Set)),
+        InstructionKind::MulAdd { offset, factor } => {
+            body.extend(call_with(offset, factor, Func::MulAdd))
+        }
+        InstructionKind::ScanRight => body.extend(scan(1)),
+        InstructionKind::ScanLeft => body.extend(scan(-1)),
+        InstructionKind::Print(byte) => {
+            body.extend([Code::I32Const(byte as i32), Code::Call(Func::Put)])
+        }
+        InstructionKind::SetAt { offset, value } => {
+            body.extend(call_with(offset, value, Func::Set))
+        }
+    }
+}
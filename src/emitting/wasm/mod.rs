@@ -0,0 +1,42 @@
+//! The WebAssembly backend. A program is first lowered into a small model
+//! of a WebAssembly module, which is then either written as text (".wat")
+//! or encoded as binary (".wasm"), so both forms always describe the same
+//! module. The tape lives in linear memory, and the input and output go
+//! through the WASI "fd_read" and "fd_write" functions.
+
+/// This module encodes a module in the binary format.
+mod binary;
+
+/// This module defines the Code type, each of the WebAssembly instructions
+/// the backend uses.
+mod code;
+
+/// This module lowers a program into a module.
+mod lower;
+
+/// This module defines the model of a WebAssembly module.
+mod module;
+
+/// This module writes a module in the text format.
+mod text;
+
+use super::{EmitError, Target};
+use crate::parsing::Program;
+use crate::virtual_machine::MachineOptions;
+
+/// Returns the given program as a WebAssembly module in the text format.
+pub(super) fn emit_text(program: &Program, options: &MachineOptions) -> Result<String, EmitError> {
+    Ok(text::write(&lower::lower(program, Target::Wat, options)?))
+}
+
+/// Returns the given program as a WebAssembly module in the binary format.
+pub(super) fn emit_binary(
+    program: &Program,
+    options: &MachineOptions,
+) -> Result<Vec<u8>, EmitError> {
+    Ok(binary::encode(&lower::lower(
+        program,
+        Target::Wasm,
+        options,
+    )?))
+}
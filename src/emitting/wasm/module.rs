@@ -0,0 +1,140 @@
+use super::code::Code;
+
+/// Types of the values the backend uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ValType {
+    I32,
+    I64,
+}
+
+/// Every function of the module, imported or defined, in the order of the
+/// function index space: imports come first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Func {
+    FdWrite,
+    FdRead,
+    ProcExit,
+    Fail,
+    Put,
+    At,
+    Address,
+    IsZero,
+    Move,
+    Add,
+    MulAdd,
+    Set,
+    Output,
+    Input,
+    Start,
+}
+
+impl Func {
+    /// Every function, in the order of the function index space.
+    pub(super) const ALL: [Func; 15] = [
+        Func::FdWrite,
+        Func::FdRead,
+        Func::ProcExit,
+        Func::Fail,
+        Func::Put,
+        Func::At,
+        Func::Address,
+        Func::IsZero,
+        Func::Move,
+        Func::Add,
+        Func::MulAdd,
+        Func::Set,
+        Func::Output,
+        Func::Input,
+        Func::Start,
+    ];
+
+    /// Returns the name of this function in the text format.
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            Func::FdWrite => "fd_write",
+            Func::FdRead => "fd_read",
+            Func::ProcExit => "proc_exit",
+            Func::Fail => "fail",
+            Func::Put => "put",
+            Func::At => "at",
+            Func::Address => "address",
+            Func::IsZero => "is_zero",
+            Func::Move => "move",
+            Func::Add => "add",
+            Func::MulAdd => "mul_add",
+            Func::Set => "set",
+            Func::Output => "output",
+            Func::Input => "input",
+            Func::Start => "start",
+        }
+    }
+
+    /// Returns the index of this function in the function index space.
+    pub(super) fn index(&self) -> u32 {
+        Self::ALL
+            .iter()
+            .position(|func| func == self)
+            .unwrap_or_default() as u32
+    }
+}
+
+/// A function of the module: its signature, its locals and its body. Only
+/// imported functions have an import, and only they have no body.
+#[derive(Debug, Clone)]
+pub(super) struct Function {
+    pub(super) func: Func,
+    pub(super) import: Option<(&'static str, &'static str)>,
+    pub(super) export: Option<&'static str>,
+    pub(super) params: Vec<(&'static str, ValType)>,
+    pub(super) result: Option<ValType>,
+    pub(super) locals: Vec<(&'static str, ValType)>,
+    pub(super) body: Vec<Code>,
+}
+
+impl Function {
+    /// Returns the name of the parameter or local at the given index.
+    pub(super) fn local_name(&self, index: u32) -> &'static str {
+        self.params
+            .iter()
+            .chain(&self.locals)
+            .nth(index as usize)
+            .map_or("unknown", |(name, _)| name)
+    }
+
+    /// Returns the signature of this function: the types of its
+    /// parameters and of its result.
+    pub(super) fn signature(&self) -> (Vec<ValType>, Option<ValType>) {
+        let params = self.params.iter().map(|(_, kind)| *kind).collect();
+        (params, self.result)
+    }
+}
+
+/// A mutable global of type i32, with its initial value.
+#[derive(Debug, Clone)]
+pub(super) struct Global {
+    pub(super) name: &'static str,
+    pub(super) initial: i32,
+}
+
+/// Bytes copied to the linear memory, at the given address, when the
+/// module is instantiated.
+#[derive(Debug, Clone)]
+pub(super) struct Data {
+    pub(super) address: u32,
+    pub(super) bytes: Vec<u8>,
+}
+
+/// A whole WebAssembly module: its functions (imported ones first), one
+/// exported memory of the given number of pages, its globals and its data.
+#[derive(Debug, Clone)]
+pub(super) struct Module {
+    pub(super) functions: Vec<Function>,
+    pub(super) memory_pages: u32,
+    pub(super) globals: Vec<Global>,
+    pub(super) data: Vec<Data>,
+}
+
+impl Module {
+    /// Name under which the memory is exported, as WASI expects.
+    pub(super) const MEMORY_EXPORT: &'static str = "memory";
+}
@@ -0,0 +1,160 @@
+use super::code::{Code, Width};
+use super::module::{Function, Module, ValType};
+use crate::emitting::source::Source;
+
+/// Returns the given module in the text format. Every function, parameter,
+/// local and global is referred to by its name, and instructions are
+/// written one per line, indented inside blocks.
+pub(super) fn write(module: &Module) -> String {
+    let mut source = Source::new("  ");
+    source.raw(";; Generated by brain-rusted.\n");
+    source.open("(module");
+    for function in module.functions.iter().filter(|f| f.import.is_some()) {
+        if let Some((from, name)) = function.import {
+            source.line(&format!(
+                "(import \"{}\" \"{}\" (func ${}{}))",
+                from,
+                name,
+                function.func.name(),
+                signature(function)
+            ));
+        }
+    }
+    source.line(&format!(
+        "(memory (export \"{}\") {})",
+        Module::MEMORY_EXPORT,
+        module.memory_pages
+    ));
+    for global in &module.globals {
+        source.line(&format!(
+            "(global ${} (mut i32) (i32.const {}))",
+            global.name, global.initial
+        ));
+    }
+    for data in &module.data {
+        source.line(&format!(
+            "(data (i32.const {}) \"{}\")",
+            data.address,
+            escape(&data.bytes)
+        ));
+    }
+    for function in module.functions.iter().filter(|f| f.import.is_none()) {
+        let export = function
+            .export
+            .map(|name| format!(" (export \"{}\")", name))
+            .unwrap_or_default();
+        source.open(&format!(
+            "(func ${}{}{}",
+            function.func.name(),
+            export,
+            signature(function)
+        ));
+        for (name, kind) in &function.locals {
+            source.line(&format!("(local ${} {})", name, value_type(*kind)));
+        }
+        for code in &function.body {
+            match code {
+                Code::End => source.close("end"),
+                Code::Block | Code::Loop | Code::If => {
+                    source.open(&instruction(module, function, code))
+                }
+                _ => source.line(&instruction(module, function, code)),
+            }
+        }
+        source.close(")");
+    }
+    source.close(")");
+    source.into_text()
+}
+
+/// Returns the parameters and result of the given function, as written
+/// after its name.
+fn signature(function: &Function) -> String {
+    let mut text = String::new();
+    for (name, kind) in &function.params {
+        text.push_str(&format!(" (param ${} {})", name, value_type(*kind)));
+    }
+    if let Some(kind) = function.result {
+        text.push_str(&format!(" (result {})", value_type(kind)));
+    }
+    text
+}
+
+/// Returns the name of the given value type.
+fn value_type(kind: ValType) -> &'static str {
+    match kind {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+    }
+}
+
+/// Returns the given instruction, part of the given function, as written
+/// in the text format.
+fn instruction(module: &Module, function: &Function, code: &Code) -> String {
+    let local = |index: &u32| function.local_name(*index);
+    let global = |index: &u32| {
+        module
+            .globals
+            .get(*index as usize)
+            .map_or("unknown", |global| global.name)
+    };
+    match code {
+        Code::Unreachable => "unreachable".to_string(),
+        Code::Block => "block".to_string(),
+        Code::Loop => "loop".to_string(),
+        Code::If => "if".to_string(),
+        Code::End => "end".to_string(),
+        Code::Br(depth) => format!("br {}", depth),
+        Code::BrIf(depth) => format!("br_if {}", depth),
+        Code::Return => "return".to_string(),
+        Code::Call(func) => format!("call ${}", func.name()),
+        Code::Drop => "drop".to_string(),
+        Code::Select => "select".to_string(),
+        Code::LocalGet(index) => format!("local.get ${}", local(index)),
+        Code::LocalSet(index) => format!("local.set ${}", local(index)),
+        Code::LocalTee(index) => format!("local.tee ${}", local(index)),
+        Code::GlobalGet(index) => format!("global.get ${}", global(index)),
+        Code::GlobalSet(index) => format!("global.set ${}", global(index)),
+        Code::I32Load => "i32.load".to_string(),
+        Code::I32Load8U => "i32.load8_u".to_string(),
+        Code::I32Store => "i32.store".to_string(),
+        Code::I32Store8 => "i32.store8".to_string(),
+        Code::I64Load(width) => match width {
+            Width::W8 => "i64.load8_u",
+            Width::W16 => "i64.load16_u",
+            Width::W32 => "i64.load32_u",
+            Width::W64 => "i64.load",
+        }
+        .to_string(),
+        Code::I64Store(width) => match width {
+            Width::W8 => "i64.store8",
+            Width::W16 => "i64.store16",
+            Width::W32 => "i64.store32",
+            Width::W64 => "i64.store",
+        }
+        .to_string(),
+        Code::I32Const(value) => format!("i32.const {}", value),
+        Code::I64Const(value) => format!("i64.const {}", value),
+        Code::I32Eqz => "i32.eqz".to_string(),
+        Code::I32GeU => "i32.ge_u".to_string(),
+        Code::I32Add => "i32.add".to_string(),
+        Code::I32Sub => "i32.sub".to_string(),
+        Code::I32Mul => "i32.mul".to_string(),
+        Code::I64Eqz => "i64.eqz".to_string(),
+        Code::I64Add => "i64.add".to_string(),
+        Code::I64Mul => "i64.mul".to_string(),
+    }
+}
+
+/// Returns the given bytes as the contents of a string literal, escaping
+/// every byte that is not printable.
+fn escape(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for byte in bytes {
+        match byte {
+            b' '..=b'~' if *byte != b'"' && *byte != b'\\' => text.push(*byte as char),
+            _ => text.push_str(&format!("\\{:02x}", byte)),
+        }
+    }
+    text
+}
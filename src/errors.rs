@@ -0,0 +1,76 @@
+use crate::emitting::EmitError;
+use crate::lexing::LexError;
+use crate::loading::LoadError;
+use crate::parsing::ParseError;
+use crate::virtual_machine::RuntimeError;
+
+/// Any error that may happen while loading, compiling or running a
+/// program. Each variant wraps the error of the corresponding step, and
+/// displays exactly like it.
+#[derive(Debug)]
+pub enum BrainError {
+    /// The program file could not be loaded.
+    Load(LoadError),
+    /// The source code could not be tokenized.
+    Lex(LexError),
+    /// The tokens could not be parsed into a program.
+    Parse(ParseError),
+    /// The program failed while being executed.
+    Runtime(RuntimeError),
+    /// The program could not be emitted in another language.
+    Emit(EmitError),
+}
+
+impl std::fmt::Display for BrainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrainError::Load(e) => e.fmt(f),
+            BrainError::Lex(e) => e.fmt(f),
+            BrainError::Parse(e) => e.fmt(f),
+            BrainError::Runtime(e) => e.fmt(f),
+            BrainError::Emit(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BrainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BrainError::Load(e) => e.source(),
+            BrainError::Lex(e) => e.source(),
+            BrainError::Parse(e) => e.source(),
+            BrainError::Runtime(e) => e.source(),
+            BrainError::Emit(e) => e.source(),
+        }
+    }
+}
+
+impl From<LoadError> for BrainError {
+    fn from(e: LoadError) -> Self {
+        BrainError::Load(e)
+    }
+}
+
+impl From<LexError> for BrainError {
+    fn from(e: LexError) -> Self {
+        BrainError::Lex(e)
+    }
+}
+
+impl From<ParseError> for BrainError {
+    fn from(e: ParseError) -> Self {
+        BrainError::Parse(e)
+    }
+}
+
+impl From<RuntimeError> for BrainError {
+    fn from(e: RuntimeError) -> Self {
+        BrainError::Runtime(e)
+    }
+}
+
+impl From<EmitError> for BrainError {
+    fn from(e: EmitError) -> Self {
+        BrainError::Emit(e)
+    }
+}
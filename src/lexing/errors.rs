@@ -1,5 +1,74 @@
-/// Returns an error message when an invalid character is encountered during
-/// the tokenization process.
-pub(crate) fn error_invalid_token_found(token: char) -> String {
-    format!("Invalid token found: {}", token)
+use crate::diagnostics::{Snippet, Span};
+
+/// Errors that may happen during the tokenization process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexError {
+    /// Strict tokenization found characters that are neither commands nor
+    /// whitespace. The first ones are listed, in the order they appear, and
+    /// the others are only counted.
+    InvalidTokens {
+        /// The first REPORTED_TOKENS offending characters.
+        tokens: Vec<InvalidToken>,
+        /// Number of offending characters found after those.
+        omitted: usize,
+    },
 }
+
+impl LexError {
+    /// Greatest number of offending characters listed by an error. A file
+    /// of prose has one on almost every character, and listing them all
+    /// would bury the first ones.
+    pub const REPORTED_TOKENS: usize = 20;
+}
+
+/// A character that is neither a command nor whitespace, found during the
+/// strict tokenization process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidToken {
+    /// The offending character.
+    pub character: char,
+    /// Where the offending character was found.
+    pub snippet: Snippet,
+}
+
+impl InvalidToken {
+    /// Returns a new invalid token for the given character, found at the
+    /// given span of the given source code.
+    pub(crate) fn new(source: &str, character: char, span: &Span) -> Self {
+        Self {
+            character,
+            snippet: Snippet::new(source, span),
+        }
+    }
+}
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LexError::InvalidTokens { tokens, omitted } => {
+                for (index, token) in tokens.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "\n\n")?;
+                    }
+                    write!(f, "{}", token)?;
+                }
+                match omitted {
+                    0 => Ok(()),
+                    1 => write!(f, "\n\n... and 1 more invalid token"),
+                    _ => write!(f, "\n\n... and {} more invalid tokens", omitted),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.snippet.render(
+            f,
+            format_args!("invalid token found: {}", self.character.escape_debug()),
+        )
+    }
+}
+
+impl std::error::Error for LexError {}
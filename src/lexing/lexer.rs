@@ -1,25 +1,76 @@
-use super::token::Token;
+use super::token::{Token, TokenKind};
+use super::{InvalidToken, LexError};
+use crate::diagnostics::Span;
 
 /// Static type that has lexing logic and serves as a public interface.
-pub(crate) struct Lexer {}
+pub struct Lexer {}
 
 impl Lexer {
-    /// Attempts to return an array containing all tokens found during the
-    /// given string. Ignores any whitespace found. Fails to find the first
-    /// invalid character in the string. In this case, returns an error
-    /// message indicating which character was found. This error message
-    /// is suitable for display by the program.
-    pub(crate) fn tokenize(program: &str) -> Result<Vec<Token>, String> {
-        program
-            .chars()
-            .filter(|c| !Self::is_ignorable_token(c))
-            .map(Token::from)
+    /// Returns an array containing all tokens found in the given string.
+    /// Every character that is not one of the eight brainf*ck commands is
+    /// treated as a comment and ignored.
+    pub fn tokenize(program: &str) -> Vec<Token> {
+        Self::characters(program)
+            .filter_map(|(character, span)| {
+                TokenKind::from(character).map(|kind| Token::new(kind, span))
+            })
             .collect()
     }
 
+    /// Attempts to return an array containing all tokens found in the given
+    /// string. Ignores any whitespace found, but fails on any other
+    /// character that is not a command. In this case, returns an error
+    /// pointing at the first invalid characters found, and counting the
+    /// others. This error is suitable for display by the program.
+    pub fn tokenize_strict(program: &str) -> Result<Vec<Token>, LexError> {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        let mut omitted = 0;
+
+        for (character, span) in Self::characters(program) {
+            if Self::is_ignorable_token(&character) {
+                continue;
+            }
+            match TokenKind::from(character) {
+                Some(kind) => tokens.push(Token::new(kind, span)),
+                None if errors.len() < LexError::REPORTED_TOKENS => {
+                    errors.push(InvalidToken::new(program, character, &span))
+                }
+                None => omitted += 1,
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(LexError::InvalidTokens {
+                tokens: errors,
+                omitted,
+            })
+        }
+    }
+
     /// Returns whether or not a given character is ignorable during the
-    /// tokenization process.
+    /// strict tokenization process.
     fn is_ignorable_token(token: &char) -> bool {
         token.is_whitespace()
     }
+
+    /// Returns an iterator over every character of the given string, paired
+    /// with the span where it was found.
+    fn characters(program: &str) -> impl Iterator<Item = (char, Span)> + '_ {
+        let mut line = 1;
+        let mut column = 1;
+
+        program.char_indices().map(move |(offset, character)| {
+            let span = Span::new(offset, character.len_utf8(), line, column);
+            if character == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+            (character, span)
+        })
+    }
 }
@@ -5,17 +5,16 @@
 /// This module defines the Lexer type, which is responsible for transforming
 /// a text input into a set of tokens, which can be more easily converted
 /// into virtual machine instructions.
-pub(crate) mod lexer;
+pub mod lexer;
 
 /// This module defines the Token type, which can be easily obtained from a
 /// text character.
-pub(crate) mod token;
+pub mod token;
 
-/// This module makes it easy to create error messages for problems that
-/// occur during the lexing process. These error messages are suitable
-/// for display by the program.
+/// This module defines the errors for problems that occur during the
+/// lexing process. These errors are suitable for display by the program.
 mod errors;
 
-use errors::error_invalid_token_found;
-pub(crate) use lexer::Lexer;
-pub(crate) use token::Token;
+pub use errors::{InvalidToken, LexError};
+pub use lexer::Lexer;
+pub use token::{Token, TokenKind};
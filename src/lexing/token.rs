@@ -1,7 +1,19 @@
+use crate::diagnostics::Span;
+
 /// Type represents each of the tokens that can be present in a
-/// brainf*ck program
-#[derive(Debug)]
-pub(crate) enum Token {
+/// brainf*ck program, together with where it was found in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    /// Which command this token is.
+    pub kind: TokenKind,
+    /// Where this token was found in the source code.
+    pub span: Span,
+}
+
+/// Type represents each of the commands that can be present in a
+/// brainf*ck program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
     Advance,
     Decrement,
     Increment,
@@ -13,21 +25,27 @@ pub(crate) enum Token {
 }
 
 impl Token {
-    /// Attempts to return the token that is equivalent to the given
-    /// characters. If it fails, it returns an error message that informs
-    /// which invalid character was found. This error message can
-    /// be displayed by the program.
-    pub(crate) fn from(token: char) -> Result<Token, String> {
+    /// Returns a new token of the given kind, found at the given span.
+    pub fn new(kind: TokenKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl TokenKind {
+    /// Attempts to return the token kind that is equivalent to the given
+    /// character. Returns None if the character is not one of the eight
+    /// brainf*ck commands.
+    pub fn from(token: char) -> Option<TokenKind> {
         match token {
-            '>' => Ok(Token::Advance),
-            '<' => Ok(Token::Recede),
-            '+' => Ok(Token::Increment),
-            '-' => Ok(Token::Decrement),
-            '.' => Ok(Token::Show),
-            '[' => Ok(Token::StartLoop),
-            ']' => Ok(Token::StopLoop),
-            ',' => Ok(Token::Read),
-            _ => Err(super::error_invalid_token_found(token)),
+            '>' => Some(TokenKind::Advance),
+            '<' => Some(TokenKind::Recede),
+            '+' => Some(TokenKind::Increment),
+            '-' => Some(TokenKind::Decrement),
+            '.' => Some(TokenKind::Show),
+            '[' => Some(TokenKind::StartLoop),
+            ']' => Some(TokenKind::StopLoop),
+            ',' => Some(TokenKind::Read),
+            _ => None,
         }
     }
 }
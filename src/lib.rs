@@ -0,0 +1,158 @@
+//! BrainRusted is a brainf*ck interpreter. This library exposes every step
+//! of the interpreter: loading a program file, the lexical analysis, the
+//! syntactic analysis and the virtual machine that executes the resulting
+//! program. The functions defined here are the documented entry points for
+//! the most common uses; the modules can be used directly for finer control.
+
+/// This module encapsulates the translation of programs into x86-64
+/// machine code, shared by the JIT compiler and the ELF backend.
+mod assembling;
+
+/// This module encapsulates the lowering of a program into the bytecode
+/// executed by the virtual machine.
+pub mod bytecode;
+
+/// This module encapsulates the locations of pieces of source code and the
+/// rendering of error messages that point at them.
+pub mod diagnostics;
+
+/// This module encapsulates the translation of programs into other
+/// languages.
+pub mod emitting;
+
+/// This module defines the BrainError type, which gathers the errors of
+/// every step of the interpreter.
+mod errors;
+
+/// This module encapsulates the lexical analysis of a program, turning the
+/// source text into tokens.
+pub mod lexing;
+
+/// This module encapsulates the loading of a program's source code from a
+/// file.
+pub mod loading;
+
+/// This module encapsulates the optional rewriting of programs into
+/// equivalent ones that run faster.
+pub mod optimizing;
+
+/// This module encapsulates the syntactic analysis of a program, turning
+/// tokens into a program that can be executed by the virtual machine.
+pub mod parsing;
+
+/// This module defines the virtual machine, which executes programs.
+pub mod virtual_machine;
+
+pub use bytecode::{Bytecode, Compiler, Op};
+pub use diagnostics::Span;
+pub use emitting::{EmitError, Emitter, Target};
+pub use errors::BrainError;
+pub use lexing::{LexError, Lexer, Token, TokenKind};
+pub use loading::{CompiledProgram, LoadError};
+pub use optimizing::{OptLevel, OptimizationReport, OptimizeOptions, Optimizer, Pass, PassManager};
+pub use parsing::{Instruction, InstructionKind, ParseError, Parser, Program};
+pub use virtual_machine::{
+    Cell, CellType, EofPolicy, InMemoryMachine, MachineOptions, OverflowPolicy, RuntimeError, Tape,
+    TapePolicy, VirtualMachine,
+};
+
+use virtual_machine::with_cell_type;
+
+/// Options that control how source code is compiled into a program.
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// When set, any character other than the eight commands and
+    /// whitespace is rejected instead of being treated as a comment.
+    pub strict: bool,
+}
+
+/// Compiles the given source code into a program that can be executed by
+/// the virtual machine, using the default options. On failure, returns an
+/// error that is suitable for display.
+pub fn compile(source: &str) -> Result<Program, BrainError> {
+    compile_with(source, &CompileOptions::default())
+}
+
+/// Compiles the given source code into a program that can be executed by
+/// the virtual machine, using the given options. On failure, returns an
+/// error that is suitable for display.
+pub fn compile_with(source: &str, options: &CompileOptions) -> Result<Program, BrainError> {
+    let tokens = if options.strict {
+        Lexer::tokenize_strict(source)?
+    } else {
+        Lexer::tokenize(source)
+    };
+    Ok(Parser::parse(source, &tokens)?)
+}
+
+/// Returns a program equivalent to the given one that runs faster, when
+/// run with the given options, using the default optimization level.
+/// Programs that are not optimized run exactly as written, so both can be
+/// run to compare their results.
+pub fn optimize(program: &Program, options: &MachineOptions) -> Program {
+    Optimizer::optimize(program, options)
+}
+
+/// Returns a program equivalent to the given one, when run with the given
+/// machine options, rewritten by the passes chosen by the given
+/// optimization options, and a report of what each pass did.
+pub fn optimize_with(
+    program: &Program,
+    optimize: &OptimizeOptions,
+    options: &MachineOptions,
+) -> (Program, OptimizationReport) {
+    Optimizer::optimize_with(program, optimize, options)
+}
+
+/// Returns the given program translated to the given target, behaving as
+/// the virtual machine would with the given options. Fails if the target
+/// cannot reproduce the behaviour chosen by the options.
+pub fn emit(
+    program: &Program,
+    target: Target,
+    options: &MachineOptions,
+) -> Result<Vec<u8>, EmitError> {
+    Emitter::emit(program, target, options)
+}
+
+/// Runs the given program on a new virtual machine, using the default
+/// options, feeding it the given bytes as input. Returns every byte the
+/// program wrote as output, or the error that stopped the execution.
+pub fn run(program: &Program, input: &[u8]) -> Result<Vec<u8>, RuntimeError> {
+    run_with(program, input, &MachineOptions::default())
+}
+
+/// Runs the given program on a new virtual machine, using the given
+/// options, feeding it the given bytes as input. Returns every byte the
+/// program wrote as output, or the error that stopped the execution.
+pub fn run_with(
+    program: &Program,
+    input: &[u8],
+    options: &MachineOptions,
+) -> Result<Vec<u8>, RuntimeError> {
+    with_cell_type!(options.cell, run_in_memory(program, input, options))
+}
+
+/// Runs the given program on a new virtual machine, using the given
+/// options, connected to the standard input and output of the process.
+pub fn run_stdio(program: &Program, options: &MachineOptions) -> Result<(), RuntimeError> {
+    with_cell_type!(options.cell, run_on_stdio(program, options))
+}
+
+/// Runs the given program on a new virtual machine with the given cell
+/// type, feeding it the given bytes as input and collecting its output.
+fn run_in_memory<C: Cell>(
+    program: &Program,
+    input: &[u8],
+    options: &MachineOptions,
+) -> Result<Vec<u8>, RuntimeError> {
+    let mut vm = VirtualMachine::<_, _, C>::with_streams(input, Vec::new(), options.clone());
+    vm.execute(program)?;
+    Ok(vm.into_streams().1)
+}
+
+/// Runs the given program on a new virtual machine with the given cell
+/// type, connected to the standard input and output of the process.
+fn run_on_stdio<C: Cell>(program: &Program, options: &MachineOptions) -> Result<(), RuntimeError> {
+    VirtualMachine::<_, _, C>::stdio(options.clone()).execute(program)
+}
@@ -0,0 +1,412 @@
+use super::LoadError;
+use crate::diagnostics::Span;
+use crate::parsing::{Instruction, InstructionKind, Program};
+use crate::virtual_machine::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Bytes every compiled program file starts with. The first one is not
+/// text, so source files are never mistaken for compiled ones.
+pub const MAGIC: [u8; 4] = *b"\0bfc";
+
+/// Version of the format written by this version of the interpreter. Files
+/// of any other version are refused.
+pub const VERSION: u16 = 1;
+
+/// Maximum number of cells the tape of a loaded program may start with or
+/// grow to, and maximum distance between cells its instructions may use,
+/// so a file cannot make the interpreter allocate an unreasonable tape or
+/// compute an index that does not fit in an isize.
+pub const MAX_TAPE_LENGTH: usize = 1 << 30;
+
+/// Number of bytes of the header, which are, in order and little-endian:
+/// the magic bytes, the version (2 bytes), the cell type, tape policy,
+/// overflow policy and EOF policy (1 byte each), the tape length, the
+/// maximum tape length, which is 0 when there is none, and the number of
+/// instructions (8 bytes each), and the checksum (4 bytes).
+///
+/// The layout of the header, the checksum and the instruction encoder are
+/// public, but hidden from the documentation, so tests can write files
+/// that "encode" never would.
+#[doc(hidden)]
+pub const HEADER_LENGTH: usize = 38;
+
+/// Position of the number of instructions in the header.
+#[doc(hidden)]
+pub const COUNT: usize = 26;
+
+/// Position of the checksum in the header. It is the CRC-32 of every other
+/// byte of the file, so changes to the header are caught as well.
+#[doc(hidden)]
+pub const CHECKSUM: usize = 34;
+
+/// Every cell type, in the order of their codes.
+const CELL_TYPES: [CellType; 8] = [
+    CellType::U8,
+    CellType::U16,
+    CellType::U32,
+    CellType::U64,
+    CellType::I8,
+    CellType::I16,
+    CellType::I32,
+    CellType::I64,
+];
+
+/// Every tape policy, in the order of their codes.
+const TAPE_POLICIES: [TapePolicy; 3] = [TapePolicy::Wrap, TapePolicy::Error, TapePolicy::Grow];
+
+/// Every overflow policy, in the order of their codes.
+const OVERFLOW_POLICIES: [OverflowPolicy; 3] = [
+    OverflowPolicy::Wrap,
+    OverflowPolicy::Saturate,
+    OverflowPolicy::Trap,
+];
+
+/// Every EOF policy, in the order of their codes.
+const EOF_POLICIES: [EofPolicy; 4] = [
+    EofPolicy::Zero,
+    EofPolicy::MinusOne,
+    EofPolicy::Unchanged,
+    EofPolicy::Error,
+];
+
+/// A program read from a compiled program file, with the options it was
+/// compiled for. Optimized programs may rely on those options, so they
+/// must be run with them.
+#[derive(Debug, Clone)]
+pub struct CompiledProgram {
+    /// The program, as it was when it was written.
+    pub program: Program,
+    /// The tape, cell, overflow and EOF settings the program was written
+    /// with. The other options are left at their defaults.
+    pub options: MachineOptions,
+}
+
+/// Returns whether or not the given bytes start like a compiled program
+/// file.
+pub fn is_compiled(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Returns the given program, meant to be run with the given options, in
+/// the compiled program file format. Every instruction is written after
+/// the header as its code, its operands in the signed LEB128 encoding and
+/// its span in the unsigned one, so runtime errors still point at the
+/// source code the program came from.
+pub fn encode(program: &Program, options: &MachineOptions) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH + 8 * program.instructions().len());
+    bytes.extend(MAGIC);
+    bytes.extend(VERSION.to_le_bytes());
+    bytes.push(code(&CELL_TYPES, options.cell));
+    bytes.push(code(&TAPE_POLICIES, options.tape_policy));
+    bytes.push(code(&OVERFLOW_POLICIES, options.overflow));
+    bytes.push(code(&EOF_POLICIES, options.eof));
+    bytes.extend((options.tape_length as u64).to_le_bytes());
+    bytes.extend((options.max_tape_length.unwrap_or(0) as u64).to_le_bytes());
+    debug_assert_eq!(bytes.len(), COUNT);
+    bytes.extend((program.instructions().len() as u64).to_le_bytes());
+    debug_assert_eq!(bytes.len(), CHECKSUM);
+    bytes.extend([0; 4]);
+    for instruction in program.instructions() {
+        write_instruction(&mut bytes, instruction);
+    }
+    let checksum = checksum(&bytes);
+    bytes[CHECKSUM..CHECKSUM + 4].copy_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+/// Attempts to read a program, and the options it was written with, from
+/// the given bytes of the compiled program file at the given path. Fails if
+/// the bytes are not a compiled program of the current version, if they
+/// were changed since they were written, or if the tape is longer than
+/// MAX_TAPE_LENGTH.
+pub fn decode(path: &str, bytes: &[u8]) -> Result<CompiledProgram, LoadError> {
+    let corrupted = || LoadError::Corrupted {
+        path: path.to_string(),
+    };
+    if !is_compiled(bytes) {
+        return Err(LoadError::NotCompiled {
+            path: path.to_string(),
+        });
+    }
+    let mut reader = Reader { bytes, position: 4 };
+    let version = u16::from_le_bytes(reader.fixed().ok_or_else(corrupted)?);
+    if version != VERSION {
+        return Err(LoadError::UnsupportedVersion {
+            path: path.to_string(),
+            version,
+        });
+    }
+    if bytes.len() < HEADER_LENGTH {
+        return Err(corrupted());
+    }
+    if checksum(bytes).to_le_bytes() != bytes[CHECKSUM..CHECKSUM + 4] {
+        return Err(corrupted());
+    }
+    let (options, count) = reader.header().ok_or_else(corrupted)?;
+    let longest = options
+        .tape_length
+        .max(options.max_tape_length.unwrap_or(0));
+    if longest > MAX_TAPE_LENGTH {
+        return Err(LoadError::TapeTooLong {
+            path: path.to_string(),
+            length: longest as u64,
+        });
+    }
+    let mut instructions = Vec::with_capacity(count.min(bytes.len()));
+    let mut depth = 0usize;
+    for _ in 0..count {
+        let instruction = reader.instruction().ok_or_else(corrupted)?;
+        match instruction.kind {
+            InstructionKind::StartLoop => depth += 1,
+            InstructionKind::StopLoop => depth = depth.checked_sub(1).ok_or_else(corrupted)?,
+            _ => {}
+        }
+        instructions.push(instruction);
+    }
+    if depth != 0 || reader.position != bytes.len() {
+        return Err(corrupted());
+    }
+    Ok(CompiledProgram {
+        program: Program::new(instructions),
+        options,
+    })
+}
+
+/// Returns the code of the given value, which is its position in the given
+/// list of every value of its type.
+fn code<T: PartialEq>(values: &[T], value: T) -> u8 {
+    values.iter().position(|v| *v == value).unwrap_or(0) as u8
+}
+
+/// Writes the given instruction, as described in "encode", to the given
+/// bytes.
+#[doc(hidden)]
+pub fn write_instruction(bytes: &mut Vec<u8>, instruction: &Instruction) {
+    let (code, operands): (u8, &[i64]) = match instruction.kind {
+        InstructionKind::Advance => (0, &[]),
+        InstructionKind::Recede => (1, &[]),
+        InstructionKind::Increment => (2, &[]),
+        InstructionKind::Decrement => (3, &[]),
+        InstructionKind::Show => (4, &[]),
+        InstructionKind::Read => (5, &[]),
+        InstructionKind::StartLoop => (6, &[]),
+        InstructionKind::StopLoop => (7, &[]),
+        InstructionKind::Add(amount) => (8, &[amount]),
+        InstructionKind::Move(offset) => (9, &[offset as i64]),
+        InstructionKind::SetZero => (10, &[]),
+        InstructionKind::MulAdd { offset, factor } => (11, &[offset as i64, factor]),
+        InstructionKind::ScanRight => (12, &[]),
+        InstructionKind::ScanLeft => (13, &[]),
+        InstructionKind::AddAt { offset, amount } => (14, &[offset as i64, amount]),
+        InstructionKind::OutputAt { offset } => (15, &[offset as i64]),
+        InstructionKind::Print(byte) => (16, &[byte as i64]),
+        InstructionKind::SetAt { offset, value } => (17, &[offset as i64, value]),
+    };
+    bytes.push(code);
+    for &operand in operands {
+        write_signed(bytes, operand);
+    }
+    let span = &instruction.span;
+    for value in [span.offset, span.length, span.line, span.column] {
+        write_unsigned(bytes, value as u64);
+    }
+}
+
+/// Writes the given number, in the unsigned LEB128 encoding, to the given
+/// bytes.
+fn write_unsigned(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Writes the given number, in the signed LEB128 encoding, to the given
+/// bytes.
+fn write_signed(bytes: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Returns the checksum of the given bytes of a compiled program file: the
+/// CRC-32 (the one used by zip and PNG) of every byte but the checksum
+/// itself.
+#[doc(hidden)]
+pub fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes[..CHECKSUM].iter().chain(&bytes[CHECKSUM + 4..]) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Position in the bytes of a compiled program file being read. Every
+/// method returns None when the bytes end too soon or hold a value that
+/// cannot be there.
+struct Reader<'a> {
+    /// Every byte of the file.
+    bytes: &'a [u8],
+    /// Position of the next byte to read.
+    position: usize,
+}
+
+impl Reader<'_> {
+    /// Reads the given number of bytes.
+    fn fixed<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.bytes.get(self.position..self.position + N)?;
+        self.position += N;
+        bytes.try_into().ok()
+    }
+
+    /// Reads a single byte.
+    fn byte(&mut self) -> Option<u8> {
+        let [byte] = self.fixed()?;
+        Some(byte)
+    }
+
+    /// Reads the byte that is the code of one of the given values, and
+    /// returns that value.
+    fn choice<T: Copy>(&mut self, values: &[T]) -> Option<T> {
+        values.get(self.byte()? as usize).copied()
+    }
+
+    /// Reads a little-endian number of 8 bytes that fits in a usize.
+    fn length(&mut self) -> Option<usize> {
+        usize::try_from(u64::from_le_bytes(self.fixed()?)).ok()
+    }
+
+    /// Reads the rest of the header, right after the version, and returns
+    /// the options and number of instructions it holds.
+    fn header(&mut self) -> Option<(MachineOptions, usize)> {
+        let cell = self.choice(&CELL_TYPES)?;
+        let tape_policy = self.choice(&TAPE_POLICIES)?;
+        let overflow = self.choice(&OVERFLOW_POLICIES)?;
+        let eof = self.choice(&EOF_POLICIES)?;
+        let tape_length = self.length().filter(|&length| length > 0)?;
+        let max_tape_length = Some(self.length()?).filter(|&length| length > 0);
+        let count = self.length()?;
+        self.fixed::<4>()?;
+        let options = MachineOptions {
+            cell,
+            tape_policy,
+            overflow,
+            eof,
+            tape_length,
+            max_tape_length,
+            ..MachineOptions::default()
+        };
+        Some((options, count))
+    }
+
+    /// Reads a number in the unsigned LEB128 encoding.
+    fn unsigned(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7F) as u64;
+            if shift >= 64 || (shift == 63 && bits > 1) {
+                return None;
+            }
+            value |= bits << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Reads a number in the signed LEB128 encoding.
+    fn signed(&mut self) -> Option<i64> {
+        let mut value = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift >= 64 {
+                return None;
+            }
+            value |= ((byte & 0x7F) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Some(value);
+            }
+        }
+    }
+
+    /// Reads a signed number that is an offset between cells, which is no
+    /// farther than MAX_TAPE_LENGTH.
+    fn offset(&mut self) -> Option<isize> {
+        let offset = self.signed()?;
+        if offset.unsigned_abs() > MAX_TAPE_LENGTH as u64 {
+            return None;
+        }
+        isize::try_from(offset).ok()
+    }
+
+    /// Reads an unsigned number that is part of a span.
+    fn position(&mut self) -> Option<usize> {
+        usize::try_from(self.unsigned()?).ok()
+    }
+
+    /// Reads an instruction, as written by "write_instruction".
+    fn instruction(&mut self) -> Option<Instruction> {
+        let kind = match self.byte()? {
+            0 => InstructionKind::Advance,
+            1 => InstructionKind::Recede,
+            2 => InstructionKind::Increment,
+            3 => InstructionKind::Decrement,
+            4 => InstructionKind::Show,
+            5 => InstructionKind::Read,
+            6 => InstructionKind::StartLoop,
+            7 => InstructionKind::StopLoop,
+            8 => InstructionKind::Add(self.signed()?),
+            9 => InstructionKind::Move(self.offset()?),
+            10 => InstructionKind::SetZero,
+            11 => InstructionKind::MulAdd {
+                offset: self.offset()?,
+                factor: self.signed()?,
+            },
+            12 => InstructionKind::ScanRight,
+            13 => InstructionKind::ScanLeft,
+            14 => InstructionKind::AddAt {
+                offset: self.offset()?,
+                amount: self.signed()?,
+            },
+            15 => InstructionKind::OutputAt {
+                offset: self.offset()?,
+            },
+            16 => InstructionKind::Print(u8::try_from(self.signed()?).ok()?),
+            17 => InstructionKind::SetAt {
+                offset: self.offset()?,
+                value: self.signed()?,
+            },
+            _ => return None,
+        };
+        let span = Span::new(
+            self.position()?,
+            self.position()?,
+            self.position()?,
+            self.position()?,
+        );
+        Some(Instruction { kind, span })
+    }
+}
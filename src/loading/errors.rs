@@ -1,12 +1,107 @@
-/// Returns an error message when a file cannot be found with the specified
-/// path.
-pub(crate) fn error_file_not_found(path: &String) -> String {
-    format!("File not found: {}", path)
+/// Errors that may happen while loading a program file.
+#[derive(Debug)]
+pub enum LoadError {
+    /// No file could be found at the given path.
+    NotFound {
+        path: String,
+        source: std::io::Error,
+    },
+    /// The file at the given path could not be opened or read due to lack
+    /// of permission.
+    PermissionDenied {
+        path: String,
+        source: std::io::Error,
+    },
+    /// The contents of the file at the given path are not valid UTF-8.
+    InvalidEncoding {
+        path: String,
+        source: std::string::FromUtf8Error,
+    },
+    /// It was not possible to load the contents of the file at the given
+    /// path, for a reason other than the ones above.
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    /// The file at the given path was expected to be a compiled program,
+    /// but does not start like one.
+    NotCompiled { path: String },
+    /// The file at the given path is a compiled program, written in a
+    /// version of the format this interpreter cannot read.
+    UnsupportedVersion { path: String, version: u16 },
+    /// The file at the given path is a compiled program that was cut short
+    /// or changed since it was written.
+    Corrupted { path: String },
+    /// The file at the given path is a compiled program whose tape, of the
+    /// given number of cells, is longer than a loaded program may ask for.
+    TapeTooLong { path: String, length: u64 },
 }
 
-/// Returns an error message when it was not possible to load the
-/// contents of the file in the program argument path, for a reason
-/// other than finding it.
-pub(crate) fn error_reading_file() -> String {
-    "Failed to read file content".to_string()
+impl LoadError {
+    /// Returns the error that corresponds to the given I/O failure, which
+    /// happened while opening or reading the file at the given path.
+    pub(crate) fn from_io(path: &str, source: std::io::Error) -> Self {
+        let path = path.to_string();
+        match source.kind() {
+            std::io::ErrorKind::NotFound => LoadError::NotFound { path, source },
+            std::io::ErrorKind::PermissionDenied => LoadError::PermissionDenied { path, source },
+            _ => LoadError::Io { path, source },
+        }
+    }
+
+    /// Returns the path of the file that could not be loaded.
+    pub fn path(&self) -> &str {
+        match self {
+            LoadError::NotFound { path, .. }
+            | LoadError::PermissionDenied { path, .. }
+            | LoadError::InvalidEncoding { path, .. }
+            | LoadError::Io { path, .. }
+            | LoadError::NotCompiled { path }
+            | LoadError::UnsupportedVersion { path, .. }
+            | LoadError::Corrupted { path }
+            | LoadError::TapeTooLong { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::NotFound { path, .. } => write!(f, "File not found: {}", path),
+            LoadError::PermissionDenied { path, .. } => write!(f, "Permission denied: {}", path),
+            LoadError::InvalidEncoding { path, .. } => {
+                write!(f, "File is not valid UTF-8: {}", path)
+            }
+            LoadError::Io { path, .. } => write!(f, "Failed to read file content: {}", path),
+            LoadError::NotCompiled { path } => write!(f, "Not a compiled program: {}", path),
+            LoadError::UnsupportedVersion { path, version } => write!(
+                f,
+                "Unsupported version {} of the compiled program format: {}",
+                version, path
+            ),
+            LoadError::Corrupted { path } => write!(f, "Compiled program is corrupted: {}", path),
+            LoadError::TapeTooLong { path, length } => write!(
+                f,
+                "Compiled program asks for a tape of {} cells, more than the limit of {}: {}",
+                length,
+                super::compiled::MAX_TAPE_LENGTH,
+                path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::NotFound { source, .. }
+            | LoadError::PermissionDenied { source, .. }
+            | LoadError::Io { source, .. } => Some(source),
+            LoadError::InvalidEncoding { source, .. } => Some(source),
+            LoadError::NotCompiled { .. }
+            | LoadError::UnsupportedVersion { .. }
+            | LoadError::Corrupted { .. }
+            | LoadError::TapeTooLong { .. } => None,
+        }
+    }
 }
@@ -1,44 +1,64 @@
 use std::io::Read;
 
-/// Attempts to return, as a string, all the content present in the file at
-/// the path given by the program's first argument. In case of failure,
-/// returns an error message explaining the problem encountered. This
-/// message is appropriate for display by the program.
-pub(crate) fn load_program_file() -> Result<String, String> {
-    get_program_path()
-        .and_then(|path: std::string::String| open_file(&path))
-        .and_then(|file: std::fs::File| get_file_content(&file))
+use super::{compiled, CompiledProgram, LoadError};
+
+/// Contents of a program file, which holds either the source code of a
+/// program or a compiled program.
+#[derive(Debug, Clone)]
+pub enum ProgramFile {
+    /// Source code, still to be compiled.
+    Source(String),
+    /// A compiled program, ready to be run.
+    Compiled(CompiledProgram),
 }
 
-/// On failure, return an error message saying that a path was not provided.
-/// This error message is suitable for display by the program.
-fn get_program_path() -> Result<String, String> {
-    let args: Vec<String> = std::env::args().collect();
+/// Attempts to return, as a string, all the content present in the file at
+/// the given path. In case of failure, returns an error explaining the
+/// problem encountered. This error is appropriate for display by the
+/// program.
+pub fn load_program_file(path: &str) -> Result<String, LoadError> {
+    open_file(path)
+        .and_then(|file: std::fs::File| get_file_content(path, &file))
+        .and_then(|content: Vec<u8>| decode_content(path, content))
+}
 
-    if args.len() <= 1 {
-        return Err("No file found".to_string());
+/// Attempts to return the contents of the file at the given path, which
+/// are read as a compiled program when they start like one, and as source
+/// code otherwise. In case of failure, returns an error explaining the
+/// problem encountered. This error is appropriate for display by the
+/// program.
+pub fn load_file(path: &str) -> Result<ProgramFile, LoadError> {
+    let content = open_file(path).and_then(|file| get_file_content(path, &file))?;
+    if compiled::is_compiled(&content) {
+        return compiled::decode(path, &content).map(ProgramFile::Compiled);
     }
-
-    Ok(args[1].clone())
+    decode_content(path, content).map(ProgramFile::Source)
 }
 
-/// Attempts to open and return the file at the given path. If this fails, a
-/// message is returned explaining that a file could not be found at the
-/// given path. This message is suitable for display by the program.
-fn open_file(path: &String) -> Result<std::fs::File, String> {
-    std::fs::File::open(path).map_err(|_| super::error_file_not_found(path))
+/// Attempts to open and return the file at the given path. If this fails,
+/// an error is returned explaining why the file could not be opened.
+fn open_file(path: &str) -> Result<std::fs::File, LoadError> {
+    std::fs::File::open(path).map_err(|e| LoadError::from_io(path, e))
 }
 
-/// Attempts to return, as a string, the entire contents of the given file.
-/// If this fails, it returns an error message stating that it was not
-/// possible to read the contents of the given file. This error message
-/// is suitable for display by the program.
-fn get_file_content(file: &std::fs::File) -> Result<String, String> {
+/// Attempts to return all the bytes of the given file, opened from the
+/// given path. If this fails, it returns an error stating that it was not
+/// possible to read the contents of the file.
+fn get_file_content(path: &str, file: &std::fs::File) -> Result<Vec<u8>, LoadError> {
     let mut buff_reader = std::io::BufReader::new(file);
-    let mut content = String::new();
+    let mut content = Vec::new();
 
-    match buff_reader.read_to_string(&mut content) {
+    match buff_reader.read_to_end(&mut content) {
         Ok(_) => Ok(content),
-        Err(_) => Err(super::error_reading_file()),
+        Err(e) => Err(LoadError::from_io(path, e)),
     }
 }
+
+/// Attempts to decode the given bytes, read from the file at the given
+/// path, as UTF-8 text. Fails if the bytes are not valid UTF-8.
+fn decode_content(path: &str, content: Vec<u8>) -> Result<String, LoadError> {
+    String::from_utf8(content).map_err(|source| LoadError::InvalidEncoding {
+        path: path.to_string(),
+        source,
+    })
+}
@@ -1,14 +1,19 @@
 //! This module encapsulates all the logic of loading the program's source
-//! code from the file at the given path. It defines only a function that
-//! attempts to return the entire contents of the file at once.
+//! code from the file at the given path. It defines functions that attempt
+//! to return the entire contents of the file at once, and the format of
+//! compiled program files, which can be run without being compiled again.
 
-/// This module defines a function to facilitate the generation of error
-/// messages that occur during the program file loading process. These
-/// error messages are suitable for display by the program.
+/// This module defines the compiled program file format (".bfc"), which
+/// holds a program, usually optimized, and the options it was compiled
+/// for.
+pub mod compiled;
+
+/// This module defines the errors that occur during the program file
+/// loading process. These errors are suitable for display by the program.
 mod errors;
-/// This module defines functions to load a file from a command line
-/// argument.
-pub(crate) mod load;
+/// This module defines functions to load a program file from a path.
+pub mod load;
 
-use errors::{error_file_not_found, error_reading_file};
-pub(crate) use load::load_program_file;
+pub use compiled::CompiledProgram;
+pub use errors::LoadError;
+pub use load::{load_file, load_program_file, ProgramFile};
@@ -1,21 +1,89 @@
-mod lexing;
-mod loading;
-mod parsing;
-mod virtual_machine;
-
-use lexing::Lexer;
-use parsing::Parser;
-use virtual_machine::VirtualMachine;
-
-fn main() {
-    let _ = loading::load_program_file()
-        .and_then(|content| Lexer::tokenize(&content))
-        .and_then(|tokens| Parser::parse(&tokens))
-        .map(|instructions| {
-            VirtualMachine::executing(&instructions);
-        })
-        .or_else(|msg| {
-            println!("{}", msg);
-            Ok::<(), String>(())
-        });
+mod cli;
+
+use std::{io::Write, process::ExitCode};
+
+use brain_rusted::loading::{self, ProgramFile};
+use brain_rusted::{BrainError, EmitError, Emitter, MachineOptions, Program, Target};
+use cli::{Arguments, Status};
+
+/// Loads, compiles and runs the program described by the given arguments,
+/// or translates it to another language if asked to.
+fn execute(args: &Arguments) -> Result<(), BrainError> {
+    let (program, machine) = match loading::load_file(&args.path)? {
+        ProgramFile::Source(content) => (compile(&content, args)?, args.machine.clone()),
+        // Compiled programs may rely on the settings they were compiled
+        // with, so only the options that limit the execution are taken
+        // from the arguments.
+        ProgramFile::Compiled(compiled) => (
+            compiled.program,
+            MachineOptions {
+                max_steps: args.machine.max_steps,
+                jit: args.machine.jit,
+                ..compiled.options
+            },
+        ),
+    };
+    match args.emit {
+        Some(target) => {
+            let emitted = brain_rusted::emit(&program, target, &machine)?;
+            Ok(write_output(args.output.as_deref(), target, &emitted)?)
+        }
+        None => Ok(brain_rusted::run_stdio(&program, &machine)?),
+    }
+}
+
+/// Compiles and optimizes the given source code as described by the given
+/// arguments, showing what each optimization pass did if asked to.
+fn compile(content: &str, args: &Arguments) -> Result<Program, BrainError> {
+    let program = brain_rusted::compile_with(content, &args.compile)?;
+    let (program, report) = brain_rusted::optimize_with(&program, &args.optimize, &args.machine);
+    if args.report {
+        eprintln!("{}", report);
+    }
+    Ok(program)
+}
+
+/// Writes the given translated program to the file at the given path, or to
+/// the standard output when there is no path. Executables are written so
+/// they can be run.
+fn write_output(path: Option<&str>, target: Target, emitted: &[u8]) -> Result<(), EmitError> {
+    match path {
+        Some(path) if target == Target::Elf => Emitter::save_executable(path, emitted),
+        Some(path) => Emitter::save(path, emitted),
+        None => std::io::stdout()
+            .write_all(emitted)
+            .map_err(|source| EmitError::Io {
+                path: "<stdout>".to_string(),
+                source,
+            }),
+    }
+}
+
+/// Writes the given error to the standard error, followed by every error
+/// that caused it.
+fn report(error: &dyn std::error::Error) {
+    eprintln!("{}", error);
+    let mut source = error.source();
+    while let Some(cause) = source {
+        eprintln!("caused by: {}", cause);
+        source = cause.source();
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Arguments::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(msg) => {
+            eprintln!("{}\n{}", msg, cli::USAGE);
+            return Status::Usage.into();
+        }
+    };
+
+    match execute(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            report(&error);
+            Status::of(&error).into()
+        }
+    }
 }
@@ -0,0 +1,210 @@
+use crate::parsing::{Instruction, InstructionKind, Program};
+use crate::virtual_machine::{with_cell_type, Cell, MachineOptions, VirtualMachine};
+
+/// Returns the given instructions with the longest prefix that does not
+/// depend on the input, and runs within the given number of steps, replaced
+/// by its effect: the bytes it writes as Prints, the slots it leaves set as
+/// SetAts and the place it leaves the pointer at as a Move. The prefix ends
+/// before the first top-level instruction (or loop) that reads the input,
+/// or earlier if running it goes beyond the budget or fails, in which case
+/// the instruction that could not be run is kept, so it runs (and fails) at
+/// runtime just as before. Programs run with a step limit are left
+/// unchanged, since the evaluated prefix would no longer count towards it.
+pub(super) fn evaluate(
+    instructions: &[Instruction],
+    budget: u64,
+    options: &MachineOptions,
+) -> Vec<Instruction> {
+    if options.max_steps.is_some() {
+        return instructions.to_vec();
+    }
+    with_cell_type!(options.cell, evaluate_prefix(instructions, budget, options))
+}
+
+/// Same as "evaluate", for the given cell type.
+fn evaluate_prefix<C: Cell>(
+    instructions: &[Instruction],
+    budget: u64,
+    options: &MachineOptions,
+) -> Vec<Instruction> {
+    let mut limited = options.clone();
+    limited.max_steps = Some(budget);
+    let mut machine =
+        VirtualMachine::<_, _, C>::with_streams(std::io::empty(), Vec::new(), limited.clone());
+
+    let mut evaluated = 0;
+    for end in top_level_ends(instructions) {
+        let chunk = Program::new(instructions[evaluated..end].to_vec());
+        if machine.execute(&chunk).is_err() {
+            // The machine stopped halfway through the chunk, so the state
+            // right before it is computed again, which is known to succeed.
+            machine = VirtualMachine::with_streams(std::io::empty(), Vec::new(), limited);
+            machine
+                .execute(&Program::new(instructions[..evaluated].to_vec()))
+                .expect("the evaluated prefix already ran successfully");
+            break;
+        }
+        evaluated = end;
+    }
+
+    if evaluated == 0 {
+        return instructions.to_vec();
+    }
+
+    let span = instructions[0].span.to(&instructions[evaluated - 1].span);
+    let tape = machine.tape();
+    let origin = tape.origin() as isize;
+
+    let printed = machine
+        .output()
+        .iter()
+        .map(|&byte| InstructionKind::Print(byte));
+    let stored = tape
+        .cells()
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_zero())
+        .map(|(index, cell)| InstructionKind::SetAt {
+            offset: index as isize - origin,
+            value: cell.to_i64(),
+        });
+    let moved = (tape.position() != 0).then(|| InstructionKind::Move(tape.position() as isize));
+
+    printed
+        .chain(stored)
+        .chain(moved)
+        .map(|kind| Instruction::new(kind, span))
+        .chain(instructions[evaluated..].iter().cloned())
+        .collect()
+}
+
+/// Returns the index right after each top-level instruction (a whole loop
+/// counting as one) that comes before the first one that reads the input.
+fn top_level_ends(instructions: &[Instruction]) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut depth = 0usize;
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction.kind {
+            InstructionKind::Read => break,
+            InstructionKind::StartLoop => depth += 1,
+            InstructionKind::StopLoop => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 {
+            ends.push(index + 1);
+        }
+    }
+
+    ends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing;
+    use super::*;
+
+    /// Returns the instructions of the given source, as written.
+    fn instructions(source: &str) -> Vec<Instruction> {
+        let program = crate::compile(source).expect("valid program");
+        program.instructions().to_vec()
+    }
+
+    /// Returns the kind of each of the given instructions.
+    fn kinds(instructions: &[Instruction]) -> Vec<InstructionKind> {
+        instructions
+            .iter()
+            .map(|instruction| instruction.kind)
+            .collect()
+    }
+
+    #[test]
+    fn prefixes_within_the_budget_become_their_output_and_tape() {
+        let source = "++++++++[>++++++++<-]>+.>++<<,.";
+        let options = MachineOptions::default();
+        let original = instructions(source);
+        let evaluated = evaluate(&original, 1_000, &options);
+
+        assert_eq!(
+            kinds(&evaluated),
+            [
+                InstructionKind::Print(b'A'),
+                InstructionKind::SetAt {
+                    offset: 1,
+                    value: 65
+                },
+                InstructionKind::SetAt {
+                    offset: 2,
+                    value: 2
+                },
+                InstructionKind::Read,
+                InstructionKind::Show,
+            ]
+        );
+        assert_eq!(evaluated[3..], original[original.len() - 2..]);
+
+        let evaluated = Program::new(evaluated);
+        let original = Program::new(original);
+        assert_eq!(
+            testing::run(&evaluated, b"z", &options).unlocated(),
+            testing::run(&original, b"z", &options).unlocated()
+        );
+    }
+
+    #[test]
+    fn prefixes_beyond_the_budget_are_kept_from_where_it_ran_out() {
+        let source = "++++++++[>++++++++<-]>.";
+        let options = MachineOptions::default();
+        let original = instructions(source);
+        let evaluated = evaluate(&original, 20, &options);
+
+        assert_eq!(
+            kinds(&evaluated[..1]),
+            [InstructionKind::SetAt {
+                offset: 0,
+                value: 8
+            }]
+        );
+        assert_eq!(evaluated[1..], original[8..]);
+    }
+
+    #[test]
+    fn programs_are_unchanged_when_nothing_fits_the_budget() {
+        let original = instructions("++++++++[>++++++++<-]>.");
+
+        assert_eq!(evaluate(&original, 0, &MachineOptions::default()), original);
+    }
+
+    #[test]
+    fn instructions_that_fail_are_kept_to_fail_at_runtime() {
+        let options = MachineOptions {
+            tape_length: 4,
+            tape_policy: crate::virtual_machine::TapePolicy::Error,
+            ..MachineOptions::default()
+        };
+        let original = instructions("+.<+");
+        let evaluated = evaluate(&original, 1_000, &options);
+
+        assert_eq!(evaluated[2..], original[2..]);
+        assert_eq!(
+            testing::run(&Program::new(evaluated), b"", &options),
+            testing::run(&Program::new(original), b"", &options)
+        );
+    }
+
+    #[test]
+    fn programs_with_a_step_limit_are_unchanged() {
+        let options = MachineOptions {
+            max_steps: Some(10),
+            ..MachineOptions::default()
+        };
+        let original = instructions("++++++++[>++++++++<-]>+.");
+        let evaluated = evaluate(&original, 1_000, &options);
+
+        assert_eq!(evaluated, original);
+        assert!(matches!(
+            crate::run_with(&Program::new(evaluated), b"", &options),
+            Err(crate::RuntimeError::StepLimitExceeded { limit: 10 })
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+use super::optimizer::{cancels_additions, cancels_moves};
+use crate::diagnostics::Span;
+use crate::parsing::{Instruction, InstructionKind};
+use crate::virtual_machine::MachineOptions;
+
+/// What a run of repeated commands changes: the value of the current
+/// memory slot, or the position of the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Target {
+    Slot,
+    Pointer,
+}
+
+/// A run of commands being folded: what they change, by how much in total,
+/// and the piece of source code they span.
+struct Run {
+    target: Target,
+    amount: i64,
+    span: Span,
+}
+
+/// Returns the given instructions with every run of Increment and
+/// Decrement folded into a single Add, and every run of Advance and Recede
+/// folded into a single Move. Opposing commands cancel each other only when
+/// that is equivalent under the given options; otherwise a change of
+/// direction starts a new run. Runs that cancel completely are removed.
+pub(super) fn fold(instructions: &[Instruction], options: &MachineOptions) -> Vec<Instruction> {
+    let mut folded = Vec::with_capacity(instructions.len());
+    let mut run: Option<Run> = None;
+
+    for instruction in instructions {
+        let Some((target, amount)) = step(instruction.kind) else {
+            if let Some(run) = run.take() {
+                run.finish(&mut folded);
+            }
+            folded.push(instruction.clone());
+            continue;
+        };
+
+        match run.as_mut() {
+            Some(current) if current.absorbs(target, amount, options) => {
+                current.amount += amount;
+                current.span = current.span.to(&instruction.span);
+            }
+            _ => {
+                if let Some(run) = run.take() {
+                    run.finish(&mut folded);
+                }
+                run = Some(Run {
+                    target,
+                    amount,
+                    span: instruction.span,
+                });
+            }
+        }
+    }
+
+    if let Some(run) = run {
+        run.finish(&mut folded);
+    }
+    folded
+}
+
+/// Returns what the given instruction changes and by how much, if it can
+/// be folded at all.
+pub(super) fn step(kind: InstructionKind) -> Option<(Target, i64)> {
+    match kind {
+        InstructionKind::Increment => Some((Target::Slot, 1)),
+        InstructionKind::Decrement => Some((Target::Slot, -1)),
+        InstructionKind::Add(amount) => Some((Target::Slot, amount)),
+        InstructionKind::Advance => Some((Target::Pointer, 1)),
+        InstructionKind::Recede => Some((Target::Pointer, -1)),
+        InstructionKind::Move(offset) => Some((Target::Pointer, offset as i64)),
+        _ => None,
+    }
+}
+
+impl Run {
+    /// Returns whether or not the given step can join this run, under the
+    /// given options.
+    fn absorbs(&self, target: Target, amount: i64, options: &MachineOptions) -> bool {
+        let cancels = match target {
+            Target::Slot => cancels_additions(options),
+            Target::Pointer => cancels_moves(options),
+        };
+        let same_direction = self.amount.signum() * amount.signum() >= 0;
+
+        target == self.target
+            && (same_direction || cancels)
+            && self.amount.checked_add(amount).is_some()
+    }
+
+    /// Writes the instruction equivalent to this run to the given
+    /// instructions, unless the run does nothing at all.
+    fn finish(self, instructions: &mut Vec<Instruction>) {
+        let kind = match self.target {
+            _ if self.amount == 0 => return,
+            Target::Slot => InstructionKind::Add(self.amount),
+            Target::Pointer => InstructionKind::Move(self.amount as isize),
+        };
+        instructions.push(Instruction::new(kind, self.span));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{self, CELLS, OVERFLOWS};
+    use super::super::OptLevel;
+    use super::*;
+    use crate::virtual_machine::{CellType, OverflowPolicy, TapePolicy};
+
+    /// Programs made of runs that go one way, the other, or both, some of
+    /// which go past the limits of the cells or the ends of a short tape.
+    const PROGRAMS: [&str; 8] = [
+        "+++++---.",
+        "--+++.-+-+.",
+        "<>+.>>><<<+.",
+        ">>>><<<<<+.",
+        "<<<>>>+.",
+        "-+.+-.",
+        "++++++++[>++++++++<-]>+.>-<--+++.",
+        "++++++++++++++++[>++++++++++++++++<-]>[+-]-.",
+    ];
+
+    /// Returns the kinds of the instructions of the given source folded
+    /// under the given options.
+    fn kinds(source: &str, options: &MachineOptions) -> Vec<InstructionKind> {
+        testing::optimized(source, OptLevel::O1, options)
+            .instructions()
+            .iter()
+            .map(|instruction| instruction.kind)
+            .collect()
+    }
+
+    /// Returns the options the programs are checked with, for the given
+    /// cell type, overflow policy and tape policy, on a short tape.
+    fn options(
+        cell: CellType,
+        overflow: OverflowPolicy,
+        tape_policy: TapePolicy,
+    ) -> MachineOptions {
+        MachineOptions {
+            tape_length: 4,
+            max_tape_length: Some(6),
+            tape_policy,
+            cell,
+            overflow,
+            ..MachineOptions::default()
+        }
+    }
+
+    #[test]
+    fn runs_are_folded_into_single_instructions() {
+        let options = MachineOptions::default();
+
+        assert_eq!(kinds("++++", &options), [InstructionKind::Add(4)]);
+        assert_eq!(kinds("----", &options), [InstructionKind::Add(-4)]);
+        assert_eq!(kinds(">>>", &options), [InstructionKind::Move(3)]);
+        assert_eq!(kinds("<<<", &options), [InstructionKind::Move(-3)]);
+        assert_eq!(
+            kinds("+++.>>", &options),
+            [
+                InstructionKind::Add(3),
+                InstructionKind::Show,
+                InstructionKind::Move(2)
+            ]
+        );
+    }
+
+    #[test]
+    fn opposing_runs_cancel_when_slots_and_the_tape_wrap() {
+        let options = MachineOptions::default();
+
+        assert_eq!(kinds("+-", &options), []);
+        assert_eq!(kinds("<>", &options), []);
+        assert_eq!(kinds("+++--", &options), [InstructionKind::Add(1)]);
+        assert_eq!(kinds("<<>>>", &options), [InstructionKind::Move(1)]);
+    }
+
+    #[test]
+    fn opposing_runs_are_kept_when_they_do_not_cancel() {
+        for overflow in [OverflowPolicy::Saturate, OverflowPolicy::Trap] {
+            let options = MachineOptions {
+                overflow,
+                ..MachineOptions::default()
+            };
+            assert!(!cancels_additions(&options));
+            assert_eq!(
+                kinds("++-", &options),
+                [InstructionKind::Add(2), InstructionKind::Add(-1)],
+                "{overflow:?}"
+            );
+            assert_eq!(kinds("<>", &options), [], "{overflow:?}");
+        }
+
+        let error = MachineOptions {
+            tape_policy: TapePolicy::Error,
+            ..MachineOptions::default()
+        };
+        let limited = MachineOptions {
+            tape_policy: TapePolicy::Grow,
+            max_tape_length: Some(100),
+            ..MachineOptions::default()
+        };
+        for options in [error, limited] {
+            assert!(!cancels_moves(&options));
+            assert_eq!(
+                kinds("<<>", &options),
+                [InstructionKind::Move(-2), InstructionKind::Move(1)],
+                "{:?}",
+                options.tape_policy
+            );
+            assert_eq!(kinds("+-", &options), []);
+        }
+
+        let unlimited = MachineOptions {
+            tape_policy: TapePolicy::Grow,
+            ..MachineOptions::default()
+        };
+        assert!(cancels_moves(&unlimited));
+    }
+
+    #[test]
+    fn folded_runs_run_like_the_original_ones() {
+        for source in PROGRAMS {
+            for cell in CELLS {
+                for overflow in OVERFLOWS {
+                    for tape_policy in [TapePolicy::Wrap, TapePolicy::Error, TapePolicy::Grow] {
+                        let options = options(cell, overflow, tape_policy);
+                        let unoptimized = testing::optimized(source, OptLevel::O0, &options);
+                        let folded = testing::optimized(source, OptLevel::O1, &options);
+                        let expected = testing::run(&unoptimized, b"", &options);
+                        let actual = testing::run(&folded, b"", &options);
+
+                        assert_eq!(
+                            (actual.output, actual.error),
+                            (expected.output, expected.error),
+                            "{source:?} with {cell:?} cells, {overflow:?} on overflow \
+                             and {tape_policy:?} at the tape ends"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use super::folding::{step, Target};
+use super::optimizer::cancels_moves;
+use crate::diagnostics::Span;
+use crate::parsing::{Instruction, InstructionKind};
+use crate::virtual_machine::MachineOptions;
+
+/// The pointer moves of a basic block that have not been written yet: how
+/// far the pointer is from where the block started, and the piece of
+/// source code those moves span.
+struct Moves {
+    offset: isize,
+    span: Option<Span>,
+}
+
+/// Returns the given instructions with every basic block (a run of
+/// additions, moves and shows between other instructions) rewritten so that
+/// additions and shows address slots by their distance from the pointer,
+/// followed by a single move of the pointer to where the block leaves it.
+/// Blocks are only rewritten when no move can fail under the given options,
+/// since the moves in between are no longer made.
+pub(super) fn fuse(instructions: &[Instruction], options: &MachineOptions) -> Vec<Instruction> {
+    if !cancels_moves(options) {
+        return instructions.to_vec();
+    }
+
+    let mut fused = Vec::with_capacity(instructions.len());
+    let mut moves = Moves {
+        offset: 0,
+        span: None,
+    };
+
+    for instruction in instructions {
+        let offset = moves.offset;
+        let kind = match (instruction.kind, step(instruction.kind)) {
+            (_, Some((Target::Pointer, amount))) => {
+                moves.add(amount as isize, &instruction.span);
+                continue;
+            }
+            (_, Some((Target::Slot, amount))) if offset != 0 => {
+                InstructionKind::AddAt { offset, amount }
+            }
+            (InstructionKind::Show, _) if offset != 0 => InstructionKind::OutputAt { offset },
+            (kind, Some(_)) | (kind @ InstructionKind::Show, _) => kind,
+            (kind, None) => {
+                moves.finish(&mut fused);
+                kind
+            }
+        };
+        fused.push(Instruction::new(kind, instruction.span));
+    }
+
+    moves.finish(&mut fused);
+    fused
+}
+
+impl Moves {
+    /// Adds the given move, found at the given span, to the pending ones.
+    fn add(&mut self, offset: isize, span: &Span) {
+        self.offset += offset;
+        self.span = Some(self.span.map_or(*span, |start| start.to(span)));
+    }
+
+    /// Writes a single move equivalent to the pending ones to the given
+    /// instructions, unless they cancel each other, and clears them.
+    fn finish(&mut self, instructions: &mut Vec<Instruction>) {
+        if let Some(span) = self.span.take() {
+            if self.offset != 0 {
+                instructions.push(Instruction::new(InstructionKind::Move(self.offset), span));
+            }
+        }
+        self.offset = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{self, CELLS, OVERFLOWS};
+    use super::super::{OptLevel, OptimizeOptions, Optimizer, Pass};
+    use super::*;
+    use crate::parsing::Program;
+    use crate::virtual_machine::TapePolicy;
+
+    /// Programs whose basic blocks end at loops, reads and shows, with
+    /// moves that cross either end of a short tape and additions that
+    /// overflow narrow cells.
+    const PROGRAMS: [&str; 9] = [
+        "+>++>+++<<.>.>.",
+        "+>+[>+<-<]>>.",
+        "+++[>+++[>++<-]<-]>>.",
+        ",>,<[->+<]>.",
+        ",[>+>+<<-]>.>.",
+        "<<<+>>>-<<<.",
+        ">>>>>>>>>>>>>>>>>>+.<<<<<<<<<<<<<<<<<<.",
+        "+[>+<-]>[<+>>+<-]<.>>.",
+        ">++++++++++++++++[<++++++++++++++++>-]<[>>+<<-]>->.<.",
+    ];
+
+    /// Returns the given source compiled and optimized at -O3 for the given
+    /// options, with or without fusion.
+    fn optimized(source: &str, fusion: bool, options: &MachineOptions) -> Program {
+        let program = crate::compile(source).expect("valid program");
+        let optimize = OptimizeOptions {
+            disabled: if fusion { vec![] } else { vec![Pass::Fusion] },
+            ..OptimizeOptions::default()
+        };
+        Optimizer::optimize_with(&program, &optimize, options).0
+    }
+
+    #[test]
+    fn blocks_address_slots_by_offset() {
+        let options = MachineOptions::default();
+        let program = optimized(">>+<.>>,", true, &options);
+        let kinds: Vec<InstructionKind> = program
+            .instructions()
+            .iter()
+            .map(|instruction| instruction.kind)
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                InstructionKind::AddAt {
+                    offset: 2,
+                    amount: 1
+                },
+                InstructionKind::OutputAt { offset: 1 },
+                InstructionKind::Move(3),
+                InstructionKind::Read,
+            ]
+        );
+    }
+
+    #[test]
+    fn fused_blocks_run_like_the_original_ones() {
+        for source in PROGRAMS {
+            for tape_policy in [TapePolicy::Wrap, TapePolicy::Grow] {
+                for cell in CELLS {
+                    for overflow in OVERFLOWS {
+                        let options = MachineOptions {
+                            tape_length: 16,
+                            tape_policy,
+                            cell,
+                            overflow,
+                            ..MachineOptions::default()
+                        };
+                        let unoptimized = testing::optimized(source, OptLevel::O0, &options);
+                        let unfused = optimized(source, false, &options);
+                        let fused = optimized(source, true, &options);
+                        let expected = testing::run(&unfused, b"\x05\x03", &options);
+                        let context = format!("{source:?} with {options:?}");
+
+                        assert_eq!(
+                            testing::run(&fused, b"\x05\x03", &options),
+                            expected,
+                            "{context}"
+                        );
+                        assert_eq!(
+                            testing::run(&unoptimized, b"\x05\x03", &options).unlocated(),
+                            expected.unlocated(),
+                            "{context}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tape_errors_keep_their_location() {
+        let bounded = [
+            MachineOptions {
+                tape_length: 3,
+                tape_policy: TapePolicy::Error,
+                ..MachineOptions::default()
+            },
+            MachineOptions {
+                tape_length: 3,
+                tape_policy: TapePolicy::Grow,
+                max_tape_length: Some(4),
+                ..MachineOptions::default()
+            },
+        ];
+        for options in bounded {
+            for source in ["+>>>>+<<<<.", "+\n>>\n>>>+.", "<<+>>", ">+>+[>+<-]>>>>>+"] {
+                let unfused = testing::run(&optimized(source, false, &options), b"", &options);
+                let fused = testing::run(&optimized(source, true, &options), b"", &options);
+
+                assert!(unfused.location.is_some(), "{source:?} must fail");
+                assert_eq!(fused, unfused, "{source:?} with {options:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+
+use super::folding::{step, Target};
+use super::optimizer::{cancels_additions, cancels_moves};
+use crate::parsing::{Instruction, InstructionKind};
+use crate::virtual_machine::{MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Returns the given instructions with every innermost loop that matches a
+/// known idiom replaced by dedicated instructions: clear loops become
+/// SetZero, balanced multiply loops become MulAdds followed by a SetZero,
+/// and "[>]" and "[<]" become ScanRight and ScanLeft. A loop is only
+/// replaced when that is equivalent under the given options.
+pub(super) fn rewrite(instructions: &[Instruction], options: &MachineOptions) -> Vec<Instruction> {
+    let mut rewritten = Vec::with_capacity(instructions.len());
+    let mut index = 0;
+
+    while index < instructions.len() {
+        if instructions[index].kind != InstructionKind::StartLoop {
+            rewritten.push(instructions[index].clone());
+            index += 1;
+            continue;
+        }
+
+        let body_start = index + 1;
+        let body_end = body_start
+            + instructions[body_start..]
+                .iter()
+                .position(|instruction| is_loop_bound(instruction.kind))
+                .expect("programs have balanced loops");
+
+        let is_innermost = instructions[body_end].kind == InstructionKind::StopLoop;
+        let replacement = is_innermost
+            .then(|| replace(&instructions[body_start..body_end], options))
+            .flatten();
+
+        match replacement {
+            Some(kinds) => {
+                let span = instructions[index].span.to(&instructions[body_end].span);
+                rewritten.extend(kinds.into_iter().map(|kind| Instruction::new(kind, span)));
+                index = body_end + 1;
+            }
+            None => {
+                rewritten.extend_from_slice(&instructions[index..body_end]);
+                index = body_end;
+            }
+        }
+    }
+
+    rewritten
+}
+
+/// Returns whether or not the given instruction starts or stops a loop.
+fn is_loop_bound(kind: InstructionKind) -> bool {
+    matches!(kind, InstructionKind::StartLoop | InstructionKind::StopLoop)
+}
+
+/// Returns the instructions that replace a loop with the given body, which
+/// has no loops inside, if it is a known idiom that can be replaced under
+/// the given options.
+fn replace(body: &[Instruction], options: &MachineOptions) -> Option<Vec<InstructionKind>> {
+    match body
+        .iter()
+        .map(|instruction| step(instruction.kind))
+        .collect::<Option<Vec<_>>>()?[..]
+    {
+        [(Target::Pointer, 1)] => Some(vec![InstructionKind::ScanRight]),
+        [(Target::Pointer, -1)] => Some(vec![InstructionKind::ScanLeft]),
+        ref steps => replace_balanced(steps, options),
+    }
+}
+
+/// Returns the instructions that replace a loop made of the given steps,
+/// if the loop ends where it started and runs a number of times that only
+/// depends on the value of the current memory slot. Then each other slot it
+/// touches gets that number of iterations times what one iteration adds to
+/// it, and the current slot ends at 0. Unless slots wrap, a slot that goes
+/// up and down within an iteration could overflow halfway, so such loops
+/// are kept.
+fn replace_balanced(
+    steps: &[(Target, i64)],
+    options: &MachineOptions,
+) -> Option<Vec<InstructionKind>> {
+    let mut position: i64 = 0;
+    let (mut lowest, mut highest) = (0, 0);
+    let mut changes = BTreeMap::new();
+
+    for &(target, amount) in steps {
+        match target {
+            Target::Pointer => {
+                position = position.checked_add(amount)?;
+                lowest = lowest.min(position);
+                highest = highest.max(position);
+            }
+            Target::Slot => {
+                let change: &mut i64 = changes.entry(position).or_default();
+                if change.signum() * amount.signum() < 0 && !cancels_additions(options) {
+                    return None;
+                }
+                *change = change.checked_add(amount)?;
+            }
+        }
+    }
+    changes.retain(|_, change| *change != 0);
+
+    let counter = changes.remove(&0)?;
+    if position != 0 || !reaches_safely(lowest, highest, &changes, options) {
+        return None;
+    }
+
+    // Each iteration adds "counter" to the current slot. Wrapping slots
+    // whose counter is odd always reach 0, but the number of iterations is
+    // only easy to compute when the counter is 1 or -1. Other slots only
+    // reach 0 without overflowing when they are unsigned and count down.
+    let factor_sign = match counter {
+        -1 if counts_down(options) => 1,
+        1 if cancels_additions(options) => -1,
+        _ if changes.is_empty() && cancels_additions(options) && counter % 2 != 0 => 0,
+        _ => return None,
+    };
+
+    let mut kinds = Vec::with_capacity(changes.len() + 1);
+    for (offset, change) in changes {
+        let factor = change.checked_mul(factor_sign)?;
+        let offset = isize::try_from(offset).ok()?;
+        kinds.push(InstructionKind::MulAdd { offset, factor });
+    }
+    kinds.push(InstructionKind::SetZero);
+    Some(kinds)
+}
+
+/// Returns whether or not the current slot reaches 0 by counting down,
+/// under the given options, without ever overflowing: slots that wrap
+/// always do, and unsigned slots do under any overflow policy.
+fn counts_down(options: &MachineOptions) -> bool {
+    cancels_additions(options) || !options.cell.is_signed()
+}
+
+/// Returns whether or not replacing a loop that moves the pointer between
+/// the given lowest and highest distances from the current slot, and
+/// changes the given slots, keeps every fault the loop could cause, and
+/// keeps every slot it touches apart from the others, under the given
+/// options. When both moves and additions may fail, which one fails first
+/// depends on the order of the loop, so such loops are kept.
+fn reaches_safely(
+    lowest: i64,
+    highest: i64,
+    changes: &BTreeMap<i64, i64>,
+    options: &MachineOptions,
+) -> bool {
+    let touched = |offset: i64| offset == 0 || changes.contains_key(&offset);
+    let keeps_faults = cancels_moves(options)
+        || touched(lowest)
+            && touched(highest)
+            && (changes.is_empty() || options.overflow != OverflowPolicy::Trap);
+    let keeps_apart = options.tape_policy != TapePolicy::Wrap
+        || usize::try_from(highest - lowest).is_ok_and(|span| span < options.tape_length);
+
+    keeps_faults && keeps_apart
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{self, CELLS, OVERFLOWS};
+    use super::super::OptLevel;
+    use super::*;
+    use crate::virtual_machine::CellType;
+
+    /// Programs built around each idiom, with the slots set so that the
+    /// idiom overflows some cell types, and whether or not they take few
+    /// enough steps to run unoptimized on wide cells, where counting down
+    /// from a negative value takes billions of steps.
+    const PROGRAMS: [(&str, bool); 9] = [
+        ("+++++[-]>+.", true),
+        ("---[-]>+.", false),
+        (
+            ">++++++++++[<++++++++++>-]<[->>++++++++++<<]>++++++++++[<+++++>-]<[->+<]>.",
+            true,
+        ),
+        ("++++++++++[>++++++++++<-]>[->++>+++<<]>.>.", true),
+        ("-[->+<]>.", false),
+        ("-[->+++<]>.", false),
+        ("+>++>+++>>+<<<<[>]<.", true),
+        ("+>+>+>>+<[<]>.", true),
+        ("+>+>+[<]>.", true),
+    ];
+
+    /// Returns the options the programs are checked with, for the given
+    /// cell type and overflow policy, on a short tape that wraps around.
+    fn options(cell: CellType, overflow: OverflowPolicy) -> MachineOptions {
+        MachineOptions {
+            tape_length: 16,
+            cell,
+            overflow,
+            ..MachineOptions::default()
+        }
+    }
+
+    #[test]
+    fn idioms_are_replaced_with_dedicated_instructions() {
+        let options = MachineOptions::default();
+        let kinds = |source| {
+            let program = testing::optimized(source, OptLevel::O2, &options);
+            program
+                .instructions()
+                .iter()
+                .map(|instruction| instruction.kind)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kinds("[-]"), [InstructionKind::SetZero]);
+        assert_eq!(
+            kinds("[->+<]"),
+            [
+                InstructionKind::MulAdd {
+                    offset: 1,
+                    factor: 1
+                },
+                InstructionKind::SetZero
+            ]
+        );
+        assert_eq!(
+            kinds("[->++>+++<<]"),
+            [
+                InstructionKind::MulAdd {
+                    offset: 1,
+                    factor: 2
+                },
+                InstructionKind::MulAdd {
+                    offset: 2,
+                    factor: 3
+                },
+                InstructionKind::SetZero
+            ]
+        );
+        assert_eq!(kinds("[>]"), [InstructionKind::ScanRight]);
+        assert_eq!(kinds("[<]"), [InstructionKind::ScanLeft]);
+    }
+
+    #[test]
+    fn idioms_run_like_the_loops_they_replace() {
+        for (source, wide) in PROGRAMS {
+            for cell in CELLS.into_iter().filter(|cell| wide || cell.bits() == 8) {
+                for overflow in OVERFLOWS {
+                    let options = options(cell, overflow);
+                    let unoptimized = testing::optimized(source, OptLevel::O0, &options);
+                    let expected = testing::run(&unoptimized, b"", &options).unlocated();
+
+                    for level in [OptLevel::O2, OptLevel::O3] {
+                        let optimized = testing::optimized(source, level, &options);
+                        assert_eq!(
+                            testing::run(&optimized, b"", &options).unlocated(),
+                            expected,
+                            "{source:?} at {level:?} with {cell:?} cells, {overflow:?} on overflow"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn idioms_fail_like_the_loops_they_replace() {
+        let options = MachineOptions {
+            tape_length: 16,
+            tape_policy: TapePolicy::Error,
+            ..MachineOptions::default()
+        };
+        let sources = [
+            "+[<]".to_string(),
+            format!("{}+[>]", "+>".repeat(15)),
+            "+[-<+>]".to_string(),
+            format!("+[-{}+{}]", ">".repeat(16), "<".repeat(16)),
+        ];
+        for source in &sources {
+            let unoptimized = testing::optimized(source, OptLevel::O0, &options);
+            let optimized = testing::optimized(source, OptLevel::O2, &options);
+            let expected = testing::run(&unoptimized, b"", &options).unlocated();
+
+            assert!(expected.error.is_some(), "{source:?} must fail");
+            assert_eq!(
+                testing::run(&optimized, b"", &options).unlocated(),
+                expected,
+                "{source:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+use super::{OptimizationReport, OptimizeOptions, Pass, PassReport};
+use crate::parsing::{Instruction, Program};
+use crate::virtual_machine::MachineOptions;
+
+/// Runs a sequence of optimization passes over a program, one after the
+/// other, keeping a report of what each pass did.
+#[derive(Debug, Clone)]
+pub struct PassManager {
+    /// Passes to run, in the order they run.
+    passes: Vec<Pass>,
+    /// Maximum number of steps the passes that run code at compile time
+    /// may execute.
+    evaluation_budget: u64,
+}
+
+impl PassManager {
+    /// Returns a pass manager that runs the given passes, in the given
+    /// order, with the default evaluation budget.
+    pub fn new(passes: Vec<Pass>) -> Self {
+        Self {
+            passes,
+            evaluation_budget: OptimizeOptions::DEFAULT_EVALUATION_BUDGET,
+        }
+    }
+
+    /// Returns a pass manager that runs the passes chosen by the given
+    /// options, with their evaluation budget.
+    pub fn from_options(options: &OptimizeOptions) -> Self {
+        Self {
+            passes: options.passes(),
+            evaluation_budget: options.evaluation_budget,
+        }
+    }
+
+    /// Returns the passes this manager runs, in the order they run.
+    pub fn passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    /// Returns the given program rewritten by every pass, for a program
+    /// that will be run with the given options, and a report of how each
+    /// pass changed it.
+    pub fn run(
+        &self,
+        program: &Program,
+        options: &MachineOptions,
+    ) -> (Program, OptimizationReport) {
+        let mut instructions = program.instructions().to_vec();
+        let mut reports = Vec::with_capacity(self.passes.len());
+
+        for pass in &self.passes {
+            let rewritten = pass.run(&instructions, self.evaluation_budget, options);
+            reports.push(PassReport {
+                pass: *pass,
+                before: instructions.len(),
+                after: rewritten.len(),
+                rewritten: count_new(&instructions, &rewritten),
+            });
+            instructions = rewritten;
+        }
+
+        (Program::new(instructions), OptimizationReport::new(reports))
+    }
+}
+
+/// Returns how many of the given rewritten instructions are not found, with
+/// the same kind and span, among the given original ones: the ones a pass
+/// produced instead of keeping.
+fn count_new(original: &[Instruction], rewritten: &[Instruction]) -> usize {
+    let original: HashSet<&Instruction> = original.iter().collect();
+    rewritten
+        .iter()
+        .filter(|instruction| !original.contains(instruction))
+        .count()
+}
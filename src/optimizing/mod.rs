@@ -0,0 +1,52 @@
+//! The optimizing module encapsulates the optional rewriting of a parsed
+//! program into an equivalent one that runs faster. Rewrites are organized
+//! in named passes, run in sequence by a pass manager, and grouped into
+//! optimization levels. Optimizations are made for the options the program
+//! will be run with, since some rewrites are only equivalent under some of
+//! them. Programs that are not optimized are run exactly as written, which
+//! is useful to compare results.
+
+/// This module defines the partial evaluation pass, which runs the part of
+/// a program that does not depend on the input at compile time.
+mod evaluation;
+
+/// This module defines the folding pass, which merges runs of repeated
+/// commands into a single instruction.
+mod folding;
+
+/// This module defines the fusion pass, which addresses slots by their
+/// distance from the pointer instead of moving it around.
+mod fusion;
+
+/// This module defines the idiom pass, which replaces common loops with
+/// dedicated instructions.
+mod idioms;
+
+/// This module defines the PassManager type, which runs a sequence of
+/// passes over a program.
+pub mod manager;
+
+/// This module defines the Optimizer type, which interfaces with the
+/// optimization passes.
+pub mod optimizer;
+
+/// This module defines the options that choose the passes to run, and the
+/// optimization levels.
+pub mod options;
+
+/// This module defines the Pass type, which names each optimization pass.
+pub mod pass;
+
+/// This module defines the reports of what each pass did to a program.
+pub mod report;
+
+/// This module defines helpers shared by the tests of the passes, which run
+/// programs with and without them and compare what they do.
+#[cfg(test)]
+mod testing;
+
+pub use manager::PassManager;
+pub use optimizer::Optimizer;
+pub use options::{OptLevel, OptimizeOptions};
+pub use pass::Pass;
+pub use report::{OptimizationReport, PassReport};
@@ -0,0 +1,47 @@
+use super::{OptimizationReport, OptimizeOptions, PassManager};
+use crate::parsing::Program;
+use crate::virtual_machine::{MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Static type that has the optimization logic and serves as a public
+/// interface.
+pub struct Optimizer {}
+
+impl Optimizer {
+    /// Returns a program that behaves exactly as the given one when run
+    /// with the given options, but executes fewer instructions, using the
+    /// default optimization level. The same program may be optimized
+    /// differently for different options.
+    pub fn optimize(program: &Program, options: &MachineOptions) -> Program {
+        Self::optimize_with(program, &OptimizeOptions::default(), options).0
+    }
+
+    /// Returns a program that behaves exactly as the given one when run
+    /// with the given machine options, rewritten by the passes chosen by the
+    /// given optimization options, and a report of what each pass did.
+    pub fn optimize_with(
+        program: &Program,
+        optimize: &OptimizeOptions,
+        options: &MachineOptions,
+    ) -> (Program, OptimizationReport) {
+        PassManager::from_options(optimize).run(program, options)
+    }
+}
+
+/// Returns whether or not adding and then subtracting the same amount to a
+/// memory slot always leaves it unchanged under the given options. Only
+/// wrapping slots do: saturating slots lose what went past the limit, and
+/// trapping slots stop the program.
+pub(super) fn cancels_additions(options: &MachineOptions) -> bool {
+    options.overflow == OverflowPolicy::Wrap
+}
+
+/// Returns whether or not moving the pointer and then moving it back always
+/// leaves the program unchanged under the given options. It does unless
+/// going past either end of the tape can stop the program.
+pub(super) fn cancels_moves(options: &MachineOptions) -> bool {
+    match options.tape_policy {
+        TapePolicy::Wrap => true,
+        TapePolicy::Error => false,
+        TapePolicy::Grow => options.max_tape_length.is_none(),
+    }
+}
@@ -0,0 +1,73 @@
+use super::Pass;
+
+/// Options that control which optimization passes run over a program.
+#[derive(Debug, Clone)]
+pub struct OptimizeOptions {
+    /// Preset that gives the passes to run.
+    pub level: OptLevel,
+    /// Passes to run even if the level does not include them.
+    pub enabled: Vec<Pass>,
+    /// Passes not to run even if the level includes them. Disabling wins
+    /// over enabling.
+    pub disabled: Vec<Pass>,
+    /// Maximum number of steps the passes that run code at compile time may
+    /// execute. When it is not enough, they do as much as they can.
+    pub evaluation_budget: u64,
+}
+
+impl OptimizeOptions {
+    /// Number of steps passes may run at compile time when no budget is
+    /// given.
+    pub const DEFAULT_EVALUATION_BUDGET: u64 = 1_000_000;
+
+    /// Returns the passes to run according to these options, in the order
+    /// they run.
+    pub fn passes(&self) -> Vec<Pass> {
+        let level = self.level.passes();
+        Pass::ALL
+            .into_iter()
+            .filter(|pass| level.contains(pass) || self.enabled.contains(pass))
+            .filter(|pass| !self.disabled.contains(pass))
+            .collect()
+    }
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            level: OptLevel::default(),
+            enabled: Vec::new(),
+            disabled: Vec::new(),
+            evaluation_budget: Self::DEFAULT_EVALUATION_BUDGET,
+        }
+    }
+}
+
+/// Presets of optimization passes, from none at all to every pass that is
+/// worth running by default. Passes that run code at compile time are never
+/// part of a preset, since they may take long; they must be enabled by
+/// name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptLevel {
+    /// No passes: the program runs exactly as written.
+    O0,
+    /// Folds runs of repeated commands.
+    O1,
+    /// Also replaces common loops with dedicated instructions.
+    O2,
+    /// Also addresses slots by offset in straight-line code.
+    #[default]
+    O3,
+}
+
+impl OptLevel {
+    /// Returns the passes this level runs, in the order they run.
+    pub fn passes(&self) -> &'static [Pass] {
+        match self {
+            OptLevel::O0 => &[],
+            OptLevel::O1 => &[Pass::Folding],
+            OptLevel::O2 => &[Pass::Folding, Pass::Idioms],
+            OptLevel::O3 => &[Pass::Folding, Pass::Idioms, Pass::Fusion],
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use super::{evaluation, folding, fusion, idioms};
+use crate::parsing::Instruction;
+use crate::virtual_machine::MachineOptions;
+
+/// Every optimization pass, each one a rewrite of the instructions of a
+/// program into equivalent ones. Passes run in the order they are declared
+/// here, since later ones build on what earlier ones produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// Folds runs of repeated commands into Add and Move.
+    Folding,
+    /// Replaces clear, multiply and scan loops with SetZero, MulAdd,
+    /// ScanRight and ScanLeft.
+    Idioms,
+    /// Rewrites straight-line code into AddAt and OutputAt, moving the
+    /// pointer once per basic block.
+    Fusion,
+    /// Runs the part of the program that comes before its first read at
+    /// compile time, replacing it with its output and the state of the
+    /// tape it leaves.
+    PartialEvaluation,
+}
+
+impl Pass {
+    /// Every pass, in the order they run.
+    pub const ALL: [Pass; 4] = [
+        Pass::Folding,
+        Pass::Idioms,
+        Pass::Fusion,
+        Pass::PartialEvaluation,
+    ];
+
+    /// Returns the name of this pass, used to enable or disable it by name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pass::Folding => "folding",
+            Pass::Idioms => "idioms",
+            Pass::Fusion => "fusion",
+            Pass::PartialEvaluation => "partial-evaluation",
+        }
+    }
+
+    /// Returns the pass with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<Pass> {
+        Self::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+
+    /// Returns the given instructions rewritten by this pass, for a program
+    /// that will be run with the given options. Passes that run code at
+    /// compile time stop after the given number of steps.
+    pub(super) fn run(
+        &self,
+        instructions: &[Instruction],
+        budget: u64,
+        options: &MachineOptions,
+    ) -> Vec<Instruction> {
+        match self {
+            Pass::Folding => folding::fold(instructions, options),
+            Pass::Idioms => idioms::rewrite(instructions, options),
+            Pass::Fusion => fusion::fuse(instructions, options),
+            Pass::PartialEvaluation => evaluation::evaluate(instructions, budget, options),
+        }
+    }
+}
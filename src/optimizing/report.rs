@@ -0,0 +1,64 @@
+use super::Pass;
+
+/// What an optimization pass did to a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PassReport {
+    /// The pass that ran.
+    pub pass: Pass,
+    /// Number of instructions before the pass ran.
+    pub before: usize,
+    /// Number of instructions after the pass ran.
+    pub after: usize,
+    /// Number of instructions the pass produced, instead of keeping them as
+    /// they were.
+    pub rewritten: usize,
+}
+
+impl PassReport {
+    /// Returns how many instructions the pass removed, in total.
+    pub fn removed(&self) -> usize {
+        self.before.saturating_sub(self.after)
+    }
+}
+
+/// What every optimization pass did to a program, in the order they ran.
+/// It is displayed as a table, one pass per line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizationReport {
+    /// Report of each pass, in the order they ran.
+    passes: Vec<PassReport>,
+}
+
+impl OptimizationReport {
+    /// Returns a new report made of the given reports of each pass.
+    pub(crate) fn new(passes: Vec<PassReport>) -> Self {
+        Self { passes }
+    }
+
+    /// Returns the report of each pass, in the order they ran.
+    pub fn passes(&self) -> &[PassReport] {
+        &self.passes
+    }
+}
+
+impl std::fmt::Display for OptimizationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<18} {:>10} {:>10} {:>10} {:>10}",
+            "pass", "before", "after", "removed", "rewritten"
+        )?;
+        for report in &self.passes {
+            write!(
+                f,
+                "\n{:<18} {:>10} {:>10} {:>10} {:>10}",
+                report.pass.name(),
+                report.before,
+                report.after,
+                report.removed(),
+                report.rewritten
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use super::{OptLevel, OptimizeOptions, Optimizer};
+use crate::parsing::Program;
+use crate::virtual_machine::{
+    with_cell_type, Cell, CellType, MachineOptions, OverflowPolicy, RuntimeError, VirtualMachine,
+};
+
+/// Steps a program may take before it is considered endless. Programs that
+/// reach it must reach it whether they are optimized or not.
+pub(super) const STEP_LIMIT: u64 = 1_000_000;
+
+/// Cell types the passes are checked with: the narrowest unsigned and
+/// signed ones, which overflow quickly, and a wider one.
+pub(super) const CELLS: [CellType; 3] = [CellType::U8, CellType::I8, CellType::U32];
+
+/// Every overflow policy.
+pub(super) const OVERFLOWS: [OverflowPolicy; 3] = [
+    OverflowPolicy::Wrap,
+    OverflowPolicy::Saturate,
+    OverflowPolicy::Trap,
+];
+
+/// Everything a run of a program does that can be observed: what it wrote,
+/// how it failed, and, if it did not, where it left the pointer and the
+/// value of every slot of the tape it started with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Outcome {
+    /// Every byte written to the output.
+    pub(super) output: Vec<u8>,
+    /// What stopped the program, without its location.
+    pub(super) error: Option<String>,
+    /// Line and column of the instruction that stopped the program.
+    pub(super) location: Option<(usize, usize)>,
+    /// Position of the pointer at the end of the program.
+    pub(super) position: i64,
+    /// Values of the slots of the tape the program started with, from the
+    /// one the pointer started at, at the end of the program.
+    pub(super) cells: Vec<i64>,
+}
+
+impl Outcome {
+    /// Returns this outcome without the location of its error, for passes
+    /// that replace several instructions, which may fail, with one.
+    pub(super) fn unlocated(self) -> Self {
+        Self {
+            location: None,
+            ..self
+        }
+    }
+}
+
+/// Returns the given source compiled and optimized at the given level for
+/// the given options.
+pub(super) fn optimized(source: &str, level: OptLevel, options: &MachineOptions) -> Program {
+    let program = crate::compile(source).expect("valid program");
+    let optimize = OptimizeOptions {
+        level,
+        ..OptimizeOptions::default()
+    };
+    Optimizer::optimize_with(&program, &optimize, options).0
+}
+
+/// Runs the given program with the given options and input, stopping it
+/// after STEP_LIMIT steps, and returns what it did.
+pub(super) fn run(program: &Program, input: &[u8], options: &MachineOptions) -> Outcome {
+    let options = MachineOptions {
+        max_steps: Some(STEP_LIMIT),
+        ..options.clone()
+    };
+    with_cell_type!(options.cell, run_with_cell(program, input, &options))
+}
+
+/// Runs the given program on a machine with the given cell type, as "run"
+/// does.
+fn run_with_cell<C: Cell>(program: &Program, input: &[u8], options: &MachineOptions) -> Outcome {
+    let mut vm = VirtualMachine::<_, _, C>::with_streams(input, Vec::new(), options.clone());
+    let result = vm.execute(program);
+    let (position, cells) = match result {
+        Ok(()) => {
+            let tape = vm.tape();
+            let start = tape.origin();
+            let end = (start + options.tape_length).min(tape.cells().len());
+            let cells = tape.cells()[start..end].iter().map(|cell| cell.to_i64());
+            (tape.position(), cells.collect())
+        }
+        Err(_) => (0, Vec::new()),
+    };
+    let (error, location) = match result {
+        Ok(()) => (None, None),
+        Err(error) => describe(&error),
+    };
+
+    Outcome {
+        output: vm.into_streams().1,
+        error,
+        location,
+        position,
+        cells,
+    }
+}
+
+/// Returns what the given error says, without its location, and the line
+/// and column of that location, if it has one.
+fn describe(error: &RuntimeError) -> (Option<String>, Option<(usize, usize)>) {
+    let (message, span) = match error {
+        RuntimeError::Io(_) => ("input or output failed".to_string(), None),
+        RuntimeError::StepLimitExceeded { .. } => ("step limit exceeded".to_string(), None),
+        RuntimeError::UnexpectedEof { span } => ("unexpected end of input".to_string(), Some(span)),
+        RuntimeError::TapeOutOfBounds { span, cell } => {
+            (format!("pointer moved to cell {}", cell), Some(span))
+        }
+        RuntimeError::TapeLimitExceeded { span, limit } => (
+            format!("tape limit of {} cells exceeded", limit),
+            Some(span),
+        ),
+        RuntimeError::Overflow { span } => ("cell overflow".to_string(), Some(span)),
+    };
+    (Some(message), span.map(|span| (span.line, span.column)))
+}
@@ -1,20 +1,47 @@
-use super::instruction::Instruction;
+use crate::diagnostics::{Snippet, Span};
 
-/// Returns an error message stating that an attempt was made to close a
-/// loop without one being open. The program must receive the status until
-/// it encounters the attempted closure.
-pub(crate) fn error_mismatched_loop_closing(mismatched: &[Instruction]) -> String {
-    format!(
-        "Mismatch close loop in: {}",
-        Instruction::represents_mismatched(mismatched)
-    )
+/// Errors that may happen during the parsing process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A loop was not closed properly. Points at the opening bracket of the
+    /// loop that was left open.
+    Unclosed(Snippet),
+    /// An attempt was made to close a loop without one being open. Points
+    /// at the closing bracket found.
+    Unmatched(Snippet),
 }
 
-/// Returns an error message stating that a loop was not closed properly.
-/// Receives the instructions for the loop that was left open.
-pub(crate) fn error_unclosed_loop(unclosed: &[Instruction]) -> String {
-    format!(
-        "Unclosed loop error in: {}",
-        Instruction::represents_unclosed(unclosed)
-    )
+impl ParseError {
+    /// Returns an error stating that a loop was not closed properly, whose
+    /// opening bracket was found at the given span of the given source.
+    pub(crate) fn unclosed(source: &str, opening: &Span) -> Self {
+        ParseError::Unclosed(Snippet::new(source, opening))
+    }
+
+    /// Returns an error stating that an attempt was made to close a loop
+    /// without one being open, with the closing bracket found at the given
+    /// span of the given source.
+    pub(crate) fn unmatched(source: &str, closing: &Span) -> Self {
+        ParseError::Unmatched(Snippet::new(source, closing))
+    }
+
+    /// Returns the location of the offending bracket.
+    pub fn span(&self) -> &Span {
+        match self {
+            ParseError::Unclosed(snippet) | ParseError::Unmatched(snippet) => snippet.span(),
+        }
+    }
 }
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Unclosed(snippet) => snippet.render(f, format_args!("unclosed loop")),
+            ParseError::Unmatched(snippet) => {
+                snippet.render(f, format_args!("mismatched loop closing"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
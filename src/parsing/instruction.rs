@@ -1,80 +1,101 @@
-use crate::lexing::Token;
+use crate::diagnostics::Span;
+use crate::lexing::{Token, TokenKind};
 
-/// Represents a brainfuck program instruction. This instruction (or a vector
-/// of them) can be executed directly by the virtual machine.
-#[derive(Debug, Clone)]
-pub(crate) enum Instruction {
+/// Represents a brainfuck program instruction, together with the piece of
+/// source code it came from. A vector of them, with balanced loops, can be
+/// executed directly by the virtual machine.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    /// What this instruction does.
+    pub kind: InstructionKind,
+    /// Where this instruction was found in the source code.
+    pub span: Span,
+}
+
+/// Represents what a brainfuck program instruction does. Loops are not
+/// nested values: their body is every instruction between a StartLoop and
+/// its matching StopLoop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstructionKind {
     Advance,
     Recede,
     Increment,
     Decrement,
     Show,
     Read,
-    Loop(Vec<Instruction>),
-}
-
-impl Instruction {
-    /// Returns an instruction that is equivalent to the given token. It
-    /// should not be called with "Token::StartLoop" or "Token::StopLoop"
-    /// as it does not return a suitable instruction in this scenario.
-    pub(crate) fn from(token: &Token) -> Self {
-        match token {
-            Token::Advance => Self::Advance,
-            Token::Decrement => Self::Decrement,
-            Token::Increment => Self::Increment,
-            Token::Read => Self::Read,
-            Token::Recede => Self::Recede,
-            Token::Show => Self::Show,
-            Token::StartLoop => Self::Loop(Vec::new()),
-            Token::StopLoop => Self::Loop(Vec::new()),
-        }
-    }
-
-    /// Returns a loop instruction containing the given instruction vector
-    /// in its body.
-    pub(crate) fn loop_from(instructions: &[Instruction]) -> Self {
-        Instruction::Loop(instructions.to_vec())
-    }
-
-    /// Returns the representation of a program in which there was an
-    /// improper attempt to close a loop.
-    pub(crate) fn represents_mismatched(instructions: &[Self]) -> String {
-        Self::represents_loop(instructions)
-            .chars()
-            .skip(1)
-            .collect()
-    }
-
-    /// Returns the representation of a loop that was not properly closed
-    /// (left open). Receives only the loop instructions and not a
-    /// loop itself.
-    pub(crate) fn represents_unclosed(instructions: &[Self]) -> String {
-        Self::represents_loop(instructions)
-            .chars()
-            .take(instructions.len() - 1)
-            .collect()
-    }
+    /// Skips to the matching StopLoop if the current memory slot is 0.
+    StartLoop,
+    /// Goes back to the matching StartLoop if the current memory slot is
+    /// not 0.
+    StopLoop,
+    /// Adds the given amount (which may be negative) to the current memory
+    /// slot. Produced by the optimizer from runs of Increment and
+    /// Decrement.
+    Add(i64),
+    /// Moves the pointer by the given number of slots (leftwards when
+    /// negative). Produced by the optimizer from runs of Advance and
+    /// Recede.
+    Move(isize),
+    /// Sets the current memory slot to 0. Produced by the optimizer from
+    /// loops like "[-]".
+    SetZero,
+    /// Adds the current memory slot, multiplied by the given factor, to the
+    /// slot at the given distance from the current one, unless the current
+    /// slot is 0. Produced by the optimizer from loops like "[->++<]",
+    /// which are then followed by a SetZero.
+    MulAdd {
+        offset: isize,
+        factor: i64,
+    },
+    /// Moves the pointer rightwards until the current memory slot is 0.
+    /// Produced by the optimizer from the loop "[>]".
+    ScanRight,
+    /// Moves the pointer leftwards until the current memory slot is 0.
+    /// Produced by the optimizer from the loop "[<]".
+    ScanLeft,
+    /// Adds the given amount (which may be negative) to the memory slot at
+    /// the given distance from the current one, without moving the pointer.
+    /// Produced by the optimizer from straight-line code.
+    AddAt {
+        offset: isize,
+        amount: i64,
+    },
+    /// Shows the memory slot at the given distance from the current one,
+    /// without moving the pointer. Produced by the optimizer from
+    /// straight-line code.
+    OutputAt {
+        offset: isize,
+    },
+    /// Writes the given byte to the output. Produced by the optimizer from
+    /// code that shows values known at compile time.
+    Print(u8),
+    /// Sets the memory slot at the given distance from the current one to
+    /// the value with the given bits, without moving the pointer. Produced
+    /// by the optimizer from code whose effect is known at compile time.
+    SetAt {
+        offset: isize,
+        value: i64,
+    },
 }
 
 impl Instruction {
-    /// Returns the representation (as a String) of this instruction.
-    fn representation(&self) -> String {
-        match self {
-            Instruction::Advance => String::from(">"),
-            Instruction::Recede => String::from("<"),
-            Instruction::Increment => String::from("+"),
-            Instruction::Decrement => String::from("-"),
-            Instruction::Show => String::from("."),
-            Instruction::Read => String::from(","),
-            Instruction::Loop(instructions) => Self::represents_loop(instructions),
-        }
+    /// Returns a new instruction of the given kind, found at the given span.
+    pub fn new(kind: InstructionKind, span: Span) -> Self {
+        Self { kind, span }
     }
 
-    /// Returns a representation of a loop with the given instructions
-    /// in its body.
-    fn represents_loop(instructions: &[Self]) -> String {
-        let ops_representation: Vec<String> =
-            instructions.iter().map(|i| i.representation()).collect();
-        format!("[{}]", ops_representation.join(""))
+    /// Returns an instruction that is equivalent to the given token.
+    pub(crate) fn from(token: &Token) -> Self {
+        let kind = match token.kind {
+            TokenKind::Advance => InstructionKind::Advance,
+            TokenKind::Decrement => InstructionKind::Decrement,
+            TokenKind::Increment => InstructionKind::Increment,
+            TokenKind::Read => InstructionKind::Read,
+            TokenKind::Recede => InstructionKind::Recede,
+            TokenKind::Show => InstructionKind::Show,
+            TokenKind::StartLoop => InstructionKind::StartLoop,
+            TokenKind::StopLoop => InstructionKind::StopLoop,
+        };
+        Self::new(kind, token.span)
     }
 }
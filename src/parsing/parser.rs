@@ -1,95 +1,85 @@
-use super::Instruction;
-use crate::lexing::Token;
+use super::{Instruction, ParseError, Program};
+use crate::diagnostics::Span;
+use crate::lexing::{Token, TokenKind};
 
 /// Type responsible for performing syntactic analysis. It can be
 /// instantiated, but its public interface uses only its static method.
-pub(crate) struct Parser {
-    /// levels are used to analyze nested loops that are being constructed in
-    /// the program. Level 0 is the level without loops, that is, the program
-    /// itself, all other levels are loops.
-    levels: Vec<Vec<Instruction>>,
+/// Programs are kept flat, so the nesting depth of loops is bounded only by
+/// memory.
+pub struct Parser<'src> {
+    /// Source code the tokens came from, used to point at the offending
+    /// brackets in error messages.
+    source: &'src str,
+    /// Instructions parsed so far, in execution order.
+    instructions: Vec<Instruction>,
+    /// Spans of the opening brackets of the loops being constructed. The
+    /// last one belongs to the innermost loop.
+    openings: Vec<Span>,
 }
 
 /// Implements the public Parser API.
-impl Parser {
-    /// Attempts to convert the input token iterator vector into a vector
-    /// of Instructions. If it fails, it returns an error message that can
-    /// be displayed by the program.
-    pub(crate) fn parse(instructions: &[Token]) -> Result<Vec<Instruction>, String> {
-        let mut parser = Self::new();
+impl Parser<'_> {
+    /// Attempts to convert the input token vector, found in the given source
+    /// code, into a Program. If it fails, it returns an error that can be
+    /// displayed by the program.
+    pub fn parse(source: &str, instructions: &[Token]) -> Result<Program, ParseError> {
+        let mut parser = Parser::new(source);
         instructions
             .iter()
             .try_for_each(|tk| parser.parse_token(tk))
-            .and_then(|_| parser.get_finished().cloned())
+            .and_then(|_| parser.get_finished())
+            .map(Program::new)
     }
 }
 
 /// Implements the private parser methods.
-impl Parser {
+impl<'src> Parser<'src> {
     /// Attempts to return the current state of the parsing as a complete
     /// program. Will fail if there are unclosed loops. In case of failure
-    /// returns the error message that can be displayed by the program.
-    fn get_finished(&self) -> Result<&Vec<Instruction>, String> {
-        if self.levels.len() > 1 {
-            Err(super::error_unclosed_loop(self.levels.last().unwrap()))
-        } else {
-            Ok(&self.levels[0])
+    /// returns the error that can be displayed by the program.
+    fn get_finished(self) -> Result<Vec<Instruction>, ParseError> {
+        match self.openings.last() {
+            Some(opening) => Err(ParseError::unclosed(self.source, opening)),
+            None => Ok(self.instructions),
         }
     }
 
     /// Returns a new instance of Parser, ready to parse a program "from
-    /// scratch". Its levels are defined as containing only level 0, and
-    /// its level 0 will be empty.
-    fn new() -> Self {
+    /// scratch", with no instructions and no open loops.
+    fn new(source: &'src str) -> Self {
         Self {
-            levels: vec![Vec::new()],
+            source,
+            instructions: Vec::new(),
+            openings: Vec::new(),
         }
     }
 
-    /// Attempts to parse the given token, adding it to the appropriate level,
-    /// creating a new level, or ending the current level. Whatever is
-    /// appropriate for the input token. On failure, returns an error
-    /// message that may be displayed by the program.
-    fn parse_token(&mut self, token: &Token) -> Result<(), String> {
-        match token {
-            Token::StopLoop => self.stop_loop(),
-            Token::StartLoop => {
-                self.start_new_loop();
-                Ok(())
-            }
-            tk => {
-                let instruction = Instruction::from(tk);
-                self.push_instruction(instruction);
-                Ok(())
-            }
+    /// Attempts to parse the given token, adding it to the program and
+    /// keeping track of the loops that are open. On failure, returns an
+    /// error that may be displayed by the program.
+    fn parse_token(&mut self, token: &Token) -> Result<(), ParseError> {
+        match token.kind {
+            TokenKind::StopLoop => self.stop_loop(&token.span)?,
+            TokenKind::StartLoop => self.start_new_loop(&token.span),
+            _ => (),
         }
+        self.instructions.push(Instruction::from(token));
+        Ok(())
     }
 
-    /// Starts a new loop, adding a new level to the levels vector.
-    fn start_new_loop(&mut self) {
-        self.levels.push(Vec::new());
+    /// Starts a new loop, opened at the given span.
+    fn start_new_loop(&mut self, opening: &Span) {
+        self.openings.push(*opening);
     }
 
-    /// Attempts to end the parsing of the current loop. Will fail if
-    /// there is no loop being constructed at the moment (level with
-    /// less than two elements). In case of failure, returns the error
-    /// message that can be displayed by the program.
-    fn stop_loop(&mut self) -> Result<(), String> {
-        if self.levels.len() <= 1 {
-            Err(super::error_mismatched_loop_closing(&self.levels[0]))
-        } else {
-            let popped = self.levels.pop().unwrap();
-            let new_loop = Instruction::loop_from(&popped);
-            self.push_instruction(new_loop);
-            Ok(())
-        }
-    }
-
-    /// Adds the given instruction to the instruction queue at the
-    /// level currently being built.
-    fn push_instruction(&mut self, instruction: Instruction) {
-        if let Some(v) = self.levels.last_mut() {
-            v.push(instruction);
+    /// Attempts to end the parsing of the current loop, closed at the given
+    /// span. Will fail if there is no loop being constructed at the moment
+    /// (no opening bracket waiting to be closed). In case of failure,
+    /// returns the error that can be displayed by the program.
+    fn stop_loop(&mut self, closing: &Span) -> Result<(), ParseError> {
+        match self.openings.pop() {
+            Some(_) => Ok(()),
+            None => Err(ParseError::unmatched(self.source, closing)),
         }
     }
 }
@@ -0,0 +1,28 @@
+use super::Instruction;
+
+/// A complete brainf*ck program, ready to be executed by the virtual
+/// machine. It is the result of the parsing process, so its loops are
+/// always balanced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program {
+    /// Instructions of the program, in the order they appear.
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Returns a new program containing the given instructions, whose loops
+    /// must be balanced.
+    pub(crate) fn new(instructions: Vec<Instruction>) -> Self {
+        Self { instructions }
+    }
+
+    /// Returns the instructions of this program.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Returns whether or not this program has no instructions at all.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+}
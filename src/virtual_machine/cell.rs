@@ -0,0 +1,248 @@
+use super::OverflowPolicy;
+
+/// Type of value stored in each memory slot of the virtual machine. It is
+/// implemented for every unsigned and signed integer from 8 to 64 bits.
+pub trait Cell: Copy + Default + PartialEq + std::fmt::Debug + 'static {
+    /// Description of this type, for when it must be chosen at runtime.
+    const TYPE: CellType;
+
+    /// The value 0, which every memory slot starts with and brainf*ck
+    /// treats as false.
+    const ZERO: Self;
+
+    /// Returns this value plus the given amount (which may be negative).
+    /// If the result does not fit in the type, the policy decides what
+    /// happens: returns None when the policy says the overflow is an error.
+    fn add(self, amount: i64, policy: OverflowPolicy) -> Option<Self>;
+
+    /// Returns this value plus the given value multiplied by the given
+    /// factor, as if the factor were added that many times. If the result
+    /// does not fit in the type, the policy decides what happens, as in
+    /// "add".
+    fn add_product(self, value: Self, factor: i64, policy: OverflowPolicy) -> Option<Self>;
+
+    /// Returns the value that represents the given input byte.
+    fn from_byte(byte: u8) -> Self;
+
+    /// Returns the byte written to the output when this value is shown,
+    /// which is its lowest byte.
+    fn to_byte(self) -> u8;
+
+    /// Returns the value -1 of this type, which for unsigned types is the
+    /// one with every bit set.
+    fn minus_one() -> Self;
+
+    /// Returns the value with the same lowest bits as the given one. Every
+    /// value of the type survives a round trip through "to_i64".
+    fn from_i64(value: i64) -> Self;
+
+    /// Returns this value as an i64. Unsigned 64-bit values beyond the
+    /// range of i64 come back negative, with the same bits.
+    fn to_i64(self) -> i64;
+
+    /// Returns whether or not this value is 0, the value treated as false
+    /// by brainf*ck.
+    fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+}
+
+/// Every type of value a memory slot can store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellType {
+    #[default]
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl CellType {
+    /// Returns the number of bits of this type.
+    pub fn bits(&self) -> u32 {
+        match self {
+            CellType::U8 | CellType::I8 => 8,
+            CellType::U16 | CellType::I16 => 16,
+            CellType::U32 | CellType::I32 => 32,
+            CellType::U64 | CellType::I64 => 64,
+        }
+    }
+
+    /// Returns whether or not this type can store negative values.
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            CellType::I8 | CellType::I16 | CellType::I32 | CellType::I64
+        )
+    }
+}
+
+/// Implements Cell for the given integer type, described by the given
+/// CellType. Every operation is done in i128, where all of them fit, and
+/// then brought back to the type: the "as" conversion wraps, so only the
+/// other policies need checking. Wrapping additions, by far the most common,
+/// are done in i64 instead, which wraps the lowest bits just the same.
+macro_rules! impl_cell {
+    ($type:ty, $description:expr) => {
+        impl Cell for $type {
+            const TYPE: CellType = $description;
+            const ZERO: Self = 0;
+
+            fn add(self, amount: i64, policy: OverflowPolicy) -> Option<Self> {
+                let sum = self as i128 + amount as i128;
+                match policy {
+                    OverflowPolicy::Wrap => Some((self as i64).wrapping_add(amount) as $type),
+                    OverflowPolicy::Saturate => {
+                        Some(sum.clamp(<$type>::MIN as i128, <$type>::MAX as i128) as $type)
+                    }
+                    OverflowPolicy::Trap => <$type>::try_from(sum).ok(),
+                }
+            }
+
+            fn add_product(self, value: Self, factor: i64, policy: OverflowPolicy) -> Option<Self> {
+                let (value, factor) = (value as i128, factor as i128);
+                let sum = value
+                    .checked_mul(factor)
+                    .and_then(|product| product.checked_add(self as i128));
+                match (policy, sum) {
+                    (OverflowPolicy::Wrap, _) => {
+                        Some(value.wrapping_mul(factor).wrapping_add(self as i128) as $type)
+                    }
+                    (OverflowPolicy::Saturate, Some(sum)) => {
+                        Some(sum.clamp(<$type>::MIN as i128, <$type>::MAX as i128) as $type)
+                    }
+                    (OverflowPolicy::Saturate, None) if (value < 0) == (factor < 0) => {
+                        Some(<$type>::MAX)
+                    }
+                    (OverflowPolicy::Saturate, None) => Some(<$type>::MIN),
+                    (OverflowPolicy::Trap, sum) => sum.and_then(|sum| <$type>::try_from(sum).ok()),
+                }
+            }
+
+            fn from_byte(byte: u8) -> Self {
+                byte as $type
+            }
+
+            fn to_byte(self) -> u8 {
+                self as u8
+            }
+
+            fn minus_one() -> Self {
+                -1i128 as $type
+            }
+
+            fn from_i64(value: i64) -> Self {
+                value as $type
+            }
+
+            fn to_i64(self) -> i64 {
+                self as i64
+            }
+        }
+    };
+}
+
+impl_cell!(u8, CellType::U8);
+impl_cell!(u16, CellType::U16);
+impl_cell!(u32, CellType::U32);
+impl_cell!(u64, CellType::U64);
+impl_cell!(i8, CellType::I8);
+impl_cell!(i16, CellType::I16);
+impl_cell!(i32, CellType::I32);
+impl_cell!(i64, CellType::I64);
+
+/// Calls the given generic function, instantiated with the cell type that
+/// matches the given CellType, with the given arguments. It allows picking
+/// the cell type of a virtual machine at runtime.
+macro_rules! with_cell_type {
+    ($cell:expr, $function:ident($($argument:expr),* $(,)?)) => {
+        match $cell {
+            $crate::virtual_machine::CellType::U8 => $function::<u8>($($argument),*),
+            $crate::virtual_machine::CellType::U16 => $function::<u16>($($argument),*),
+            $crate::virtual_machine::CellType::U32 => $function::<u32>($($argument),*),
+            $crate::virtual_machine::CellType::U64 => $function::<u64>($($argument),*),
+            $crate::virtual_machine::CellType::I8 => $function::<i8>($($argument),*),
+            $crate::virtual_machine::CellType::I16 => $function::<i16>($($argument),*),
+            $crate::virtual_machine::CellType::I32 => $function::<i32>($($argument),*),
+            $crate::virtual_machine::CellType::I64 => $function::<i64>($($argument),*),
+        }
+    };
+}
+
+pub(crate) use with_cell_type;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping_cells_go_around_their_range() {
+        assert_eq!(255u8.add(1, OverflowPolicy::Wrap), Some(0));
+        assert_eq!(0u8.add(-1, OverflowPolicy::Wrap), Some(255));
+        assert_eq!(127i8.add(1, OverflowPolicy::Wrap), Some(-128));
+        assert_eq!(0u32.add(-1, OverflowPolicy::Wrap), Some(u32::MAX));
+        assert_eq!(i64::MAX.add(1, OverflowPolicy::Wrap), Some(i64::MIN));
+        assert_eq!(
+            u64::MAX.add(i64::MAX, OverflowPolicy::Wrap),
+            Some(i64::MAX as u64 - 1)
+        );
+    }
+
+    #[test]
+    fn saturating_cells_stay_at_the_ends_of_their_range() {
+        assert_eq!(250u8.add(10, OverflowPolicy::Saturate), Some(255));
+        assert_eq!(5u8.add(-10, OverflowPolicy::Saturate), Some(0));
+        assert_eq!(120i8.add(10, OverflowPolicy::Saturate), Some(127));
+        assert_eq!((-120i8).add(-10, OverflowPolicy::Saturate), Some(-128));
+        assert_eq!(u64::MAX.add(1, OverflowPolicy::Saturate), Some(u64::MAX));
+        assert_eq!(i64::MIN.add(-1, OverflowPolicy::Saturate), Some(i64::MIN));
+    }
+
+    #[test]
+    fn trapping_cells_refuse_to_overflow() {
+        assert_eq!(255u8.add(1, OverflowPolicy::Trap), None);
+        assert_eq!(0u16.add(-1, OverflowPolicy::Trap), None);
+        assert_eq!(127i8.add(1, OverflowPolicy::Trap), None);
+        assert_eq!((-128i8).add(-1, OverflowPolicy::Trap), None);
+        assert_eq!(254u8.add(1, OverflowPolicy::Trap), Some(255));
+        assert_eq!((-1i32).add(1, OverflowPolicy::Trap), Some(0));
+    }
+
+    #[test]
+    fn products_behave_as_repeated_additions() {
+        assert_eq!(1u8.add_product(100, 3, OverflowPolicy::Wrap), Some(45));
+        assert_eq!(1u8.add_product(100, 3, OverflowPolicy::Saturate), Some(255));
+        assert_eq!(1u8.add_product(100, 3, OverflowPolicy::Trap), None);
+        assert_eq!(
+            0i8.add_product(-100, 2, OverflowPolicy::Saturate),
+            Some(-128)
+        );
+        assert_eq!(0i8.add_product(100, -2, OverflowPolicy::Wrap), Some(56));
+        let huge = i64::MAX.add_product(i64::MAX, i64::MAX, OverflowPolicy::Saturate);
+        assert_eq!(huge, Some(i64::MAX));
+        let huge = 0i64.add_product(i64::MIN, i64::MAX, OverflowPolicy::Saturate);
+        assert_eq!(huge, Some(i64::MIN));
+    }
+
+    #[test]
+    fn bytes_go_in_and_out_by_their_lowest_bits() {
+        assert_eq!(i8::from_byte(200), -56);
+        assert_eq!(u32::from_byte(200), 200);
+        assert_eq!((-56i8).to_byte(), 200);
+        assert_eq!(0x1234u16.to_byte(), 0x34);
+        assert_eq!(u16::minus_one(), u16::MAX);
+        assert_eq!(i32::minus_one(), -1);
+    }
+
+    #[test]
+    fn cell_types_describe_their_range() {
+        assert_eq!(CellType::U16.bits(), 16);
+        assert_eq!(CellType::I64.bits(), 64);
+        assert!(CellType::I8.is_signed());
+        assert!(!CellType::U32.is_signed());
+    }
+}
@@ -0,0 +1,135 @@
+use super::TapeFault;
+use crate::diagnostics::Span;
+
+/// Errors that may happen while the virtual machine executes a program.
+#[derive(Debug)]
+pub enum RuntimeError {
+    /// Reading from the input stream or writing to the output stream
+    /// failed.
+    Io(std::io::Error),
+    /// The program executed more steps than the given limit allows.
+    StepLimitExceeded { limit: u64 },
+    /// The read instruction at the given span found no more bytes in the
+    /// input, and the end of the input is treated as an error.
+    UnexpectedEof { span: Span },
+    /// The instruction at the given span moved the pointer past either end
+    /// of the tape, to the given cell (counted from the starting cell), the
+    /// first one past that end.
+    TapeOutOfBounds { span: Span, cell: i64 },
+    /// The instruction at the given span needed the tape to grow beyond
+    /// the given number of memory slots.
+    TapeLimitExceeded { span: Span, limit: usize },
+    /// The instruction at the given span took a memory slot beyond the
+    /// values its type can store, and overflows are treated as errors.
+    Overflow { span: Span },
+}
+
+impl RuntimeError {
+    /// Returns whether or not this error was caused by the program going
+    /// beyond one of the limits set for its execution, instead of by a
+    /// failure of the program itself.
+    pub fn is_limit_exceeded(&self) -> bool {
+        matches!(
+            self,
+            RuntimeError::StepLimitExceeded { .. } | RuntimeError::TapeLimitExceeded { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::Io(_) => write!(f, "Failed to read input or write output"),
+            RuntimeError::StepLimitExceeded { limit } => {
+                write!(f, "Step limit exceeded: {} steps", limit)
+            }
+            RuntimeError::UnexpectedEof { span } => write!(
+                f,
+                "Unexpected end of input at line {}, column {}",
+                span.line, span.column
+            ),
+            RuntimeError::TapeOutOfBounds { span, cell } => write!(
+                f,
+                "Pointer moved out of the tape, to cell {}, at line {}, column {}",
+                cell, span.line, span.column
+            ),
+            RuntimeError::Overflow { span } => write!(
+                f,
+                "Cell overflow at line {}, column {}",
+                span.line, span.column
+            ),
+            RuntimeError::TapeLimitExceeded { span, limit } => write!(
+                f,
+                "Tape limit exceeded: {} cells, at line {}, column {}",
+                limit, span.line, span.column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RuntimeError::Io(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RuntimeError {
+    fn from(source: std::io::Error) -> Self {
+        RuntimeError::Io(source)
+    }
+}
+
+/// Ways the execution of an operation may fail. They lack the location of
+/// the offending operation, which is added once, by the virtual machine
+/// loop, so the operations themselves stay simple.
+#[derive(Debug)]
+pub(crate) enum Fault {
+    /// Reading from the input stream or writing to the output stream
+    /// failed.
+    Io(std::io::Error),
+    /// The input had no more bytes, and this is treated as an error.
+    UnexpectedEof,
+    /// A memory slot went beyond the values its type can store, and this is
+    /// treated as an error.
+    Overflow,
+    /// The tape could not move its pointer.
+    Tape(TapeFault),
+    /// The virtual machine went beyond its step limit in the middle of an
+    /// operation that takes many steps.
+    StepLimitExceeded { limit: u64 },
+}
+
+impl Fault {
+    /// Returns the runtime error this fault causes when it happens while
+    /// executing the operation at the given span.
+    pub(crate) fn at(self, span: &Span) -> RuntimeError {
+        let span = *span;
+        match self {
+            Fault::Io(source) => RuntimeError::Io(source),
+            Fault::UnexpectedEof => RuntimeError::UnexpectedEof { span },
+            Fault::Overflow => RuntimeError::Overflow { span },
+            Fault::StepLimitExceeded { limit } => RuntimeError::StepLimitExceeded { limit },
+            Fault::Tape(TapeFault::OutOfBounds { cell }) => {
+                RuntimeError::TapeOutOfBounds { span, cell }
+            }
+            Fault::Tape(TapeFault::LimitExceeded { limit }) => {
+                RuntimeError::TapeLimitExceeded { span, limit }
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for Fault {
+    fn from(source: std::io::Error) -> Self {
+        Fault::Io(source)
+    }
+}
+
+impl From<TapeFault> for Fault {
+    fn from(fault: TapeFault) -> Self {
+        Fault::Tape(fault)
+    }
+}
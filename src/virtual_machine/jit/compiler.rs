@@ -0,0 +1,229 @@
+use std::io::{Read, Write};
+
+use super::memory::ExecutableMemory;
+use crate::assembling::{
+    self, Assembler, Label, Memory, Reg, Runtime, BASE, INDEX, LENGTH, POSITION,
+};
+use crate::parsing::Program;
+use crate::virtual_machine::tape::TapeFault;
+use crate::virtual_machine::{Cell, Fault, RuntimeError, TapePolicy, VirtualMachine};
+
+/// Register that holds the address of the context.
+const CONTEXT: Reg = Reg::R13;
+
+/// Signature of the translated program. It takes the context, the address
+/// of the first cell, the index of the current cell and the number of
+/// cells, and returns 0 on success, or the index of the instruction that
+/// failed plus one.
+type Entry = unsafe extern "sysv64" fn(*mut u8, *mut u8, usize, usize) -> u64;
+
+/// State shared between the translated program and the functions it calls
+/// back. Its first fields are written by the machine code, so their layout
+/// is fixed.
+#[repr(C)]
+struct Context<'vm, I: Read, O: Write, C: Cell> {
+    /// Index of the current cell when the program returned.
+    position: usize,
+    /// Index of the cell the pointer tried to reach when it moved out of
+    /// the tape.
+    target: i64,
+    /// Virtual machine whose streams are used for input and output.
+    vm: &'vm mut VirtualMachine<I, O, C>,
+    /// Fault of the last function called back that failed.
+    fault: Option<Fault>,
+}
+
+/// Offsets, in bytes, of the fields of the context written by the machine
+/// code.
+const POSITION_FIELD: i32 = 0;
+const TARGET_FIELD: i32 = 8;
+
+/// Ways the translated program can fail, each handled by its own piece of
+/// code at the end of the program.
+enum Failure {
+    /// A function called back failed, and left its fault in the context.
+    Callback,
+    /// The pointer tried to reach the cell whose index is in INDEX.
+    OutOfTape,
+}
+
+/// Runtime of the translated program: it is called with the context and
+/// the state of the tape, and calls back into the virtual machine for
+/// input and output. On failure, it returns which instruction failed.
+struct Callbacks {
+    /// Addresses of the functions called back for output and input.
+    output: i64,
+    input: i64,
+    /// Label, instruction index and kind of every failure to handle.
+    failures: Vec<(Label, usize, Failure)>,
+}
+
+/// Returns the given program translated into machine code, for a tape of
+/// the given length and policy, and the streams and cells of a virtual
+/// machine with the given type parameters.
+pub(super) fn compile<I: Read, O: Write, C: Cell>(
+    program: &Program,
+    policy: TapePolicy,
+    length: usize,
+) -> Vec<u8> {
+    let output: extern "sysv64" fn(*mut Context<I, O, C>, u64) -> u64 = output::<I, O, C>;
+    let input: extern "sysv64" fn(*mut Context<I, O, C>, *mut C) -> u64 = input::<I, O, C>;
+    let mut callbacks = Callbacks {
+        output: output as usize as i64,
+        input: input as usize as i64,
+        failures: vec![],
+    };
+    let mut asm = Assembler::new(std::mem::size_of::<C>() as u8);
+    assembling::translate(program, &mut asm, &mut callbacks, policy, length);
+    asm.finish()
+}
+
+/// Runs the given machine code, translated from the given program, on the
+/// tape of the given virtual machine. Fails as the interpreter would, at
+/// the same instruction.
+pub(super) fn run<I: Read, O: Write, C: Cell>(
+    vm: &mut VirtualMachine<I, O, C>,
+    program: &Program,
+    memory: &ExecutableMemory,
+) -> Result<(), RuntimeError> {
+    let tape = vm.tape_mut();
+    let (position, origin) = (tape.index(), tape.origin());
+    let cells = tape.cells_mut();
+    let (base, length) = (cells.as_mut_ptr() as *mut u8, cells.len());
+    let mut context = Context {
+        position,
+        target: 0,
+        vm: &mut *vm,
+        fault: None,
+    };
+    // SAFETY: the code was translated for this tape and these type
+    // parameters, only accesses cells within the tape, and the tape is not
+    // reallocated while it runs, since the functions it calls back only use
+    // the streams of the virtual machine.
+    let status = unsafe {
+        let entry = std::mem::transmute::<*const u8, Entry>(memory.address());
+        entry(
+            &mut context as *mut Context<I, O, C> as *mut u8,
+            base,
+            position,
+            length,
+        )
+    };
+    let (position, target, fault) = (context.position, context.target, context.fault);
+    vm.tape_mut().set_index(position);
+    if status == 0 {
+        return Ok(());
+    }
+    let fault = fault.unwrap_or(Fault::Tape(TapeFault::OutOfBounds {
+        cell: target.clamp(-1, length as i64) - origin as i64,
+    }));
+    Err(fault.at(&program.instructions()[status as usize - 1].span))
+}
+
+/// Writes the given byte to the output stream of the virtual machine of the
+/// given context. Returns 0 on success.
+extern "sysv64" fn output<I: Read, O: Write, C: Cell>(
+    context: *mut Context<I, O, C>,
+    byte: u64,
+) -> u64 {
+    // SAFETY: the machine code passes back the context it was given.
+    let context = unsafe { &mut *context };
+    match context.vm.display_byte(byte as u8) {
+        Ok(()) => 0,
+        Err(fault) => {
+            context.fault = Some(fault);
+            1
+        }
+    }
+}
+
+/// Reads a byte from the input stream of the virtual machine of the given
+/// context into the given cell, following the EOF policy. Returns 0 on
+/// success.
+extern "sysv64" fn input<I: Read, O: Write, C: Cell>(
+    context: *mut Context<I, O, C>,
+    cell: *mut C,
+) -> u64 {
+    // SAFETY: the machine code passes back the context it was given.
+    let context = unsafe { &mut *context };
+    match context.vm.read_value() {
+        Ok(value) => {
+            if let Some(value) = value {
+                // SAFETY: the machine code passes the address of the
+                // current cell, which is within the tape.
+                unsafe { cell.write(value) };
+            }
+            0
+        }
+        Err(fault) => {
+            context.fault = Some(fault);
+            1
+        }
+    }
+}
+
+impl Callbacks {
+    /// Writes the code that calls back the function at the given address,
+    /// with the context as its first argument, and fails as the
+    /// instruction at the given index if it fails.
+    fn call(&mut self, asm: &mut Assembler, index: usize, function: i64) {
+        let failure = asm.label();
+        self.failures.push((failure, index, Failure::Callback));
+        asm.mov(Reg::Rdi, CONTEXT);
+        asm.mov_immediate(Reg::Rax, function);
+        asm.call(Reg::Rax);
+        asm.test(Reg::Rax, Reg::Rax);
+        asm.jump_if(assembling::Condition::NotZero, failure);
+    }
+}
+
+impl Runtime for Callbacks {
+    /// Writes the code that saves the registers the program uses and loads
+    /// its arguments into them. R15 is saved too, only so the stack stays
+    /// aligned for calls.
+    fn prologue(&mut self, asm: &mut Assembler) {
+        for reg in [Reg::Rbx, Reg::R12, Reg::R13, Reg::R14, Reg::R15] {
+            asm.push(reg);
+        }
+        asm.mov(CONTEXT, Reg::Rdi);
+        asm.mov(BASE, Reg::Rsi);
+        asm.mov(POSITION, Reg::Rdx);
+        asm.mov(LENGTH, Reg::Rcx);
+    }
+
+    fn output(&mut self, asm: &mut Assembler, index: usize) {
+        self.call(asm, index, self.output);
+    }
+
+    fn input(&mut self, asm: &mut Assembler, index: usize) {
+        self.call(asm, index, self.input);
+    }
+
+    fn out_of_tape(&mut self, asm: &mut Assembler, index: usize) -> Label {
+        let failure = asm.label();
+        self.failures.push((failure, index, Failure::OutOfTape));
+        failure
+    }
+
+    /// Writes the code that returns successfully, the code that returns
+    /// after each failure and the code they all end with, which saves the
+    /// position and restores the registers.
+    fn epilogue(&mut self, asm: &mut Assembler) {
+        let exit = asm.label();
+        asm.mov_immediate(Reg::Rax, 0);
+        asm.bind(exit);
+        asm.store(8, Memory::offset(CONTEXT, POSITION_FIELD), POSITION);
+        for reg in [Reg::R15, Reg::R14, Reg::R13, Reg::R12, Reg::Rbx] {
+            asm.pop(reg);
+        }
+        asm.ret();
+        for (label, index, failure) in std::mem::take(&mut self.failures) {
+            asm.bind(label);
+            if let Failure::OutOfTape = failure {
+                asm.store(8, Memory::offset(CONTEXT, TARGET_FIELD), INDEX);
+            }
+            asm.mov_immediate(Reg::Rax, index as i64 + 1);
+            asm.jump(exit);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::ffi::c_void;
+
+extern "C" {
+    fn mmap(
+        address: *mut c_void,
+        length: usize,
+        protection: i32,
+        flags: i32,
+        descriptor: i32,
+        offset: i64,
+    ) -> *mut c_void;
+    fn mprotect(address: *mut c_void, length: usize, protection: i32) -> i32;
+    fn munmap(address: *mut c_void, length: usize) -> i32;
+}
+
+/// Protection and flags of the mappings, as defined by Linux.
+const PROT_READ: i32 = 0x1;
+const PROT_WRITE: i32 = 0x2;
+const PROT_EXEC: i32 = 0x4;
+const MAP_PRIVATE: i32 = 0x02;
+const MAP_ANONYMOUS: i32 = 0x20;
+
+/// Value mmap returns when it fails.
+const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+/// Memory mapping that holds machine code. It is written while it is only
+/// readable and writable, then made only readable and executable, so it is
+/// never writable and executable at once. It is unmapped when dropped.
+pub(super) struct ExecutableMemory {
+    /// Address of the first byte of the mapping.
+    address: *mut c_void,
+    /// Number of bytes of the mapping.
+    length: usize,
+}
+
+impl ExecutableMemory {
+    /// Returns a new mapping holding a copy of the given machine code, or
+    /// None if the system refuses to create it.
+    pub(super) fn new(code: &[u8]) -> Option<Self> {
+        let length = code.len().max(1);
+        // SAFETY: a new anonymous mapping is requested, so no existing
+        // memory is affected, and the result is checked before use.
+        let address = unsafe {
+            mmap(
+                std::ptr::null_mut(),
+                length,
+                PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if address == MAP_FAILED {
+            return None;
+        }
+        let memory = Self { address, length };
+        // SAFETY: the mapping is writable and at least as long as the code,
+        // and nothing else refers to it yet.
+        unsafe {
+            std::ptr::copy_nonoverlapping(code.as_ptr(), address as *mut u8, code.len());
+            if mprotect(address, length, PROT_READ | PROT_EXEC) != 0 {
+                return None;
+            }
+        }
+        Some(memory)
+    }
+
+    /// Returns the address of the first byte of the machine code.
+    pub(super) fn address(&self) -> *const u8 {
+        self.address as *const u8
+    }
+}
+
+impl Drop for ExecutableMemory {
+    fn drop(&mut self) {
+        // SAFETY: the mapping was created by "new" and is no longer used,
+        // since its address never outlives it.
+        unsafe {
+            munmap(self.address, self.length);
+        }
+    }
+}
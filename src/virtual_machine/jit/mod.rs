@@ -0,0 +1,103 @@
+//! The jit module translates programs into x86-64 machine code, written to
+//! an executable memory mapping and called directly. The code works on the
+//! tape of the virtual machine in place, and calls back into it for every
+//! read and write, so its input and output streams and its EOF policy are
+//! used as they would be by the interpreter. Only what the machine code can
+//! reproduce exactly is translated: everything else is left to the
+//! interpreter.
+
+/// This module translates programs into machine code that calls back into
+/// the virtual machine, and runs it.
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+mod compiler;
+
+/// This module defines the ExecutableMemory type, which holds machine code
+/// that can be called.
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+mod memory;
+
+use std::io::{Read, Write};
+
+use super::{Cell, RuntimeError, VirtualMachine};
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+use super::{MachineOptions, OverflowPolicy, TapePolicy};
+use crate::parsing::Program;
+
+/// Returns whether or not programs can be translated into machine code for
+/// the given options. Growing tapes, overflow policies other than wrapping
+/// and step limits are only handled by the interpreter.
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+fn supports(options: &MachineOptions) -> bool {
+    options.tape_policy != TapePolicy::Grow
+        && options.overflow == OverflowPolicy::Wrap
+        && options.max_steps.is_none()
+}
+
+/// Executes the given program on the given virtual machine as machine code.
+/// Returns None, without doing anything, if the program cannot be
+/// translated for the options of the virtual machine, so it can be
+/// interpreted instead.
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+pub(super) fn execute<I: Read, O: Write, C: Cell>(
+    vm: &mut VirtualMachine<I, O, C>,
+    program: &Program,
+) -> Option<Result<(), RuntimeError>> {
+    if !supports(vm.options()) {
+        return None;
+    }
+    let length = vm.tape_mut().cells_mut().len();
+    let code = compiler::compile::<I, O, C>(program, vm.options().tape_policy, length);
+    let memory = memory::ExecutableMemory::new(&code)?;
+    Some(compiler::run(vm, program, &memory))
+}
+
+/// Executes the given program on the given virtual machine as machine code.
+/// Machine code is not supported on this target, so this always returns
+/// None, for the program to be interpreted instead.
+#[cfg(not(all(target_arch = "x86_64", target_os = "linux")))]
+pub(super) fn execute<I: Read, O: Write, C: Cell>(
+    _vm: &mut VirtualMachine<I, O, C>,
+    _program: &Program,
+) -> Option<Result<(), RuntimeError>> {
+    None
+}
+
+#[cfg(all(test, target_arch = "x86_64", target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_fixed_wrapping_tapes_without_a_step_limit_are_supported() {
+        assert!(supports(&MachineOptions::default()));
+        assert!(supports(&MachineOptions {
+            tape_policy: TapePolicy::Error,
+            ..MachineOptions::default()
+        }));
+
+        for options in [
+            MachineOptions {
+                tape_policy: TapePolicy::Grow,
+                ..MachineOptions::default()
+            },
+            MachineOptions {
+                tape_policy: TapePolicy::Grow,
+                max_tape_length: Some(64),
+                ..MachineOptions::default()
+            },
+            MachineOptions {
+                overflow: OverflowPolicy::Saturate,
+                ..MachineOptions::default()
+            },
+            MachineOptions {
+                overflow: OverflowPolicy::Trap,
+                ..MachineOptions::default()
+            },
+            MachineOptions {
+                max_steps: Some(1_000),
+                ..MachineOptions::default()
+            },
+        ] {
+            assert!(!supports(&options), "{options:?}");
+        }
+    }
+}
@@ -0,0 +1,571 @@
+use std::io::{Cursor, Read, Stdin, Stdout, Write};
+
+use super::{jit, Cell, EofPolicy, Fault, MachineOptions, RuntimeError, Tape};
+use crate::bytecode::{Bytecode, Compiler, Op};
+use crate::parsing::{InstructionKind, Program};
+
+/// Implements the constructors of a virtual machine with one-byte memory
+/// slots, connected to the standard input and output of the process.
+impl VirtualMachine {
+    /// Creates a new virtual machine completely empty, connected to the
+    /// standard input and output of the process. Its current memory slot
+    /// will be the first one (0), all slots will have 0 as their registered
+    /// value, and the tape will have the default length.
+    pub fn new() -> Self {
+        Self::with_options(MachineOptions::default())
+    }
+
+    /// Creates a new virtual machine completely empty, connected to the
+    /// standard input and output of the process, that executes programs
+    /// according to the given options.
+    pub fn with_options(options: MachineOptions) -> Self {
+        Self::stdio(options)
+    }
+
+    /// Returns a new virtual machine, but returns it after executing the
+    /// given program. Fails if the execution fails.
+    pub fn executing(program: &Program) -> Result<Self, RuntimeError> {
+        let mut vm = Self::new();
+        vm.execute(program)?;
+        Ok(vm)
+    }
+}
+
+impl Default for VirtualMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implements the constructors of a virtual machine with any type of memory
+/// slot, connected to the standard input and output of the process.
+impl<C: Cell> VirtualMachine<Stdin, Stdout, C> {
+    /// Creates a new virtual machine completely empty, connected to the
+    /// standard input and output of the process, that executes programs
+    /// according to the given options.
+    pub fn stdio(options: MachineOptions) -> Self {
+        Self::with_streams(std::io::stdin(), std::io::stdout(), options)
+    }
+}
+
+/// Implements the constructors of a virtual machine with one-byte memory
+/// slots that keeps its input and output in memory.
+impl InMemoryMachine {
+    /// Creates a new virtual machine completely empty, that reads its input
+    /// from the given bytes and collects its output in memory, and executes
+    /// programs according to the given options. The output can be retrieved
+    /// with "output" or "into_streams".
+    pub fn in_memory(input: impl Into<Vec<u8>>, options: MachineOptions) -> Self {
+        Self::with_streams(Cursor::new(input.into()), Vec::new(), options)
+    }
+}
+
+/// Implements the virtual machine's public API.
+impl<I: Read, O: Write, C: Cell> VirtualMachine<I, O, C> {
+    /// Creates a new virtual machine completely empty, that reads its input
+    /// from and writes its output to the given streams, and executes
+    /// programs according to the given options.
+    pub fn with_streams(input: I, output: O, options: MachineOptions) -> Self {
+        Self {
+            tape: Tape::new(
+                options.tape_length,
+                options.tape_policy,
+                options.max_tape_length,
+            ),
+            input,
+            output,
+            options,
+            steps: 0,
+        }
+    }
+
+    /// Executes the given program, compiling it to bytecode first, or to
+    /// native code when the options ask for it and it is supported. All the
+    /// output is flushed by the end of the execution. Fails if reading the
+    /// input or writing the output fails.
+    pub fn execute(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        if self.options.jit {
+            if let Some(result) = jit::execute(self, program) {
+                result?;
+                return self.output.flush().map_err(RuntimeError::from);
+            }
+        }
+        self.execute_bytecode(&Compiler::compile(program))
+    }
+
+    /// Executes the given bytecode, stopping at the first operation that
+    /// fails. Loops are executed by jumping to precomputed targets, never
+    /// by recursion, so any nesting depth is handled. Steps are only counted
+    /// when there is a step limit. All the output is flushed by the end of
+    /// the execution.
+    pub fn execute_bytecode(&mut self, bytecode: &Bytecode) -> Result<(), RuntimeError> {
+        let result = match self.options.max_steps {
+            None => self.run::<false>(bytecode, u64::MAX),
+            Some(limit) => self.run::<true>(bytecode, limit),
+        };
+        result.map_err(|(fault, counter)| fault.at(bytecode.span(counter)))?;
+        self.output.flush().map_err(RuntimeError::from)
+    }
+
+    /// Executes the given bytecode, counting every step and stopping after
+    /// the given number of them when LIMITED is set. Returns the fault that
+    /// stopped the execution and the index of the operation that caused it.
+    fn run<const LIMITED: bool>(
+        &mut self,
+        bytecode: &Bytecode,
+        limit: u64,
+    ) -> Result<(), (Fault, usize)> {
+        let ops = bytecode.ops();
+        let mut counter = 0;
+
+        while let Some(&op) = ops.get(counter) {
+            if LIMITED {
+                self.steps += 1;
+                if self.steps > limit {
+                    return Err((Fault::StepLimitExceeded { limit }, counter));
+                }
+            }
+            counter = self
+                .execute_op(op, counter, bytecode)
+                .map_err(|fault| (fault, counter))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the memory of the virtual machine.
+    pub fn tape(&self) -> &Tape<C> {
+        &self.tape
+    }
+
+    /// Returns the memory of the virtual machine, so it can be changed in
+    /// place.
+    pub(super) fn tape_mut(&mut self) -> &mut Tape<C> {
+        &mut self.tape
+    }
+
+    /// Returns the options that control how programs are executed.
+    pub(super) fn options(&self) -> &MachineOptions {
+        &self.options
+    }
+
+    /// Returns the stream from which the read instruction takes its bytes.
+    pub fn input(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns the stream to which the show instruction writes its bytes.
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+
+    /// Consumes the virtual machine, returning its input and output
+    /// streams.
+    pub fn into_streams(self) -> (I, O) {
+        (self.input, self.output)
+    }
+}
+
+/// Brainf*ck Virtual Machine. Its tape has 30,000 memory slots by default,
+/// but both its length and what happens past its ends can be configured.
+/// Each slot stores a value of the cell type, one unsigned byte by default.
+/// The execution of the virtual machine can only fail when its input or
+/// output streams fail, or when it goes beyond the limits set by its
+/// options. It reads its input from and writes its output to any pair of
+/// streams, the standard ones by default.
+pub struct VirtualMachine<I: Read = Stdin, O: Write = Stdout, C: Cell = u8> {
+    /// Virtual machine memory: its memory slots and the index of the slot
+    /// that is currently in use. This means that this is the slot where a
+    /// value will be read or written if an instruction requests it.
+    tape: Tape<C>,
+    /// Stream from which the read instruction takes its bytes.
+    input: I,
+    /// Stream to which the show instruction writes its bytes.
+    output: O,
+    /// Options that control how programs are executed.
+    options: MachineOptions,
+    /// Number of steps executed so far.
+    steps: u64,
+}
+
+/// Virtual machine that reads its input from a byte buffer and collects its
+/// output in another one.
+pub type InMemoryMachine<C = u8> = VirtualMachine<Cursor<Vec<u8>>, Vec<u8>, C>;
+
+/// Implements commands for the virtual machine. A command is anything that
+/// can be executed directly by an instruction, so everything here can be
+/// executed directly by the user.
+impl<I: Read, O: Write, C: Cell> VirtualMachine<I, O, C> {
+    /// Executes the given operation, found at the given index of the given
+    /// bytecode. Basically maps an operation to a method of this VM. Returns
+    /// the index of the next operation to execute.
+    #[inline(always)]
+    fn execute_op(&mut self, op: Op, counter: usize, bytecode: &Bytecode) -> Result<usize, Fault> {
+        match op {
+            Op::Advance => self.move_to_next_slot()?,
+            Op::Recede => self.move_to_previous_slot()?,
+            Op::Increment => self.increment_slot_value()?,
+            Op::Decrement => self.decrement_slot_value()?,
+            Op::Show => self.display_from_current_memslot()?,
+            Op::Read => self.read_from_user()?,
+            Op::Add(amount) => self.add_to_slot_value(amount.into())?,
+            Op::Move(offset) => self.move_by_slots(offset as isize)?,
+            Op::SetZero => self.set_current_memslot_value(C::ZERO),
+            Op::MulAdd { offset, factor } => {
+                self.multiply_into_slot(offset.into(), factor.into())?
+            }
+            Op::ScanRight => self.scan_for_zero(1)?,
+            Op::ScanLeft => self.scan_for_zero(-1)?,
+            Op::AddAt { offset, amount } => self.add_to_slot_at(offset.into(), amount.into())?,
+            Op::OutputAt { offset } => self.display_from_memslot_at(offset as isize)?,
+            Op::Print(byte) => self.display_byte(byte)?,
+            Op::SetAt { offset, value } => {
+                self.set_slot_at(offset.into(), C::from_i64(value.into()))?
+            }
+            Op::JumpIfZero(target) if !self.check_current_memslot() => return Ok(target as usize),
+            Op::JumpIfNotZero(target) if self.check_current_memslot() => return Ok(target as usize),
+            Op::JumpIfZero(_) | Op::JumpIfNotZero(_) => (),
+            Op::Wide(index) => self.execute_wide(bytecode.wide(index))?,
+        }
+        Ok(counter + 1)
+    }
+
+    /// Executes the given wide instruction: one whose operands are too
+    /// large for an operation. Every other instruction is ignored.
+    #[cold]
+    fn execute_wide(&mut self, kind: InstructionKind) -> Result<(), Fault> {
+        match kind {
+            InstructionKind::Add(amount) => self.add_to_slot_value(amount),
+            InstructionKind::Move(offset) => self.move_by_slots(offset),
+            InstructionKind::MulAdd { offset, factor } => self.multiply_into_slot(offset, factor),
+            InstructionKind::AddAt { offset, amount } => self.add_to_slot_at(offset, amount),
+            InstructionKind::OutputAt { offset } => self.display_from_memslot_at(offset),
+            InstructionKind::SetAt { offset, value } => {
+                self.set_slot_at(offset, C::from_i64(value))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes the lowest byte of the value stored in the current memory
+    /// slot to the output stream.
+    fn display_from_current_memslot(&mut self) -> Result<(), Fault> {
+        let value = self.get_current_memslot_value().to_byte();
+        self.output.write_all(&[value]).map_err(Fault::from)
+    }
+
+    /// Increments the value registered in the memory slot currently in use.
+    /// In case of overflow, does whatever the overflow policy says. Fails
+    /// if the policy treats the overflow as an error.
+    fn increment_slot_value(&mut self) -> Result<(), Fault> {
+        self.add_to_slot_value(1)
+    }
+
+    /// Decrements the value registered in the memory slot currently in use.
+    /// In case of underflow, does whatever the overflow policy says. Fails
+    /// if the policy treats the underflow as an error.
+    fn decrement_slot_value(&mut self) -> Result<(), Fault> {
+        self.add_to_slot_value(-1)
+    }
+
+    /// Advances to the next memory slot. What happens past the last memory
+    /// slot depends on the tape policy. Fails if the policy does not allow
+    /// moving there.
+    fn move_to_next_slot(&mut self) -> Result<(), Fault> {
+        self.tape.move_by(1).map_err(Fault::from)
+    }
+
+    /// Moves to the previous memory slot. What happens before the first
+    /// memory slot depends on the tape policy. Fails if the policy does not
+    /// allow moving there.
+    fn move_to_previous_slot(&mut self) -> Result<(), Fault> {
+        self.tape.move_by(-1).map_err(Fault::from)
+    }
+
+    /// Moves the pointer by the given number of memory slots (leftwards
+    /// when negative). What happens past either end of the tape depends on
+    /// the tape policy. Fails if the policy does not allow moving there.
+    fn move_by_slots(&mut self, offset: isize) -> Result<(), Fault> {
+        self.tape.move_by(offset).map_err(Fault::from)
+    }
+
+    /// Adds the given amount to the memory slot at the given distance from
+    /// the current one, following the overflow policy, without moving the
+    /// pointer. Fails if the tape policy does not allow reaching that slot,
+    /// or if the overflow policy treats the overflow as an error.
+    fn add_to_slot_at(&mut self, offset: isize, amount: i64) -> Result<(), Fault> {
+        let index = self.tape.resolve(offset)?;
+        let new_value = self
+            .tape
+            .get(index)
+            .add(amount, self.options.overflow)
+            .ok_or(Fault::Overflow)?;
+        self.tape.set(index, new_value);
+        Ok(())
+    }
+
+    /// Writes the lowest byte of the value stored in the memory slot at the
+    /// given distance from the current one to the output stream, without
+    /// moving the pointer. Fails if the tape policy does not allow reaching
+    /// that slot.
+    fn display_from_memslot_at(&mut self, offset: isize) -> Result<(), Fault> {
+        let index = self.tape.resolve(offset)?;
+        let value = self.tape.get(index).to_byte();
+        self.output.write_all(&[value]).map_err(Fault::from)
+    }
+
+    /// Writes the given byte to the output stream.
+    pub(super) fn display_byte(&mut self, byte: u8) -> Result<(), Fault> {
+        self.output.write_all(&[byte]).map_err(Fault::from)
+    }
+
+    /// Changes the value of the memory slot at the given distance from the
+    /// current one to the given value, without moving the pointer. Fails if
+    /// the tape policy does not allow reaching that slot.
+    fn set_slot_at(&mut self, offset: isize, value: C) -> Result<(), Fault> {
+        let index = self.tape.resolve(offset)?;
+        self.tape.set(index, value);
+        Ok(())
+    }
+
+    /// Adds the value of the current memory slot, multiplied by the given
+    /// factor, to the slot at the given distance from the current one,
+    /// following the overflow policy. Does nothing if the current slot is
+    /// 0, so the tape is not touched, just like the loop this replaces.
+    /// Fails if the tape policy does not allow reaching that slot, or if the
+    /// overflow policy treats the overflow as an error.
+    fn multiply_into_slot(&mut self, offset: isize, factor: i64) -> Result<(), Fault> {
+        let value = self.get_current_memslot_value();
+        if value.is_zero() {
+            return Ok(());
+        }
+
+        let index = self.tape.resolve(offset)?;
+        let new_value = self
+            .tape
+            .get(index)
+            .add_product(value, factor, self.options.overflow)
+            .ok_or(Fault::Overflow)?;
+        self.tape.set(index, new_value);
+        Ok(())
+    }
+
+    /// Moves the pointer by the given number of memory slots until the
+    /// current slot is 0. Every move is a step, just like every iteration of
+    /// the loop this replaces, so the step limit still stops programs that
+    /// scan forever. Fails if the tape policy does not allow moving there.
+    fn scan_for_zero(&mut self, offset: isize) -> Result<(), Fault> {
+        while self.check_current_memslot() {
+            if let Some(limit) = self.options.max_steps {
+                self.steps += 1;
+                if self.steps > limit {
+                    return Err(Fault::StepLimitExceeded { limit });
+                }
+            }
+            self.tape.move_by(offset)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a byte from the input stream and writes the value to the
+    /// current memory slot. Any pending output is flushed first, so
+    /// prompts are visible before the program waits for input. At the end
+    /// of the input, does whatever the EOF policy says. Fails if the input
+    /// stream fails, or if the policy treats the end of the input as an
+    /// error.
+    fn read_from_user(&mut self) -> Result<(), Fault> {
+        if let Some(input) = self.read_value()? {
+            self.set_current_memslot_value(input);
+        }
+        Ok(())
+    }
+
+    /// Reads a byte from the input stream, flushing any pending output
+    /// first, and returns the value the current memory slot should take:
+    /// None when the EOF policy leaves it unchanged. Fails if the input
+    /// stream fails, or if the policy treats the end of the input as an
+    /// error.
+    pub(super) fn read_value(&mut self) -> Result<Option<C>, Fault> {
+        self.output.flush()?;
+        let mut buffer = [0u8; 1];
+
+        match self.input.read_exact(&mut buffer) {
+            Ok(()) => Ok(Some(C::from_byte(buffer[0]))),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => match self.options.eof {
+                EofPolicy::Zero => Ok(Some(C::ZERO)),
+                EofPolicy::MinusOne => Ok(Some(C::minus_one())),
+                EofPolicy::Unchanged => Ok(None),
+                EofPolicy::Error => Err(Fault::UnexpectedEof),
+            },
+            Err(e) => Err(Fault::from(e)),
+        }
+    }
+}
+
+/// Implements special virtual machine operations. Nothing here is executed
+/// directly by an instruction and therefore cannot be executed directly by
+/// the user.
+impl<I: Read, O: Write, C: Cell> VirtualMachine<I, O, C> {
+    /// Returns the value recorded in the current memory slot.
+    fn get_current_memslot_value(&self) -> C {
+        self.tape.current()
+    }
+
+    /// Changes the current memslot value to the given value.
+    fn set_current_memslot_value(&mut self, new_value: C) {
+        self.tape.set_current(new_value);
+    }
+
+    /// Adds the given amount to the current memslot value, following the
+    /// overflow policy. Fails if the policy treats the overflow as an error.
+    fn add_to_slot_value(&mut self, amount: i64) -> Result<(), Fault> {
+        let new_value = self
+            .get_current_memslot_value()
+            .add(amount, self.options.overflow)
+            .ok_or(Fault::Overflow)?;
+        self.set_current_memslot_value(new_value);
+        Ok(())
+    }
+
+    /// Returns whether or not the value written to the current memory slot
+    /// is a value treated as true by the language (any value except 0).
+    fn check_current_memslot(&self) -> bool {
+        !self.get_current_memslot_value().is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::virtual_machine::{EofPolicy, TapePolicy};
+
+    /// Machine that reads from and writes to memory, with the given cell
+    /// type.
+    type TestMachine<C> = VirtualMachine<Cursor<Vec<u8>>, Vec<u8>, C>;
+
+    /// Runs the given source on a machine with the given EOF policy and
+    /// cell type, feeding it the given input, and returns the result and
+    /// the machine.
+    fn run<C: Cell>(
+        source: &str,
+        input: &[u8],
+        eof: EofPolicy,
+    ) -> (Result<(), RuntimeError>, TestMachine<C>) {
+        let program = crate::compile(source).expect("valid program");
+        let options = MachineOptions {
+            eof,
+            ..MachineOptions::default()
+        };
+        let mut vm = VirtualMachine::with_streams(Cursor::new(input.to_vec()), Vec::new(), options);
+        (vm.execute(&program), vm)
+    }
+
+    #[test]
+    fn input_bytes_are_read_in_order() {
+        let (result, vm) = run::<u8>(",>,>,", b"ab", EofPolicy::Zero);
+
+        assert!(result.is_ok());
+        assert_eq!(&vm.tape().cells()[..3], &[b'a', b'b', 0]);
+    }
+
+    #[test]
+    fn end_of_input_writes_zero() {
+        let (result, vm) = run::<u8>("+++,", b"", EofPolicy::Zero);
+
+        assert!(result.is_ok());
+        assert_eq!(vm.tape().current(), 0);
+    }
+
+    #[test]
+    fn end_of_input_writes_minus_one() {
+        let (_, bytes) = run::<u8>("+++,", b"", EofPolicy::MinusOne);
+        let (_, signed) = run::<i16>("+++,", b"", EofPolicy::MinusOne);
+        let (_, wide) = run::<u32>("+++,", b"", EofPolicy::MinusOne);
+
+        assert_eq!(bytes.tape().current(), 255);
+        assert_eq!(signed.tape().current(), -1);
+        assert_eq!(wide.tape().current(), u32::MAX);
+    }
+
+    #[test]
+    fn end_of_input_leaves_the_cell_unchanged() {
+        let (result, vm) = run::<u8>("+++,", b"", EofPolicy::Unchanged);
+
+        assert!(result.is_ok());
+        assert_eq!(vm.tape().current(), 3);
+    }
+
+    #[test]
+    fn end_of_input_is_an_error_where_it_was_read() {
+        let (result, vm) = run::<u8>(",.\n+,", b"x", EofPolicy::Error);
+
+        match result {
+            Err(RuntimeError::UnexpectedEof { span }) => {
+                assert_eq!((span.line, span.column), (2, 2));
+            }
+            other => panic!("expected an unexpected end of input, got {:?}", other),
+        }
+        assert_eq!(vm.output(), b"x");
+    }
+
+    #[test]
+    fn faults_point_at_the_offending_instruction() {
+        let program = crate::compile("+\n>>").expect("valid program");
+        let options = MachineOptions {
+            tape_length: 2,
+            tape_policy: TapePolicy::Error,
+            ..MachineOptions::default()
+        };
+        let result = VirtualMachine::<_, _, u8>::with_streams(&b""[..], Vec::new(), options)
+            .execute(&program);
+
+        match result {
+            Err(RuntimeError::TapeOutOfBounds { span, cell }) => {
+                assert_eq!((span.line, span.column, cell), (2, 2, 2));
+            }
+            other => panic!("expected the pointer out of the tape, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn wide_instructions_run_like_narrow_ones() {
+        let span = crate::Span::new(0, 1, 1, 1);
+        let program = Program::new(
+            [
+                InstructionKind::SetAt {
+                    offset: 1,
+                    value: (1 << 40) + 7,
+                },
+                InstructionKind::Move(100_000 * 30_000 + 1),
+                InstructionKind::Add(1 << 33),
+            ]
+            .into_iter()
+            .map(|kind| crate::Instruction::new(kind, span))
+            .collect(),
+        );
+        let mut vm = VirtualMachine::<_, _, u64>::with_streams(
+            &b""[..],
+            Vec::new(),
+            MachineOptions::default(),
+        );
+
+        assert!(vm.execute(&program).is_ok());
+        assert_eq!(vm.tape().position(), 1);
+        assert_eq!(vm.tape().current(), (1 << 40) + (1 << 33) + 7);
+    }
+
+    #[test]
+    fn the_step_limit_stops_endless_loops() {
+        let program = crate::compile("+[]").expect("valid program");
+        let options = MachineOptions {
+            max_steps: Some(100),
+            ..MachineOptions::default()
+        };
+        let result = VirtualMachine::<_, _, u8>::with_streams(&b""[..], Vec::new(), options)
+            .execute(&program);
+
+        assert!(matches!(
+            result,
+            Err(RuntimeError::StepLimitExceeded { limit: 100 })
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+//! The virtual machine module encapsulates the execution of programs. It
+//! defines the VirtualMachine type, which executes the instructions produced
+//! by the parsing process, and the errors that may happen while doing so.
+
+/// This module defines the Cell trait, which is implemented by every type
+/// of value a memory slot can store.
+pub mod cell;
+
+/// This module defines the errors that may happen while the virtual machine
+/// executes a program. These errors are suitable for display by the
+/// program.
+mod errors;
+
+/// This module translates programs into native machine code, which the
+/// virtual machine runs instead of interpreting them when asked to.
+mod jit;
+
+/// This module defines the VirtualMachine type, which executes programs.
+pub mod machine;
+
+/// This module defines the options that control how the virtual machine
+/// executes a program.
+pub mod options;
+
+/// This module defines the Tape type, which is the memory of the virtual
+/// machine.
+pub mod tape;
+
+pub(crate) use cell::with_cell_type;
+pub use cell::{Cell, CellType};
+use errors::Fault;
+pub use errors::RuntimeError;
+pub use machine::{InMemoryMachine, VirtualMachine};
+pub use options::{EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+pub use tape::Tape;
+use tape::TapeFault;
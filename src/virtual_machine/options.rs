@@ -0,0 +1,98 @@
+use super::CellType;
+
+/// Options that control how the virtual machine executes a program.
+#[derive(Debug, Clone)]
+pub struct MachineOptions {
+    /// Maximum number of steps the virtual machine may execute before
+    /// giving up. Every instruction executed is a step, and so is every
+    /// check of a loop condition, so optimized programs usually take far
+    /// fewer steps. There is no limit when None.
+    pub max_steps: Option<u64>,
+    /// What the read instruction does when the input has no more bytes.
+    pub eof: EofPolicy,
+    /// Number of memory slots the tape starts with.
+    pub tape_length: usize,
+    /// What happens when the pointer goes past either end of the tape.
+    pub tape_policy: TapePolicy,
+    /// Maximum number of memory slots a growable tape may reach. There is
+    /// no limit when None.
+    pub max_tape_length: Option<usize>,
+    /// Type of value stored in each memory slot. A VirtualMachine always
+    /// uses its own cell type parameter; this field is what the library
+    /// functions use to pick that parameter at runtime.
+    pub cell: CellType,
+    /// What happens when a memory slot goes beyond the values its type can
+    /// store.
+    pub overflow: OverflowPolicy,
+    /// When set, programs are translated into native machine code before
+    /// being executed, which is much faster for long-running programs.
+    /// This is only done on x86-64 Linux, with a tape that wraps around or
+    /// stops at its ends, cells that wrap around and no step limit; in any
+    /// other case, programs are interpreted as usual.
+    pub jit: bool,
+}
+
+impl MachineOptions {
+    /// Number of memory slots of the tape when no length is given. This is
+    /// the length of the tape in the original brainf*ck implementation.
+    pub const DEFAULT_TAPE_LENGTH: usize = 30_000;
+}
+
+impl Default for MachineOptions {
+    fn default() -> Self {
+        Self {
+            max_steps: None,
+            eof: EofPolicy::default(),
+            tape_length: Self::DEFAULT_TAPE_LENGTH,
+            tape_policy: TapePolicy::default(),
+            max_tape_length: None,
+            cell: CellType::default(),
+            overflow: OverflowPolicy::default(),
+            jit: false,
+        }
+    }
+}
+
+/// What the read instruction does when the input has no more bytes. Each
+/// brainf*ck interpreter picks one of these, and programs are usually
+/// written against a specific one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EofPolicy {
+    /// Writes 0 to the current memory slot.
+    #[default]
+    Zero,
+    /// Writes -1 to the current memory slot. For unsigned slots, this is
+    /// the value with every bit set (255 for one-byte slots).
+    MinusOne,
+    /// Leaves the current memory slot unchanged.
+    Unchanged,
+    /// Stops the execution with a runtime error.
+    Error,
+}
+
+/// What happens when the pointer goes past either end of the tape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TapePolicy {
+    /// The pointer goes around to the other end of the tape.
+    #[default]
+    Wrap,
+    /// Stops the execution with a runtime error pointing at the
+    /// instruction that moved the pointer.
+    Error,
+    /// The tape grows in that direction, so it never runs out of slots.
+    Grow,
+}
+
+/// What happens when a memory slot goes beyond the values its type can
+/// store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// The value goes around to the other end of the range of the type.
+    #[default]
+    Wrap,
+    /// The value stays at the end of the range of the type.
+    Saturate,
+    /// Stops the execution with a runtime error pointing at the
+    /// instruction that changed the value.
+    Trap,
+}
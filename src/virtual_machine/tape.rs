@@ -0,0 +1,274 @@
+use super::{Cell, TapePolicy};
+
+/// Memory of the virtual machine: a row of memory slots and a pointer to
+/// the slot currently in use. What happens when the pointer goes past
+/// either end of the tape is decided by its policy.
+#[derive(Debug, Clone)]
+pub struct Tape<C: Cell = u8> {
+    /// Memory slots. Each slot can store a value of the cell type.
+    cells: Vec<C>,
+    /// Index, in cells, of the memory slot that is currently in use.
+    position: usize,
+    /// Index, in cells, of the slot the pointer started at. It only
+    /// changes when the tape grows leftwards, so positions reported to the
+    /// user stay the same.
+    origin: usize,
+    /// What happens when the pointer goes past either end of the tape.
+    policy: TapePolicy,
+    /// Maximum number of slots a growable tape may have. There is no limit
+    /// when None.
+    max_length: Option<usize>,
+}
+
+/// Ways a tape may fail to move its pointer. They lack the location of the
+/// offending instruction, which is added by the virtual machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TapeFault {
+    /// The pointer went past either end of the tape, to the given cell
+    /// (counted from the cell the pointer started at).
+    OutOfBounds { cell: i64 },
+    /// The tape would have to grow beyond the given number of slots.
+    LimitExceeded { limit: usize },
+}
+
+/// Implements the public Tape API.
+impl<C: Cell> Tape<C> {
+    /// Value written to a memory slot when it is created.
+    pub const INITIAL_VALUE: C = C::ZERO;
+
+    /// Creates a new tape with the given number of slots (at least one),
+    /// with the pointer at the first one and every slot set to 0.
+    pub fn new(length: usize, policy: TapePolicy, max_length: Option<usize>) -> Self {
+        Self {
+            cells: vec![Self::INITIAL_VALUE; length.max(1)],
+            position: 0,
+            origin: 0,
+            policy,
+            max_length,
+        }
+    }
+
+    /// Returns every memory slot of the tape, including the ones created
+    /// to the left of the starting cell.
+    pub fn cells(&self) -> &[C] {
+        &self.cells
+    }
+
+    /// Returns the position of the pointer, counted from the cell it
+    /// started at. It is negative if the tape grew leftwards and the
+    /// pointer is still there.
+    pub fn position(&self) -> i64 {
+        self.position as i64 - self.origin as i64
+    }
+
+    /// Returns the value recorded in the current memory slot.
+    pub fn current(&self) -> C {
+        self.cells[self.position]
+    }
+
+    /// Changes the current memory slot value to the given value.
+    pub fn set_current(&mut self, value: C) {
+        self.cells[self.position] = value;
+    }
+
+    /// Returns the index, in cells, of the slot the pointer started at.
+    pub(crate) fn origin(&self) -> usize {
+        self.origin
+    }
+
+    /// Returns the index, in cells, of the memory slot that is currently in
+    /// use.
+    pub(crate) fn index(&self) -> usize {
+        self.position
+    }
+
+    /// Makes the memory slot at the given index, in cells, the current one.
+    pub(crate) fn set_index(&mut self, index: usize) {
+        self.position = index;
+    }
+
+    /// Returns every memory slot of the tape, so they can be changed in
+    /// place.
+    pub(crate) fn cells_mut(&mut self) -> &mut [C] {
+        &mut self.cells
+    }
+
+    /// Returns the value recorded in the memory slot at the given index, in
+    /// cells, as returned by "resolve".
+    pub(crate) fn get(&self, index: usize) -> C {
+        self.cells[index]
+    }
+
+    /// Changes the value of the memory slot at the given index, in cells,
+    /// as returned by "resolve".
+    pub(crate) fn set(&mut self, index: usize, value: C) {
+        self.cells[index] = value;
+    }
+}
+
+/// Implements the pointer arithmetic of the tape.
+impl<C: Cell> Tape<C> {
+    /// Moves the pointer by the given number of slots (leftwards when
+    /// negative), following the policy of the tape.
+    pub(crate) fn move_by(&mut self, offset: isize) -> Result<(), TapeFault> {
+        let target = self.position.wrapping_add_signed(offset);
+        self.position = match target < self.cells.len() {
+            true => target,
+            false => self.resolve_outside(offset)?,
+        };
+        Ok(())
+    }
+
+    /// Returns the index, in cells, of the slot at the given distance from
+    /// the current one, following the policy of the tape. Growable tapes
+    /// grow as needed to contain that slot.
+    #[inline]
+    pub(crate) fn resolve(&mut self, offset: isize) -> Result<usize, TapeFault> {
+        let target = self.position.wrapping_add_signed(offset);
+        match target < self.cells.len() {
+            true => Ok(target),
+            false => self.resolve_outside(offset),
+        }
+    }
+
+    /// Returns the index, in cells, of the slot at the given distance from
+    /// the current one, which is past either end of the tape, following the
+    /// policy of the tape.
+    #[cold]
+    fn resolve_outside(&mut self, offset: isize) -> Result<usize, TapeFault> {
+        let length = self.cells.len() as isize;
+        let target = self.position as isize + offset;
+
+        match self.policy {
+            TapePolicy::Wrap => Ok(target.rem_euclid(length) as usize),
+            // The cell named is the first one past the end, which the
+            // pointer would reach first if it moved one cell at a time, so
+            // folded moves fail like the commands they replace.
+            TapePolicy::Error => Err(TapeFault::OutOfBounds {
+                cell: target.clamp(-1, length) as i64 - self.origin as i64,
+            }),
+            TapePolicy::Grow if target < 0 => {
+                self.grow_left(target.unsigned_abs())?;
+                Ok((self.position as isize + offset) as usize)
+            }
+            TapePolicy::Grow => {
+                self.grow_right(target as usize + 1 - self.cells.len())?;
+                Ok(target as usize)
+            }
+        }
+    }
+
+    /// Adds at least the given number of slots to the right end of the
+    /// tape. Fails if the tape would grow beyond its maximum length.
+    fn grow_right(&mut self, needed: usize) -> Result<(), TapeFault> {
+        let extra = self.growth(needed)?;
+        self.cells
+            .resize(self.cells.len() + extra, Self::INITIAL_VALUE);
+        Ok(())
+    }
+
+    /// Adds at least the given number of slots to the left end of the tape,
+    /// shifting every index kept by the tape. Fails if the tape would grow
+    /// beyond its maximum length.
+    fn grow_left(&mut self, needed: usize) -> Result<(), TapeFault> {
+        let extra = self.growth(needed)?;
+        let mut cells = vec![Self::INITIAL_VALUE; extra + self.cells.len()];
+        cells[extra..].copy_from_slice(&self.cells);
+        self.cells = cells;
+        self.position += extra;
+        self.origin += extra;
+        Ok(())
+    }
+
+    /// Returns how many slots to add to the tape, when at least the given
+    /// number of slots is needed. The tape at least doubles, so growing
+    /// stays cheap, but never goes beyond its maximum length.
+    fn growth(&self, needed: usize) -> Result<usize, TapeFault> {
+        let length = self.cells.len();
+        let extra = needed.max(length);
+
+        match self.max_length {
+            Some(limit) if length + needed > limit => Err(TapeFault::LimitExceeded { limit }),
+            Some(limit) => Ok(extra.min(limit - length)),
+            None => Ok(extra),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tapes_start_with_every_slot_at_zero() {
+        let tape = Tape::<u8>::new(5, TapePolicy::Wrap, None);
+
+        assert_eq!(tape.cells(), &[0; 5]);
+        assert_eq!(tape.position(), 0);
+        assert_eq!(Tape::<u8>::new(0, TapePolicy::Wrap, None).cells().len(), 1);
+    }
+
+    #[test]
+    fn wrapping_tapes_go_around_both_ends() {
+        let mut tape = Tape::<u8>::new(5, TapePolicy::Wrap, None);
+
+        tape.move_by(-1).expect("wraps");
+        assert_eq!(tape.position(), 4);
+        tape.move_by(3).expect("wraps");
+        assert_eq!(tape.position(), 2);
+        assert_eq!(tape.resolve(-13), Ok(4));
+        assert_eq!(tape.cells().len(), 5);
+    }
+
+    #[test]
+    fn bounded_tapes_refuse_to_go_past_either_end() {
+        let mut tape = Tape::<u8>::new(5, TapePolicy::Error, None);
+
+        assert_eq!(tape.move_by(-1), Err(TapeFault::OutOfBounds { cell: -1 }));
+        assert_eq!(tape.position(), 0);
+        tape.move_by(4).expect("last slot");
+        assert_eq!(tape.move_by(1), Err(TapeFault::OutOfBounds { cell: 5 }));
+        assert_eq!(tape.resolve(-7), Err(TapeFault::OutOfBounds { cell: -1 }));
+        assert_eq!(tape.resolve(9), Err(TapeFault::OutOfBounds { cell: 5 }));
+        assert_eq!(tape.position(), 4);
+    }
+
+    #[test]
+    fn growing_tapes_grow_rightwards() {
+        let mut tape = Tape::<u8>::new(4, TapePolicy::Grow, None);
+        tape.set_current(7);
+
+        tape.move_by(5).expect("grows");
+        assert_eq!(tape.position(), 5);
+        assert!(tape.cells().len() >= 8);
+        assert_eq!(tape.cells()[0], 7);
+    }
+
+    #[test]
+    fn growing_tapes_grow_leftwards_keeping_positions() {
+        let mut tape = Tape::<u8>::new(4, TapePolicy::Grow, None);
+        tape.set_current(7);
+
+        tape.move_by(-3).expect("grows");
+        assert_eq!(tape.position(), -3);
+        assert!(tape.cells().len() >= 8);
+        tape.move_by(3).expect("back at the start");
+        assert_eq!(tape.position(), 0);
+        assert_eq!(tape.current(), 7);
+    }
+
+    #[test]
+    fn growing_tapes_stop_at_their_maximum_length() {
+        let mut tape = Tape::<u8>::new(4, TapePolicy::Grow, Some(6));
+
+        tape.move_by(5).expect("within the limit");
+        assert_eq!(tape.cells().len(), 6);
+        assert_eq!(tape.move_by(1), Err(TapeFault::LimitExceeded { limit: 6 }));
+        assert_eq!(
+            tape.resolve(-6 - 1),
+            Err(TapeFault::LimitExceeded { limit: 6 })
+        );
+        assert_eq!(tape.position(), 5);
+        assert_eq!(tape.cells().len(), 6);
+    }
+}
@@ -0,0 +1,64 @@
+//! Tests for the assembly backend. The emitted listings are compared with
+//! the files stored under "tests/golden/asm" and, on x86-64 Linux with the
+//! GNU assembler and linker installed, built, run and cross-checked
+//! against the interpreter.
+
+mod common;
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use std::process::Command;
+
+#[test]
+fn hello_world_matches_the_golden_file() {
+    common::check_golden(
+        Target::Asm,
+        "hello.s",
+        common::HELLO,
+        &MachineOptions::default(),
+    );
+}
+
+#[test]
+fn errors_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::U32,
+        eof: EofPolicy::Error,
+        tape_policy: TapePolicy::Error,
+        tape_length: 16,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Asm, "errors_u32.s", ",[->+<]>[>]<.", &options);
+}
+
+#[test]
+fn unsupported_options_are_rejected() {
+    let grow = MachineOptions {
+        tape_policy: TapePolicy::Grow,
+        ..MachineOptions::default()
+    };
+    common::check_unsupported(Target::Asm, &grow, "growing tapes");
+
+    let trap = MachineOptions {
+        overflow: OverflowPolicy::Trap,
+        ..MachineOptions::default()
+    };
+    common::check_unsupported(Target::Asm, &trap, "overflow policies other than wrap");
+}
+
+#[test]
+#[cfg_attr(
+    not(all(target_os = "linux", target_arch = "x86_64")),
+    ignore = "executables only run on x86-64 Linux"
+)]
+fn assembled_programs_behave_like_the_interpreter() {
+    if !common::tools_installed(&["as", "ld"]) {
+        return;
+    }
+    common::cross_check(Target::Asm, "s", &common::CASES, |listing| {
+        let object = listing.with_extension("o");
+        let executable = listing.with_extension("exe");
+        common::build(Command::new("as").arg("-o").arg(&object).arg(listing));
+        common::build(Command::new("ld").arg("-o").arg(&executable).arg(&object));
+        Command::new(executable)
+    });
+}
@@ -0,0 +1,68 @@
+//! Tests for the C backend. The emitted sources are compared with the files
+//! stored under "tests/golden/c" and, when a C compiler is installed,
+//! compiled, run and cross-checked against the interpreter.
+
+mod common;
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use std::process::Command;
+
+#[test]
+fn hello_world_matches_the_golden_file() {
+    common::check_golden(
+        Target::C,
+        "hello.c",
+        common::HELLO,
+        &MachineOptions::default(),
+    );
+}
+
+#[test]
+fn errors_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::I16,
+        eof: EofPolicy::Error,
+        tape_policy: TapePolicy::Error,
+        tape_length: 16,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::C, "errors_i16.c", ",[->+<]>[>]<.", &options);
+}
+
+#[test]
+fn growing_tapes_are_rejected() {
+    let options = MachineOptions {
+        tape_policy: TapePolicy::Grow,
+        ..MachineOptions::default()
+    };
+    common::check_unsupported(Target::C, &options, "growing tapes");
+}
+
+#[test]
+fn overflow_policies_other_than_wrap_are_rejected() {
+    for overflow in [OverflowPolicy::Saturate, OverflowPolicy::Trap] {
+        let options = MachineOptions {
+            overflow,
+            ..MachineOptions::default()
+        };
+        common::check_unsupported(Target::C, &options, "overflow policies other than wrap");
+    }
+}
+
+#[test]
+fn compiled_programs_behave_like_the_interpreter() {
+    if !common::tools_installed(&["cc"]) {
+        return;
+    }
+    common::cross_check(Target::C, "c", &common::CASES, |source| {
+        let executable = source.with_extension("exe");
+        common::build(
+            Command::new("cc")
+                .arg("-w")
+                .arg("-o")
+                .arg(&executable)
+                .arg(source),
+        );
+        Command::new(executable)
+    });
+}
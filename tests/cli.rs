@@ -0,0 +1,186 @@
+//! Tests for the command line interface. They run the interpreter binary
+//! and check the exit code of every class of failure, which scripts rely
+//! on, and that diagnostics never mix with the output of the program.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Writes the given source code to a file of the given name, in a
+/// directory of its own for this test binary, and returns its path.
+fn program_file(name: &str, source: &str) -> String {
+    let directory = std::env::temp_dir().join(format!("brain-rusted-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).expect("writable temporary directory");
+    let path = directory.join(name);
+    std::fs::write(&path, source).expect("writable program file");
+    path.to_string_lossy().into_owned()
+}
+
+/// Runs the interpreter with the given arguments, feeding it the given
+/// input.
+fn run(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_brain-rusted"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("runnable interpreter");
+    child
+        .stdin
+        .take()
+        .expect("piped input")
+        .write_all(input)
+        .expect("writable input");
+    child.wait_with_output().expect("finished interpreter")
+}
+
+/// Asserts that the given run ended with the given exit code, wrote
+/// nothing to the standard output and a diagnostic containing the given
+/// text to the standard error.
+fn assert_failure(output: &Output, status: i32, diagnostic: &str) {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(status), "stderr: {}", stderr);
+    assert!(output.stdout.is_empty(), "diagnostics written to stdout");
+    assert!(stderr.contains(diagnostic), "unexpected stderr: {}", stderr);
+}
+
+#[test]
+fn successful_programs_exit_with_0() {
+    let path = program_file("echo.bf", ",[.,]");
+    let output = run(&[&path], b"hi");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"hi");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn invalid_arguments_exit_with_64() {
+    let path = program_file("usage.bf", "+");
+
+    assert_failure(&run(&["--frobnicate", &path], b""), 64, "Unknown flag");
+    assert_failure(&run(&[], b""), 64, "Usage:");
+    assert_failure(&run(&["--cell=u7", &path], b""), 64, "--cell");
+    assert_failure(&run(&["-o", "out", &path], b""), 64, "--emit");
+}
+
+#[test]
+fn unsupported_targets_exit_with_64() {
+    let path = program_file("unsupported.bf", "+.");
+    let output = run(&["--tape=grow", "--emit=c", &path], b"");
+
+    assert_failure(&output, 64, "growing tapes");
+}
+
+#[test]
+fn syntax_errors_exit_with_65() {
+    let unclosed = program_file("unclosed.bf", "+[\n-");
+    let strict = program_file("strict.bf", "+ comment");
+
+    assert_failure(&run(&[&unclosed], b""), 65, "unclosed loop");
+    assert_failure(&run(&["--strict", &strict], b""), 65, "invalid token");
+}
+
+#[test]
+fn missing_files_exit_with_66() {
+    let output = run(&["/nonexistent/program.bf"], b"");
+
+    assert_failure(&output, 66, "File not found");
+}
+
+#[test]
+fn runtime_errors_exit_with_70_after_the_output_so_far() {
+    let path = program_file("runtime.bf", "++++++++[>++++++++<-]>+.<<");
+    let output = run(&["--tape=error", &path], b"");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(output.stdout, b"A");
+    assert!(
+        stderr.contains("Pointer moved out of the tape"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn unwritable_outputs_exit_with_73() {
+    let path = program_file("output.bf", "+.");
+    let output = run(&["--emit=c", "-o", "/nonexistent/program.c", &path], b"");
+
+    assert_failure(&output, 73, "/nonexistent/program.c");
+}
+
+#[test]
+fn exceeded_limits_exit_with_75() {
+    let endless = program_file("endless.bf", "+[]");
+    let growing = program_file("growing.bf", "+[>+]");
+
+    assert_failure(
+        &run(&["-O0", "--max-steps=100", &endless], b""),
+        75,
+        "Step limit",
+    );
+    assert_failure(
+        &run(&["--tape=grow", "--max-tape=64", &growing], b""),
+        75,
+        "Tape limit",
+    );
+}
+
+#[test]
+fn every_optimization_level_writes_the_same_output() {
+    let path = program_file(
+        "levels.bf",
+        "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.,[->+<]>.",
+    );
+    for level in ["-O0", "-O1", "-O2", "-O3", "--naive"] {
+        let output = run(&[level, &path], b"!");
+
+        assert_eq!(output.status.code(), Some(0), "{}", level);
+        assert_eq!(output.stdout, b"Helloy", "{}", level);
+    }
+}
+
+#[test]
+fn unknown_pass_names_exit_with_64() {
+    let path = program_file("passes.bf", "+.");
+
+    assert_failure(
+        &run(&["--enable-pass=folding,inlining", &path], b""),
+        64,
+        "--enable-pass",
+    );
+    assert_failure(&run(&["--disable-pass=", &path], b""), 64, "--disable-pass");
+}
+
+#[test]
+fn the_report_shows_every_pass_on_stderr() {
+    let path = program_file("report.bf", "+++[->++<]>>>,.");
+    let output = run(
+        &[
+            "--report",
+            "--disable-pass=fusion",
+            "--enable-pass=partial-evaluation",
+            &path,
+        ],
+        b"x",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<Vec<&str>> = stderr
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+
+    assert_eq!(output.status.code(), Some(0), "{}", stderr);
+    assert_eq!(output.stdout, b"x");
+    assert_eq!(
+        lines,
+        [
+            vec!["pass", "before", "after", "removed", "rewritten"],
+            vec!["folding", "15", "10", "5", "6"],
+            vec!["idioms", "10", "6", "4", "2"],
+            vec!["partial-evaluation", "6", "4", "2", "2"],
+        ]
+    );
+}
@@ -0,0 +1,260 @@
+//! Helpers shared by the tests of the backends that write source files or
+//! executables: golden files of the emitted programs, and cross-checks that
+//! build and run them and compare what they do with what the interpreter
+//! does.
+
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use brain_rusted::{EmitError, MachineOptions, Target};
+
+/// Program that prints "Hello World!\n".
+pub const HELLO: &str = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+/// A program run by the cross-checks: its source, the options it is run
+/// and emitted with, and its input.
+pub struct Case {
+    pub source: &'static str,
+    pub args: &'static [&'static str],
+    pub input: &'static [u8],
+}
+
+/// Programs every backend supports, covering the cell types, the EOF
+/// policies and the tape policies other than growing, with and without
+/// errors.
+pub const CASES: [Case; 10] = [
+    Case {
+        source: HELLO,
+        args: &[],
+        input: b"",
+    },
+    Case {
+        source: ",[.,]",
+        args: &[],
+        input: b"echo",
+    },
+    Case {
+        source: "++++[>++++[>++++<-]<-]>>+.",
+        args: &["-O0"],
+        input: b"",
+    },
+    Case {
+        source: ",+[-.,+]",
+        args: &["--eof=minus-one", "--cell=u16"],
+        input: b"minus one",
+    },
+    Case {
+        source: ",.,.,.",
+        args: &["--eof=unchanged", "--cell=i32"],
+        input: b"u",
+    },
+    Case {
+        source: ",.,.,.",
+        args: &["--eof=error"],
+        input: b"ab",
+    },
+    Case {
+        source: "+[>+]",
+        args: &["--tape=error", "--tape-length=16"],
+        input: b"",
+    },
+    Case {
+        source: "<<<++++++++[>>>++++++++<<<-]>>>+.<<<<<.",
+        args: &["--tape-length=8"],
+        input: b"",
+    },
+    Case {
+        source: "-[->+<]>[-<+>]<.>>++++++++++.",
+        args: &["--cell=i8"],
+        input: b"",
+    },
+    Case {
+        source: ",[>+>++<<-]>>[-<<+>>]<<.",
+        args: &["--cell=u64", "--tape-length=3"],
+        input: b"\xff",
+    },
+];
+
+/// Programs that need overflow policies other than wrapping or growing
+/// tapes, which only some backends support.
+pub const EXTENDED_CASES: [Case; 5] = [
+    Case {
+        source: "-.++.",
+        args: &["--overflow=saturate"],
+        input: b"",
+    },
+    Case {
+        source: "+.-.-",
+        args: &["--overflow=trap"],
+        input: b"",
+    },
+    Case {
+        source: "++++++++[>++++++++<-]>+[>+>+<<-]>.>.",
+        args: &["--tape=grow", "--tape-length=1"],
+        input: b"",
+    },
+    Case {
+        source: "+[>+]",
+        args: &["--tape=grow", "--tape-length=2", "--max-tape=100"],
+        input: b"",
+    },
+    Case {
+        source: "<<+++++++++++++++++++++++++++++++++.",
+        args: &["--tape=grow", "--tape-length=1"],
+        input: b"",
+    },
+];
+
+/// Returns the given source, optimized for the given options, emitted for
+/// the given target.
+pub fn emit(source: &str, target: Target, options: &MachineOptions) -> Result<Vec<u8>, EmitError> {
+    let program = brain_rusted::compile(source).expect("valid program");
+    let program = brain_rusted::optimize(&program, options);
+    brain_rusted::emit(&program, target, options)
+}
+
+/// Emits the given source for the given target and compares it with the
+/// golden file of the given name, under "tests/golden/<target>". When the
+/// backend changes on purpose, running the tests with UPDATE_GOLDEN=1
+/// rewrites the stored files instead.
+pub fn check_golden(target: Target, name: &str, source: &str, options: &MachineOptions) {
+    let emitted = emit(source, target, options).expect("supported options");
+    let emitted = String::from_utf8(emitted).expect("UTF-8");
+
+    let directory = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), target);
+    let path = format!("{}/{}", directory, name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(&directory).expect("writable golden directory");
+        std::fs::write(&path, &emitted).expect("writable golden file");
+    }
+    let golden = std::fs::read_to_string(&path).expect("golden file present");
+    assert_eq!(emitted, golden, "{} differs from {}", name, path);
+}
+
+/// Checks that emitting a program for the given target with the given
+/// options fails, naming the given option.
+pub fn check_unsupported(target: Target, options: &MachineOptions, option: &str) {
+    match emit("+[>+<-].", target, options) {
+        Err(EmitError::Unsupported {
+            target: refused,
+            option: named,
+        }) => {
+            assert_eq!(refused, target);
+            assert_eq!(named, option);
+        }
+        other => panic!(
+            "{} emitted with {:?}: {:?}",
+            target,
+            options,
+            other.map(|_| ())
+        ),
+    }
+}
+
+/// Returns whether the given tool can be run.
+fn has_tool(name: &str) -> bool {
+    Command::new(name)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Returns whether every given tool can be run, so the test that needs
+/// them can go on. When one cannot, the test fails if the CI variable is
+/// set, so continuous integration never silently skips it, and otherwise
+/// says it is skipped and should return.
+pub fn tools_installed(tools: &[&str]) -> bool {
+    let missing: Vec<_> = tools.iter().filter(|tool| !has_tool(tool)).collect();
+    if missing.is_empty() {
+        return true;
+    }
+    assert!(
+        std::env::var_os("CI").is_none(),
+        "{:?} must be installed on CI",
+        missing
+    );
+    eprintln!("skipped: {:?} not installed", missing);
+    false
+}
+
+/// Returns an empty directory, unique to the given test, for the files a
+/// cross-check writes.
+pub fn scratch(name: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("brain-rusted-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).expect("writable temporary directory");
+    directory
+}
+
+/// Runs the given command with the given input, and returns its exit code
+/// and everything it wrote to the standard output.
+pub fn run(command: &mut Command, input: &[u8]) -> (Option<i32>, Vec<u8>) {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("runnable command");
+    // Programs may exit before reading all their input, closing the pipe.
+    let _ = child.stdin.take().expect("piped input").write_all(input);
+    let output = child.wait_with_output().expect("finished command");
+    (output.status.code(), output.stdout)
+}
+
+/// Returns a command that runs the interpreter.
+pub fn interpreter() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_brain-rusted"))
+}
+
+/// Emits every given case for the given target into a scratch directory,
+/// builds it with the given function, which returns the command that runs
+/// it, and checks that it writes the same output and exits with the same
+/// code as the interpreter.
+pub fn cross_check<'a>(
+    target: Target,
+    extension: &str,
+    cases: impl IntoIterator<Item = &'a Case>,
+    build: impl Fn(&Path) -> Command,
+) {
+    let directory = scratch(target.name());
+    for (index, case) in cases.into_iter().enumerate() {
+        let source = directory.join(format!("case{}.bf", index));
+        std::fs::write(&source, case.source).expect("writable program");
+        let expected = run(interpreter().args(case.args).arg(&source), case.input);
+
+        let emitted = directory.join(format!("case{}.{}", index, extension));
+        let status = interpreter()
+            .args(case.args)
+            .arg(format!("--emit={}", target))
+            .arg("-o")
+            .arg(&emitted)
+            .arg(&source)
+            .status()
+            .expect("runnable interpreter");
+        assert!(status.success(), "{} could not be emitted", case.source);
+
+        let actual = run(&mut build(&emitted), case.input);
+        assert_eq!(
+            actual, expected,
+            "{} with {:?} differs from the interpreter",
+            case.source, case.args
+        );
+    }
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+/// Runs the given build command, checking that it succeeds.
+pub fn build(command: &mut Command) {
+    let output = command.output().expect("runnable build command");
+    assert!(
+        output.status.success(),
+        "build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
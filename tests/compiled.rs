@@ -0,0 +1,172 @@
+//! Tests for the compiled program file format. Programs are written with
+//! the bfc target and read back, whole or after being damaged.
+
+use brain_rusted::loading::compiled;
+use brain_rusted::{
+    CellType, EofPolicy, Instruction, InstructionKind, LoadError, MachineOptions, OverflowPolicy,
+    Span, TapePolicy,
+};
+
+/// Program that reads, writes, clears, multiplies and scans, so once
+/// optimized it has instructions with and without operands.
+const PROGRAM: &str =
+    "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.,[->+<]>[-]<<<[>]";
+
+/// Returns the program, optimized for the given options, and the file it
+/// is written to.
+fn compile(options: &MachineOptions) -> (brain_rusted::Program, Vec<u8>) {
+    let program = brain_rusted::compile(PROGRAM).expect("valid program");
+    let program = brain_rusted::optimize(&program, options);
+    let bytes = brain_rusted::emit(&program, brain_rusted::Target::Bfc, options)
+        .expect("every option is supported");
+    (program, bytes)
+}
+
+#[test]
+fn programs_and_options_survive_a_round_trip() {
+    let options = MachineOptions {
+        tape_length: 123,
+        tape_policy: TapePolicy::Grow,
+        max_tape_length: Some(4567),
+        cell: CellType::I16,
+        overflow: OverflowPolicy::Saturate,
+        eof: EofPolicy::MinusOne,
+        ..MachineOptions::default()
+    };
+    let (program, bytes) = compile(&options);
+    let loaded = compiled::decode("program.bfc", &bytes).expect("valid file");
+
+    assert_eq!(loaded.program, program);
+    assert_eq!(loaded.options.tape_length, 123);
+    assert_eq!(loaded.options.tape_policy, TapePolicy::Grow);
+    assert_eq!(loaded.options.max_tape_length, Some(4567));
+    assert_eq!(loaded.options.cell, CellType::I16);
+    assert_eq!(loaded.options.overflow, OverflowPolicy::Saturate);
+    assert_eq!(loaded.options.eof, EofPolicy::MinusOne);
+}
+
+#[test]
+fn loaded_programs_run_like_the_original() {
+    let options = MachineOptions::default();
+    let (program, bytes) = compile(&options);
+    let loaded = compiled::decode("program.bfc", &bytes).expect("valid file");
+
+    assert_eq!(
+        brain_rusted::run_with(&loaded.program, b"x", &loaded.options).expect("no error"),
+        brain_rusted::run_with(&program, b"x", &options).expect("no error"),
+    );
+}
+
+#[test]
+fn every_changed_byte_is_detected() {
+    let (_, bytes) = compile(&MachineOptions::default());
+    for index in compiled::MAGIC.len() + 2..bytes.len() {
+        let mut damaged = bytes.clone();
+        damaged[index] ^= 0x10;
+        assert!(
+            matches!(
+                compiled::decode("program.bfc", &damaged),
+                Err(LoadError::Corrupted { .. })
+            ),
+            "change at byte {} not detected",
+            index
+        );
+    }
+}
+
+#[test]
+fn truncated_files_are_refused() {
+    let (_, bytes) = compile(&MachineOptions::default());
+    for length in [8, compiled::HEADER_LENGTH - 1, bytes.len() - 1] {
+        assert!(matches!(
+            compiled::decode("program.bfc", &bytes[..length]),
+            Err(LoadError::Corrupted { .. })
+        ));
+    }
+}
+
+#[test]
+fn other_versions_and_files_are_refused() {
+    let (_, mut bytes) = compile(&MachineOptions::default());
+    assert!(matches!(
+        compiled::decode("program.bf", PROGRAM.as_bytes()),
+        Err(LoadError::NotCompiled { .. })
+    ));
+
+    bytes[4..6].copy_from_slice(&(compiled::VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        compiled::decode("program.bfc", &bytes),
+        Err(LoadError::UnsupportedVersion { version, .. }) if version == compiled::VERSION + 1
+    ));
+}
+
+/// Returns a file, with a valid checksum, holding the single instruction
+/// "move by the given offset", for the default options.
+fn moving_by(offset: isize) -> Vec<u8> {
+    let empty = brain_rusted::compile("").expect("valid program");
+    let options = MachineOptions::default();
+    let mut bytes = brain_rusted::emit(&empty, brain_rusted::Target::Bfc, &options)
+        .expect("every option is supported");
+    bytes[compiled::COUNT..compiled::COUNT + 8].copy_from_slice(&1u64.to_le_bytes());
+    let instruction = Instruction::new(InstructionKind::Move(offset), Span::new(0, 1, 1, 1));
+    compiled::write_instruction(&mut bytes, &instruction);
+    let checksum = compiled::checksum(&bytes);
+    bytes[compiled::CHECKSUM..compiled::CHECKSUM + 4].copy_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn wrong_checksums_are_refused() {
+    let (_, mut bytes) = compile(&MachineOptions::default());
+    let stored = compiled::CHECKSUM..compiled::CHECKSUM + 4;
+    assert_eq!(bytes[stored], compiled::checksum(&bytes).to_le_bytes());
+
+    bytes[compiled::CHECKSUM] ^= 1;
+    assert!(matches!(
+        compiled::decode("program.bfc", &bytes),
+        Err(LoadError::Corrupted { .. })
+    ));
+}
+
+#[test]
+fn oversized_tapes_are_refused() {
+    let longest = MachineOptions {
+        tape_length: compiled::MAX_TAPE_LENGTH,
+        ..MachineOptions::default()
+    };
+    let (_, bytes) = compile(&longest);
+    assert!(compiled::decode("program.bfc", &bytes).is_ok());
+
+    let too_long = MachineOptions {
+        tape_length: compiled::MAX_TAPE_LENGTH + 1,
+        ..MachineOptions::default()
+    };
+    let too_large = MachineOptions {
+        tape_policy: TapePolicy::Grow,
+        max_tape_length: Some(usize::MAX),
+        ..MachineOptions::default()
+    };
+    for options in [too_long, too_large] {
+        let (_, bytes) = compile(&options);
+        assert!(matches!(
+            compiled::decode("program.bfc", &bytes),
+            Err(LoadError::TapeTooLong { length, .. })
+                if length == options.tape_length.max(options.max_tape_length.unwrap_or(0)) as u64
+        ));
+    }
+}
+
+#[test]
+fn offsets_beyond_the_longest_tape_are_refused() {
+    let farthest = compiled::MAX_TAPE_LENGTH as isize;
+    for offset in [farthest, -farthest] {
+        let loaded = compiled::decode("program.bfc", &moving_by(offset)).expect("valid file");
+        assert!(brain_rusted::run_with(&loaded.program, b"", &loaded.options).is_ok());
+    }
+    for offset in [farthest + 1, -farthest - 1, isize::MAX, isize::MIN] {
+        assert!(matches!(
+            compiled::decode("program.bfc", &moving_by(offset)),
+            Err(LoadError::Corrupted { .. })
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! Regression tests for programs whose loops are nested far deeper than the
+//! native stack could handle with recursion.
+
+/// Nesting depth used by the tests. Any recursive step (parsing, executing,
+/// cloning or dropping the program) would overflow the stack of a test
+/// thread long before this depth.
+const DEPTH: usize = 1_000_000;
+
+/// Returns a program that enters DEPTH nested loops, clears the cell in the
+/// innermost one, leaves them all and prints a character.
+fn deeply_nested_program() -> String {
+    let mut source = String::with_capacity(2 * DEPTH + 80);
+    source.push('+');
+    source.push_str(&"[".repeat(DEPTH));
+    source.push('-');
+    source.push_str(&"]".repeat(DEPTH));
+    source.push_str(&"+".repeat(65));
+    source.push('.');
+    source
+}
+
+#[test]
+fn deeply_nested_loops_compile_and_run() {
+    let program = brain_rusted::compile(&deeply_nested_program()).expect("balanced program");
+    let output = brain_rusted::run(&program, b"").expect("no runtime error");
+
+    assert_eq!(output, b"A");
+}
+
+#[test]
+fn deeply_nested_programs_can_be_cloned_and_dropped() {
+    let program = brain_rusted::compile(&deeply_nested_program()).expect("balanced program");
+    let copy = program.clone();
+
+    assert_eq!(copy, program);
+}
+
+#[test]
+fn deeply_unclosed_loops_are_reported() {
+    let source = "[".repeat(DEPTH);
+    let error = brain_rusted::compile(&source).expect_err("unclosed loops");
+
+    match error {
+        brain_rusted::BrainError::Parse(brain_rusted::ParseError::Unclosed(snippet)) => {
+            assert_eq!(snippet.span().column, DEPTH);
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
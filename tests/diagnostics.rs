@@ -0,0 +1,50 @@
+//! Tests for the rendering of diagnostics, which show the source line an
+//! error points at, cut around the error when the line is long.
+
+use brain_rusted::diagnostics::Snippet;
+use brain_rusted::{BrainError, CompileOptions, Span};
+
+#[test]
+fn short_lines_are_shown_whole_with_a_caret_under_the_error() {
+    let source = "++\n+a[-]\n";
+    let snippet = Snippet::new(source, &Span::new(4, 1, 2, 2));
+
+    assert_eq!(snippet.line(), "+a[-]");
+    let error = brain_rusted::compile_with(source, &CompileOptions { strict: true })
+        .expect_err("invalid token");
+    assert_eq!(
+        error.to_string(),
+        "error: invalid token found: a\n --> 2:2\n  |\n2 | +a[-]\n  |  ^"
+    );
+}
+
+#[test]
+fn long_lines_are_cut_around_the_error() {
+    let source = format!("{}[{}", "+".repeat(1000), "x".repeat(1000));
+    let error = brain_rusted::compile(&source).expect_err("unclosed loop");
+    let BrainError::Parse(error) = error else {
+        panic!("expected a parse error");
+    };
+    let rendered = error.to_string();
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert_eq!(lines[1], " --> 1:1001");
+    assert!(lines[3].starts_with("1 | ...+"));
+    assert!(lines[3].ends_with("x..."));
+    assert!(lines[3].len() < 100);
+    let caret = lines[4].find('^').expect("caret");
+    assert_eq!(&lines[3][caret..caret + 1], "[");
+}
+
+#[test]
+fn large_invalid_inputs_are_reported_quickly() {
+    // 100 KB of prose on a single line, which used to copy the whole line
+    // into every one of its tens of thousands of diagnostics.
+    let source = "The quick brown fox jumps over the lazy dog. ".repeat(2300);
+    let error = brain_rusted::compile_with(&source, &CompileOptions { strict: true })
+        .expect_err("invalid tokens");
+
+    let rendered = error.to_string();
+    assert!(rendered.starts_with("error: invalid token found: T\n --> 1:1\n"));
+    assert!(rendered.lines().all(|line| line.len() < 100));
+}
@@ -0,0 +1,107 @@
+//! Tests for the ELF backend. The emitted executables are read back just
+//! enough to check their headers and, on x86-64 Linux, run and
+//! cross-checked against the interpreter.
+
+mod common;
+
+use brain_rusted::{CellType, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use std::process::Command;
+
+/// Returns the little-endian number of the given size at the given offset
+/// of the given bytes.
+fn read(bytes: &[u8], offset: usize, size: usize) -> u64 {
+    bytes[offset..offset + size]
+        .iter()
+        .rev()
+        .fold(0, |value, &byte| value << 8 | byte as u64)
+}
+
+#[test]
+fn executables_have_valid_headers() {
+    let elf = common::emit(common::HELLO, Target::Elf, &MachineOptions::default())
+        .expect("supported options");
+
+    assert_eq!(&elf[..8], b"\x7FELF\x02\x01\x01\x00");
+    assert_eq!(read(&elf, 16, 2), 2, "an executable file");
+    assert_eq!(read(&elf, 18, 2), 0x3E, "for x86-64");
+    let entry = read(&elf, 24, 8);
+    let headers = read(&elf, 32, 8) as usize;
+    let header_size = read(&elf, 54, 2) as usize;
+    let header_count = read(&elf, 56, 2) as usize;
+    assert_eq!(header_size, 56);
+    assert!(headers + header_size * header_count <= elf.len());
+
+    // The first segment maps the whole file, entry point included, and is
+    // the only executable one.
+    let segments: Vec<_> = (0..header_count)
+        .map(|index| &elf[headers + index * header_size..][..header_size])
+        .collect();
+    let (kind, flags, address, file_size) = (
+        read(segments[0], 0, 4),
+        read(segments[0], 4, 4),
+        read(segments[0], 16, 8),
+        read(segments[0], 32, 8),
+    );
+    assert_eq!((kind, flags), (1, 0b101));
+    assert_eq!(file_size as usize, elf.len());
+    assert!(address < entry && entry < address + file_size);
+    for segment in &segments[1..] {
+        assert_eq!(read(segment, 4, 4) & 1, 0, "only the code is executable");
+    }
+}
+
+#[test]
+fn the_tape_is_sized_by_the_options() {
+    let memory = |cell, tape_length| {
+        let options = MachineOptions {
+            cell,
+            tape_length,
+            ..MachineOptions::default()
+        };
+        let elf = common::emit("+.", Target::Elf, &options).expect("supported options");
+        // The size in memory of the writable segment.
+        read(&elf, 64 + 56 + 40, 8)
+    };
+
+    assert_eq!(
+        memory(CellType::U32, 1000) - memory(CellType::U8, 1000),
+        3000
+    );
+    assert_eq!(
+        memory(CellType::U8, 2000) - memory(CellType::U8, 1000),
+        1000
+    );
+}
+
+#[test]
+fn unsupported_options_are_rejected() {
+    let grow = MachineOptions {
+        tape_policy: TapePolicy::Grow,
+        ..MachineOptions::default()
+    };
+    common::check_unsupported(Target::Elf, &grow, "growing tapes");
+
+    let saturate = MachineOptions {
+        overflow: OverflowPolicy::Saturate,
+        ..MachineOptions::default()
+    };
+    common::check_unsupported(Target::Elf, &saturate, "overflow policies other than wrap");
+
+    let large = MachineOptions {
+        cell: CellType::U64,
+        tape_length: 1 << 30,
+        ..MachineOptions::default()
+    };
+    common::check_unsupported(Target::Elf, &large, "tapes larger than 4 GiB");
+}
+
+#[test]
+#[cfg_attr(
+    not(all(target_os = "linux", target_arch = "x86_64")),
+    ignore = "executables only run on x86-64 Linux"
+)]
+fn executables_behave_like_the_interpreter() {
+    common::cross_check(Target::Elf, "exe", &common::CASES, |executable| {
+        Command::new(executable)
+    });
+}
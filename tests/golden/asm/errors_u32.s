@@ -0,0 +1,158 @@
+# Generated by brain-rusted: 16 cells of type U32, Error at the tape ends,
+# Error at the end of the input. Every instruction of the program is
+# preceded by a comment naming it and where it came from.
+# Build with: as -o program.o program.s && ld -o program program.o
+	.intel_syntax noprefix
+	.section .note.GNU-stack, "", @progbits
+	.text
+	.globl _start
+_start:
+	mov rbx, OFFSET tape
+	mov r12d, 0
+	mov r14d, 16
+	mov r15, OFFSET buffer
+	# Read, at line 1, column 1 (bytes 0..1)
+	lea rsi, [rbx + r12*4]
+	call .L0
+	test rax, rax
+	jnz .L1
+	# MulAdd { offset: 1, factor: 1 }, at line 1, column 2 (bytes 1..7)
+	mov eax, DWORD PTR [rbx + r12*4]
+	test rax, rax
+	jz .L2
+	mov rdx, r12
+	add rdx, 1
+	cmp rdx, r14
+	jae .L3
+	imul rax, rax, 1
+	add DWORD PTR [rbx + rdx*4], eax
+.L2:
+	# SetZero, at line 1, column 2 (bytes 1..7)
+	mov DWORD PTR [rbx + r12*4], 0
+	# Move(1), at line 1, column 8 (bytes 7..8)
+	mov rdx, r12
+	add rdx, 1
+	cmp rdx, r14
+	jae .L4
+	mov r12, rdx
+	# ScanRight, at line 1, column 9 (bytes 8..11)
+.L5:
+	cmp DWORD PTR [rbx + r12*4], 0
+	jz .L6
+	mov rdx, r12
+	add rdx, 1
+	cmp rdx, r14
+	jae .L7
+	mov r12, rdx
+	jmp .L5
+.L6:
+	# Move(-1), at line 1, column 12 (bytes 11..12)
+	mov rdx, r12
+	add rdx, -1
+	cmp rdx, r14
+	jae .L8
+	mov r12, rdx
+	# Show, at line 1, column 13 (bytes 12..13)
+	movzx esi, BYTE PTR [rbx + r12*4]
+	call .L9
+	call .L10
+	mov eax, 60
+	mov edi, 0
+	syscall
+.L1:
+	call .L10
+	lea rsi, [rip + .L12]
+	mov edx, 44
+	jmp .L11
+.L3:
+	call .L10
+	lea rsi, [rip + .L13]
+	mov edx, 50
+	jmp .L11
+.L4:
+	call .L10
+	lea rsi, [rip + .L14]
+	mov edx, 50
+	jmp .L11
+.L7:
+	call .L10
+	lea rsi, [rip + .L15]
+	mov edx, 50
+	jmp .L11
+.L8:
+	call .L10
+	lea rsi, [rip + .L16]
+	mov edx, 51
+	jmp .L11
+.L9:
+	mov BYTE PTR [r15], sil
+	add r15, 1
+	mov rax, OFFSET buffer + 4096
+	cmp r15, rax
+	jae .L10
+	ret
+.L0:
+	mov r8, rsi
+	call .L10
+	mov eax, 0
+	mov edi, 0
+	mov rsi, OFFSET input
+	mov edx, 1
+	syscall
+	test rax, rax
+	jl .L17
+	jz .L18
+	movzx eax, BYTE PTR [rsi]
+	mov DWORD PTR [r8], eax
+	mov eax, 0
+	ret
+.L18:
+	mov eax, 1
+	ret
+.L10:
+	mov rsi, OFFSET buffer
+.L19:
+	mov rdx, r15
+	sub rdx, rsi
+	jz .L20
+	mov eax, 1
+	mov edi, 1
+	syscall
+	test rax, rax
+	jle .L17
+	add rsi, rax
+	jmp .L19
+.L20:
+	mov r15, OFFSET buffer
+	ret
+.L17:
+	lea rsi, [rip + .L21]
+	mov edx, 37
+	jmp .L11
+.L11:
+	mov eax, 1
+	mov edi, 2
+	syscall
+	mov eax, 60
+	mov edi, 70
+	syscall
+.L12:
+	.ascii "Unexpected end of input at line 1, column 1\012"
+.L13:
+	.ascii "Pointer moved out of the tape at line 1, column 2\012"
+.L14:
+	.ascii "Pointer moved out of the tape at line 1, column 8\012"
+.L15:
+	.ascii "Pointer moved out of the tape at line 1, column 9\012"
+.L16:
+	.ascii "Pointer moved out of the tape at line 1, column 12\012"
+.L21:
+	.ascii "Failed to read input or write output\012"
+	.bss
+	.balign 64
+buffer:
+	.zero 4096
+input:
+	.zero 64
+tape:
+	.zero 64
@@ -0,0 +1,370 @@
+# Generated by brain-rusted: 30000 cells of type U8, Wrap at the tape ends,
+# Zero at the end of the input. Every instruction of the program is
+# preceded by a comment naming it and where it came from.
+# Build with: as -o program.o program.s && ld -o program program.o
+	.intel_syntax noprefix
+	.section .note.GNU-stack, "", @progbits
+	.text
+	.globl _start
+_start:
+	mov rbx, OFFSET tape
+	mov r12d, 0
+	mov r14d, 30000
+	mov r15, OFFSET buffer
+	# Add(8), at line 1, column 1 (bytes 0..8)
+	add BYTE PTR [rbx + r12*1], 8
+	# StartLoop, at line 1, column 9 (bytes 8..9)
+	cmp BYTE PTR [rbx + r12*1], 0
+	jz .L1
+.L0:
+	# AddAt { offset: 1, amount: 4 }, at line 1, column 11 (bytes 10..14)
+	mov rdx, r12
+	add rdx, 1
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 4
+	# Move(1), at line 1, column 10 (bytes 9..10)
+	mov rdx, r12
+	add rdx, 1
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	mov r12, rdx
+	# MulAdd { offset: 1, factor: 2 }, at line 1, column 15 (bytes 14..34)
+	movzx eax, BYTE PTR [rbx + r12*1]
+	test rax, rax
+	jz .L2
+	mov rdx, r12
+	add rdx, 1
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	imul rax, rax, 2
+	add BYTE PTR [rbx + rdx*1], al
+.L2:
+	# MulAdd { offset: 2, factor: 3 }, at line 1, column 15 (bytes 14..34)
+	movzx eax, BYTE PTR [rbx + r12*1]
+	test rax, rax
+	jz .L3
+	mov rdx, r12
+	add rdx, 2
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	imul rax, rax, 3
+	add BYTE PTR [rbx + rdx*1], al
+.L3:
+	# MulAdd { offset: 3, factor: 3 }, at line 1, column 15 (bytes 14..34)
+	movzx eax, BYTE PTR [rbx + r12*1]
+	test rax, rax
+	jz .L4
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	imul rax, rax, 3
+	add BYTE PTR [rbx + rdx*1], al
+.L4:
+	# MulAdd { offset: 4, factor: 1 }, at line 1, column 15 (bytes 14..34)
+	movzx eax, BYTE PTR [rbx + r12*1]
+	test rax, rax
+	jz .L5
+	mov rdx, r12
+	add rdx, 4
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	imul rax, rax, 1
+	add BYTE PTR [rbx + rdx*1], al
+.L5:
+	# SetZero, at line 1, column 15 (bytes 14..34)
+	mov BYTE PTR [rbx + r12*1], 0
+	# AddAt { offset: 1, amount: 1 }, at line 1, column 36 (bytes 35..36)
+	mov rdx, r12
+	add rdx, 1
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 1
+	# AddAt { offset: 2, amount: 1 }, at line 1, column 38 (bytes 37..38)
+	mov rdx, r12
+	add rdx, 2
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 1
+	# AddAt { offset: 3, amount: -1 }, at line 1, column 40 (bytes 39..40)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], -1
+	# AddAt { offset: 5, amount: 1 }, at line 1, column 43 (bytes 42..43)
+	mov rdx, r12
+	add rdx, 5
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 1
+	# Move(5), at line 1, column 35 (bytes 34..42)
+	mov rdx, r12
+	add rdx, 5
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	mov r12, rdx
+	# ScanLeft, at line 1, column 44 (bytes 43..46)
+.L6:
+	cmp BYTE PTR [rbx + r12*1], 0
+	jz .L7
+	mov rdx, r12
+	add rdx, 29999
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	mov r12, rdx
+	jmp .L6
+.L7:
+	# AddAt { offset: -1, amount: -1 }, at line 1, column 48 (bytes 47..48)
+	mov rdx, r12
+	add rdx, 29999
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], -1
+	# Move(-1), at line 1, column 47 (bytes 46..47)
+	mov rdx, r12
+	add rdx, 29999
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	mov r12, rdx
+	# StopLoop, at line 1, column 49 (bytes 48..49)
+	cmp BYTE PTR [rbx + r12*1], 0
+	jnz .L0
+.L1:
+	# OutputAt { offset: 2 }, at line 1, column 52 (bytes 51..52)
+	mov rdx, r12
+	add rdx, 2
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 3, amount: -3 }, at line 1, column 54 (bytes 53..56)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], -3
+	# OutputAt { offset: 3 }, at line 1, column 57 (bytes 56..57)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 3, amount: 7 }, at line 1, column 58 (bytes 57..64)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 7
+	# OutputAt { offset: 3 }, at line 1, column 65 (bytes 64..65)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# OutputAt { offset: 3 }, at line 1, column 66 (bytes 65..66)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 3, amount: 3 }, at line 1, column 67 (bytes 66..69)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 3
+	# OutputAt { offset: 3 }, at line 1, column 70 (bytes 69..70)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# OutputAt { offset: 5 }, at line 1, column 73 (bytes 72..73)
+	mov rdx, r12
+	add rdx, 5
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 4, amount: -1 }, at line 1, column 75 (bytes 74..75)
+	mov rdx, r12
+	add rdx, 4
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], -1
+	# OutputAt { offset: 4 }, at line 1, column 76 (bytes 75..76)
+	mov rdx, r12
+	add rdx, 4
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# OutputAt { offset: 3 }, at line 1, column 78 (bytes 77..78)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 3, amount: 3 }, at line 1, column 79 (bytes 78..81)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 3
+	# OutputAt { offset: 3 }, at line 1, column 82 (bytes 81..82)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 3, amount: -6 }, at line 1, column 83 (bytes 82..88)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], -6
+	# OutputAt { offset: 3 }, at line 1, column 89 (bytes 88..89)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 3, amount: -8 }, at line 1, column 90 (bytes 89..97)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], -8
+	# OutputAt { offset: 3 }, at line 1, column 98 (bytes 97..98)
+	mov rdx, r12
+	add rdx, 3
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 5, amount: 1 }, at line 1, column 101 (bytes 100..101)
+	mov rdx, r12
+	add rdx, 5
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 1
+	# OutputAt { offset: 5 }, at line 1, column 102 (bytes 101..102)
+	mov rdx, r12
+	add rdx, 5
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# AddAt { offset: 6, amount: 2 }, at line 1, column 104 (bytes 103..105)
+	mov rdx, r12
+	add rdx, 6
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	add BYTE PTR [rbx + rdx*1], 2
+	# OutputAt { offset: 6 }, at line 1, column 106 (bytes 105..106)
+	mov rdx, r12
+	add rdx, 6
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	movzx esi, BYTE PTR [rbx + rdx*1]
+	call .L8
+	# Move(6), at line 1, column 50 (bytes 49..103)
+	mov rdx, r12
+	add rdx, 6
+	mov rcx, rdx
+	sub rcx, r14
+	cmovae rdx, rcx
+	mov r12, rdx
+	call .L9
+	mov eax, 60
+	mov edi, 0
+	syscall
+.L8:
+	mov BYTE PTR [r15], sil
+	add r15, 1
+	mov rax, OFFSET buffer + 4096
+	cmp r15, rax
+	jae .L9
+	ret
+.L9:
+	mov rsi, OFFSET buffer
+.L11:
+	mov rdx, r15
+	sub rdx, rsi
+	jz .L12
+	mov eax, 1
+	mov edi, 1
+	syscall
+	test rax, rax
+	jle .L10
+	add rsi, rax
+	jmp .L11
+.L12:
+	mov r15, OFFSET buffer
+	ret
+.L10:
+	lea rsi, [rip + .L14]
+	mov edx, 37
+	jmp .L13
+.L13:
+	mov eax, 1
+	mov edi, 2
+	syscall
+	mov eax, 60
+	mov edi, 70
+	syscall
+.L14:
+	.ascii "Failed to read input or write output\012"
+	.bss
+	.balign 64
+buffer:
+	.zero 4096
+input:
+	.zero 64
+tape:
+	.zero 30000
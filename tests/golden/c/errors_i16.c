@@ -0,0 +1,61 @@
+/* Generated by brain-rusted: 16 cells of type I16, Error at the tape ends,
+   Error at the end of the input. */
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#define TAPE_LENGTH 16LL
+
+typedef uint16_t cell;
+
+static cell tape[TAPE_LENGTH];
+static long long pointer = 0;
+
+static long long at(long long offset) {
+    long long index = pointer + offset;
+    if (index < 0 || index >= TAPE_LENGTH) {
+        fflush(stdout);
+        fprintf(stderr, "Pointer moved out of the tape, to cell %lld\n",
+                index < 0 ? -1 : TAPE_LENGTH);
+        exit(70);
+    }
+    return index;
+}
+
+static void move(long long offset) {
+    pointer = at(offset);
+}
+
+static void mul_add(long long offset, long long factor) {
+    if (tape[pointer]) {
+        long long index = at(offset);
+        tape[index] = (cell)((uint64_t)tape[index] + (uint64_t)tape[pointer] * (uint64_t)factor);
+    }
+}
+
+static void output(long long offset) {
+    putchar((unsigned char)tape[at(offset)]);
+}
+
+static void input(void) {
+    int byte;
+    fflush(stdout);
+    byte = getchar();
+    if (byte != EOF) {
+        tape[pointer] = (cell)byte;
+        return;
+    }
+    fprintf(stderr, "Unexpected end of input\n");
+    exit(70);
+}
+
+int main(void) {
+    input();
+    mul_add(1, 1);
+    tape[pointer] = 0;
+    move(1);
+    while (tape[pointer]) move(1);
+    move(-1);
+    output(0);
+    return 0;
+}
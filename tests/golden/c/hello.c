@@ -0,0 +1,82 @@
+/* Generated by brain-rusted: 30000 cells of type U8, Wrap at the tape ends,
+   Zero at the end of the input. */
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#define TAPE_LENGTH 30000LL
+
+typedef uint8_t cell;
+
+static cell tape[TAPE_LENGTH];
+static long long pointer = 0;
+
+static long long at(long long offset) {
+    long long index = (pointer + offset) % TAPE_LENGTH;
+    return index < 0 ? index + TAPE_LENGTH : index;
+}
+
+static void move(long long offset) {
+    pointer = at(offset);
+}
+
+static void add(long long offset, long long amount) {
+    long long index = at(offset);
+    tape[index] = (cell)((uint64_t)tape[index] + (uint64_t)amount);
+}
+
+static void mul_add(long long offset, long long factor) {
+    if (tape[pointer]) {
+        long long index = at(offset);
+        tape[index] = (cell)((uint64_t)tape[index] + (uint64_t)tape[pointer] * (uint64_t)factor);
+    }
+}
+
+static void output(long long offset) {
+    putchar((unsigned char)tape[at(offset)]);
+}
+
+int main(void) {
+    add(0, 8);
+    while (tape[pointer]) {
+        add(1, 4);
+        move(1);
+        mul_add(1, 2);
+        mul_add(2, 3);
+        mul_add(3, 3);
+        mul_add(4, 1);
+        tape[pointer] = 0;
+        add(1, 1);
+        add(2, 1);
+        add(3, -1);
+        add(5, 1);
+        move(5);
+        while (tape[pointer]) move(-1);
+        add(-1, -1);
+        move(-1);
+    }
+    output(2);
+    add(3, -3);
+    output(3);
+    add(3, 7);
+    output(3);
+    output(3);
+    add(3, 3);
+    output(3);
+    output(5);
+    add(4, -1);
+    output(4);
+    output(3);
+    add(3, 3);
+    output(3);
+    add(3, -6);
+    output(3);
+    add(3, -8);
+    output(3);
+    add(5, 1);
+    output(5);
+    add(6, 2);
+    output(6);
+    move(6);
+    return 0;
+}
@@ -0,0 +1,192 @@
+// Generated by brain-rusted: 30000 cells of type U8, Wrap at the tape ends,
+// Wrap on overflow, Zero at the end of the input.
+"use strict";
+
+const TAPE_LENGTH = 30000;
+const MAX_TAPE_LENGTH = null;
+const Tape = Uint8Array;
+const BITS = 8;
+const SIGNED = false;
+
+const MIN = SIGNED ? -(2 ** (BITS - 1)) : 0;
+const MAX = SIGNED ? 2 ** (BITS - 1) - 1 : 2 ** BITS - 1;
+
+/** Error thrown when the program fails, with the exit status it ends with. */
+class BrainError extends Error {
+  constructor(message, status) {
+    super(message);
+    this.name = "BrainError";
+    this.status = status;
+  }
+}
+
+class Machine {
+  constructor(read, write) {
+    this.tape = new Tape(Math.max(TAPE_LENGTH, 1));
+    this.pointer = 0;
+    this.origin = 0;
+    this.read = read;
+    this.write = write;
+  }
+
+  fail(message, status) {
+    throw new BrainError(message, status);
+  }
+
+  isNonzero() {
+    return this.tape[this.pointer] != 0;
+  }
+
+  advance(offset) {
+    this.pointer = this.at(offset);
+  }
+
+  set(offset, value) {
+    this.store(this.at(offset), value);
+  }
+
+  print(byte) {
+    this.write(byte);
+  }
+
+  add(offset, amount) {
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], amount, 1));
+  }
+
+  mulAdd(offset, factor) {
+    const value = this.tape[this.pointer];
+    if (value === 0) {
+      return;
+    }
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], value, factor));
+  }
+
+  store(index, value) {
+    if (value === null) {
+      this.fail("Cell overflow", 70);
+    }
+    this.tape[index] = value;
+  }
+
+  output(offset) {
+    this.print(this.tape[this.at(offset)] & 255);
+  }
+
+  input() {
+    const byte = this.read();
+    if (byte === null || byte === undefined) {
+      this.endOfInput();
+    } else {
+      this.store(this.pointer, byte);
+    }
+  }
+
+  at(offset) {
+    const length = this.tape.length;
+    return (((this.pointer + offset) % length) + length) % length;
+  }
+
+  endOfInput() {
+    this.store(this.pointer, 0);
+  }
+}
+
+function add(cell, value, factor) {
+  return (cell + Math.imul(value, factor)) | 0;
+}
+
+/**
+ * Runs the program. It calls read() whenever it needs a byte of input,
+ * which must return a number from 0 to 255, or null at the end of the
+ * input, and write(byte) for every byte of output. Throws a BrainError
+ * when the program fails.
+ */
+function run(read, write) {
+  const machine = new Machine(read, write);
+  machine.add(0, 8);
+  while (machine.isNonzero()) {
+    machine.add(1, 4);
+    machine.advance(1);
+    machine.mulAdd(1, 2);
+    machine.mulAdd(2, 3);
+    machine.mulAdd(3, 3);
+    machine.mulAdd(4, 1);
+    machine.set(0, 0);
+    machine.add(1, 1);
+    machine.add(2, 1);
+    machine.add(3, -1);
+    machine.add(5, 1);
+    machine.advance(5);
+    while (machine.isNonzero()) machine.advance(-1);
+    machine.add(-1, -1);
+    machine.advance(-1);
+  }
+  machine.output(2);
+  machine.add(3, -3);
+  machine.output(3);
+  machine.add(3, 7);
+  machine.output(3);
+  machine.output(3);
+  machine.add(3, 3);
+  machine.output(3);
+  machine.output(5);
+  machine.add(4, -1);
+  machine.output(4);
+  machine.output(3);
+  machine.add(3, 3);
+  machine.output(3);
+  machine.add(3, -6);
+  machine.output(3);
+  machine.add(3, -8);
+  machine.output(3);
+  machine.add(5, 1);
+  machine.output(5);
+  machine.add(6, 2);
+  machine.output(6);
+  machine.advance(6);
+}
+
+if (typeof module !== "undefined") {
+  module.exports = { run, BrainError };
+}
+
+if (typeof require !== "undefined" && require.main === module) {
+  const fs = require("fs");
+  let output = [];
+  const flush = () => {
+    const bytes = Uint8Array.from(output);
+    let written = 0;
+    while (written < bytes.length) {
+      written += fs.writeSync(1, bytes, written);
+    }
+    output = [];
+  };
+  const read = () => {
+    flush();
+    const byte = new Uint8Array(1);
+    return fs.readSync(0, byte, 0, 1, null) === 1 ? byte[0] : null;
+  };
+  const write = (byte) => {
+    output.push(byte);
+    if (output.length >= 4096) {
+      flush();
+    }
+  };
+  try {
+    run(read, write);
+    flush();
+  } catch (error) {
+    if (error instanceof BrainError) {
+      try {
+        flush();
+      } catch (_) {}
+      console.error(error.message);
+      process.exitCode = error.status;
+    } else {
+      console.error("Failed to read input or write output");
+      process.exitCode = 70;
+    }
+  }
+}
@@ -0,0 +1,187 @@
+// Generated by brain-rusted: 8 cells of type I64, Grow at the tape ends,
+// Saturate on overflow, MinusOne at the end of the input.
+"use strict";
+
+const TAPE_LENGTH = 8;
+const MAX_TAPE_LENGTH = null;
+const Tape = BigInt64Array;
+const BITS = 64n;
+const SIGNED = true;
+
+const MIN = SIGNED ? -(1n << (BITS - 1n)) : 0n;
+const MAX = SIGNED ? (1n << (BITS - 1n)) - 1n : (1n << BITS) - 1n;
+
+/** Error thrown when the program fails, with the exit status it ends with. */
+class BrainError extends Error {
+  constructor(message, status) {
+    super(message);
+    this.name = "BrainError";
+    this.status = status;
+  }
+}
+
+class Machine {
+  constructor(read, write) {
+    this.tape = new Tape(Math.max(TAPE_LENGTH, 1));
+    this.pointer = 0;
+    this.origin = 0;
+    this.read = read;
+    this.write = write;
+  }
+
+  fail(message, status) {
+    throw new BrainError(message, status);
+  }
+
+  isNonzero() {
+    return this.tape[this.pointer] != 0;
+  }
+
+  advance(offset) {
+    this.pointer = this.at(offset);
+  }
+
+  set(offset, value) {
+    this.store(this.at(offset), value);
+  }
+
+  print(byte) {
+    this.write(byte);
+  }
+
+  add(offset, amount) {
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], amount, 1n));
+  }
+
+  mulAdd(offset, factor) {
+    const value = this.tape[this.pointer];
+    if (value === 0n) {
+      return;
+    }
+    const index = this.at(offset);
+    this.store(index, add(this.tape[index], value, factor));
+  }
+
+  store(index, value) {
+    if (value === null) {
+      this.fail("Cell overflow", 70);
+    }
+    this.tape[index] = value;
+  }
+
+  output(offset) {
+    this.print(Number(this.tape[this.at(offset)] & 255n));
+  }
+
+  input() {
+    const byte = this.read();
+    if (byte === null || byte === undefined) {
+      this.endOfInput();
+    } else {
+      this.store(this.pointer, BigInt(byte));
+    }
+  }
+
+  at(offset) {
+    const target = this.pointer + offset;
+    if (target < 0) {
+      const extra = this.growth(-target);
+      const tape = new Tape(extra + this.tape.length);
+      tape.set(this.tape, extra);
+      this.tape = tape;
+      this.pointer += extra;
+      this.origin += extra;
+      return this.pointer + offset;
+    }
+    if (target >= this.tape.length) {
+      const extra = this.growth(target + 1 - this.tape.length);
+      const tape = new Tape(this.tape.length + extra);
+      tape.set(this.tape);
+      this.tape = tape;
+    }
+    return target;
+  }
+
+  growth(needed) {
+    const length = this.tape.length;
+    if (MAX_TAPE_LENGTH === null) {
+      return Math.max(needed, length);
+    }
+    if (length + needed > MAX_TAPE_LENGTH) {
+      this.fail(`Tape limit exceeded: ${MAX_TAPE_LENGTH} cells`, 75);
+    }
+    return Math.min(Math.max(needed, length), MAX_TAPE_LENGTH - length);
+  }
+
+  endOfInput() {
+    this.store(this.pointer, -1n);
+  }
+}
+
+function add(cell, value, factor) {
+  const sum = cell + value * factor;
+  return sum < MIN ? MIN : sum > MAX ? MAX : sum;
+}
+
+/**
+ * Runs the program. It calls read() whenever it needs a byte of input,
+ * which must return a number from 0 to 255, or null at the end of the
+ * input, and write(byte) for every byte of output. Throws a BrainError
+ * when the program fails.
+ */
+function run(read, write) {
+  const machine = new Machine(read, write);
+  machine.input();
+  while (machine.isNonzero()) {
+    machine.add(0, -1n);
+    machine.add(1, 1n);
+  }
+  machine.advance(1);
+  while (machine.isNonzero()) machine.advance(1);
+  machine.output(-1);
+  machine.advance(-1);
+}
+
+if (typeof module !== "undefined") {
+  module.exports = { run, BrainError };
+}
+
+if (typeof require !== "undefined" && require.main === module) {
+  const fs = require("fs");
+  let output = [];
+  const flush = () => {
+    const bytes = Uint8Array.from(output);
+    let written = 0;
+    while (written < bytes.length) {
+      written += fs.writeSync(1, bytes, written);
+    }
+    output = [];
+  };
+  const read = () => {
+    flush();
+    const byte = new Uint8Array(1);
+    return fs.readSync(0, byte, 0, 1, null) === 1 ? byte[0] : null;
+  };
+  const write = (byte) => {
+    output.push(byte);
+    if (output.length >= 4096) {
+      flush();
+    }
+  };
+  try {
+    run(read, write);
+    flush();
+  } catch (error) {
+    if (error instanceof BrainError) {
+      try {
+        flush();
+      } catch (_) {}
+      console.error(error.message);
+      process.exitCode = error.status;
+    } else {
+      console.error("Failed to read input or write output");
+      process.exitCode = 70;
+    }
+  }
+}
@@ -0,0 +1,86 @@
+; Generated by brain-rusted: 64 cells of type U16, Wrap at the tape ends,
+; MinusOne at the end of the input.
+
+@tape = internal global [64 x i16] zeroinitializer
+@pointer = internal global i64 0
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+declare i32 @fflush(ptr)
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32) noreturn
+
+define internal i64 @at(i64 %offset) {
+  %pointer = load i64, ptr @pointer
+  %sum = add i64 %pointer, %offset
+  %remainder = srem i64 %sum, 64
+  %negative = icmp slt i64 %remainder, 0
+  %wrapped = add i64 %remainder, 64
+  %index = select i1 %negative, i64 %wrapped, i64 %remainder
+  ret i64 %index
+}
+
+define internal ptr @cell(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  %cell = getelementptr inbounds [64 x i16], ptr @tape, i64 0, i64 %index
+  ret ptr %cell
+}
+
+define internal i1 @nonzero() {
+  %pointer = load i64, ptr @pointer
+  %cell = getelementptr inbounds [64 x i16], ptr @tape, i64 0, i64 %pointer
+  %value = load i16, ptr %cell
+  %nonzero = icmp ne i16 %value, 0
+  ret i1 %nonzero
+}
+
+define internal void @add(i64 %offset, i16 %amount) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load i16, ptr %cell
+  %sum = add i16 %value, %amount
+  store i16 %sum, ptr %cell
+  ret void
+}
+
+define internal void @output(i64 %offset) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load i16, ptr %cell
+  %wide = zext i16 %value to i32
+  %byte = and i32 %wide, 255
+  %written = call i32 @putchar(i32 %byte)
+  ret void
+}
+
+define internal void @input() {
+entry:
+  %flushed = call i32 @fflush(ptr null)
+  %byte = call i32 @getchar()
+  %cell = call ptr @cell(i64 0)
+  %end = icmp slt i32 %byte, 0
+  br i1 %end, label %eof, label %read
+read:
+  %value = trunc i32 %byte to i16
+  store i16 %value, ptr %cell
+  ret void
+eof:
+  store i16 -1, ptr %cell
+  ret void
+}
+
+define i32 @main() {
+entry:
+  call void @input()
+  call void @add(i64 0, i16 1)
+  br label %loop0
+loop0:
+  %nonzero0 = call i1 @nonzero()
+  br i1 %nonzero0, label %body0, label %end0
+body0:
+  call void @add(i64 0, i16 -1)
+  call void @output(i64 0)
+  call void @input()
+  call void @add(i64 0, i16 1)
+  br label %loop0
+end0:
+  ret i32 0
+}
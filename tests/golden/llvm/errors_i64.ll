@@ -0,0 +1,120 @@
+; Generated by brain-rusted: 16 cells of type I64, Error at the tape ends,
+; Error at the end of the input.
+
+@tape = internal global [16 x i64] zeroinitializer
+@pointer = internal global i64 0
+@out_of_tape = private constant [30 x i8] c"Pointer moved out of the tape\0A"
+@end_of_input = private constant [24 x i8] c"Unexpected end of input\0A"
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+declare i32 @fflush(ptr)
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32) noreturn
+
+define internal void @fail(ptr %message, i64 %length) noreturn {
+  %flushed = call i32 @fflush(ptr null)
+  %written = call i64 @write(i32 2, ptr %message, i64 %length)
+  call void @exit(i32 70)
+  unreachable
+}
+
+define internal i64 @at(i64 %offset) {
+entry:
+  %pointer = load i64, ptr @pointer
+  %index = add i64 %pointer, %offset
+  %outside = icmp uge i64 %index, 16
+  br i1 %outside, label %fail, label %inside
+fail:
+  call void @fail(ptr @out_of_tape, i64 30)
+  unreachable
+inside:
+  ret i64 %index
+}
+
+define internal ptr @cell(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  %cell = getelementptr inbounds [16 x i64], ptr @tape, i64 0, i64 %index
+  ret ptr %cell
+}
+
+define internal i1 @nonzero() {
+  %pointer = load i64, ptr @pointer
+  %cell = getelementptr inbounds [16 x i64], ptr @tape, i64 0, i64 %pointer
+  %value = load i64, ptr %cell
+  %nonzero = icmp ne i64 %value, 0
+  ret i1 %nonzero
+}
+
+define internal void @move(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  store i64 %index, ptr @pointer
+  ret void
+}
+
+define internal void @mul_add(i64 %offset, i64 %factor) {
+entry:
+  %current = call ptr @cell(i64 0)
+  %value = load i64, ptr %current
+  %zero = icmp eq i64 %value, 0
+  br i1 %zero, label %done, label %add
+add:
+  %cell = call ptr @cell(i64 %offset)
+  %old = load i64, ptr %cell
+  %product = mul i64 %value, %factor
+  %sum = add i64 %old, %product
+  store i64 %sum, ptr %cell
+  br label %done
+done:
+  ret void
+}
+
+define internal void @set(i64 %offset, i64 %value) {
+  %cell = call ptr @cell(i64 %offset)
+  store i64 %value, ptr %cell
+  ret void
+}
+
+define internal void @output(i64 %offset) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load i64, ptr %cell
+  %wide = trunc i64 %value to i32
+  %byte = and i32 %wide, 255
+  %written = call i32 @putchar(i32 %byte)
+  ret void
+}
+
+define internal void @input() {
+entry:
+  %flushed = call i32 @fflush(ptr null)
+  %byte = call i32 @getchar()
+  %cell = call ptr @cell(i64 0)
+  %end = icmp slt i32 %byte, 0
+  br i1 %end, label %eof, label %read
+read:
+  %value = zext i32 %byte to i64
+  store i64 %value, ptr %cell
+  ret void
+eof:
+  call void @fail(ptr @end_of_input, i64 24)
+  unreachable
+}
+
+define i32 @main() {
+entry:
+  call void @input()
+  call void @mul_add(i64 1, i64 1)
+  call void @set(i64 0, i64 0)
+  call void @move(i64 1)
+  br label %loop0
+loop0:
+  %nonzero0 = call i1 @nonzero()
+  br i1 %nonzero0, label %body0, label %end0
+body0:
+  call void @move(i64 1)
+  br label %loop0
+end0:
+  call void @move(i64 -1)
+  call void @output(i64 0)
+  ret i32 0
+}
@@ -0,0 +1,138 @@
+; Generated by brain-rusted: 30000 cells of type U8, Wrap at the tape ends,
+; Zero at the end of the input.
+
+@tape = internal global [30000 x i8] zeroinitializer
+@pointer = internal global i64 0
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+declare i32 @fflush(ptr)
+declare i64 @write(i32, ptr, i64)
+declare void @exit(i32) noreturn
+
+define internal i64 @at(i64 %offset) {
+  %pointer = load i64, ptr @pointer
+  %sum = add i64 %pointer, %offset
+  %remainder = srem i64 %sum, 30000
+  %negative = icmp slt i64 %remainder, 0
+  %wrapped = add i64 %remainder, 30000
+  %index = select i1 %negative, i64 %wrapped, i64 %remainder
+  ret i64 %index
+}
+
+define internal ptr @cell(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  %cell = getelementptr inbounds [30000 x i8], ptr @tape, i64 0, i64 %index
+  ret ptr %cell
+}
+
+define internal i1 @nonzero() {
+  %pointer = load i64, ptr @pointer
+  %cell = getelementptr inbounds [30000 x i8], ptr @tape, i64 0, i64 %pointer
+  %value = load i8, ptr %cell
+  %nonzero = icmp ne i8 %value, 0
+  ret i1 %nonzero
+}
+
+define internal void @move(i64 %offset) {
+  %index = call i64 @at(i64 %offset)
+  store i64 %index, ptr @pointer
+  ret void
+}
+
+define internal void @add(i64 %offset, i8 %amount) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load i8, ptr %cell
+  %sum = add i8 %value, %amount
+  store i8 %sum, ptr %cell
+  ret void
+}
+
+define internal void @mul_add(i64 %offset, i8 %factor) {
+entry:
+  %current = call ptr @cell(i64 0)
+  %value = load i8, ptr %current
+  %zero = icmp eq i8 %value, 0
+  br i1 %zero, label %done, label %add
+add:
+  %cell = call ptr @cell(i64 %offset)
+  %old = load i8, ptr %cell
+  %product = mul i8 %value, %factor
+  %sum = add i8 %old, %product
+  store i8 %sum, ptr %cell
+  br label %done
+done:
+  ret void
+}
+
+define internal void @set(i64 %offset, i8 %value) {
+  %cell = call ptr @cell(i64 %offset)
+  store i8 %value, ptr %cell
+  ret void
+}
+
+define internal void @output(i64 %offset) {
+  %cell = call ptr @cell(i64 %offset)
+  %value = load i8, ptr %cell
+  %byte = zext i8 %value to i32
+  %written = call i32 @putchar(i32 %byte)
+  ret void
+}
+
+define i32 @main() {
+entry:
+  call void @add(i64 0, i8 8)
+  br label %loop0
+loop0:
+  %nonzero0 = call i1 @nonzero()
+  br i1 %nonzero0, label %body0, label %end0
+body0:
+  call void @add(i64 1, i8 4)
+  call void @move(i64 1)
+  call void @mul_add(i64 1, i8 2)
+  call void @mul_add(i64 2, i8 3)
+  call void @mul_add(i64 3, i8 3)
+  call void @mul_add(i64 4, i8 1)
+  call void @set(i64 0, i8 0)
+  call void @add(i64 1, i8 1)
+  call void @add(i64 2, i8 1)
+  call void @add(i64 3, i8 -1)
+  call void @add(i64 5, i8 1)
+  call void @move(i64 5)
+  br label %loop1
+loop1:
+  %nonzero1 = call i1 @nonzero()
+  br i1 %nonzero1, label %body1, label %end1
+body1:
+  call void @move(i64 -1)
+  br label %loop1
+end1:
+  call void @add(i64 -1, i8 -1)
+  call void @move(i64 -1)
+  br label %loop0
+end0:
+  call void @output(i64 2)
+  call void @add(i64 3, i8 -3)
+  call void @output(i64 3)
+  call void @add(i64 3, i8 7)
+  call void @output(i64 3)
+  call void @output(i64 3)
+  call void @add(i64 3, i8 3)
+  call void @output(i64 3)
+  call void @output(i64 5)
+  call void @add(i64 4, i8 -1)
+  call void @output(i64 4)
+  call void @output(i64 3)
+  call void @add(i64 3, i8 3)
+  call void @output(i64 3)
+  call void @add(i64 3, i8 -6)
+  call void @output(i64 3)
+  call void @add(i64 3, i8 -8)
+  call void @output(i64 3)
+  call void @add(i64 5, i8 1)
+  call void @output(i64 5)
+  call void @add(i64 6, i8 2)
+  call void @output(i64 6)
+  call void @move(i64 6)
+  ret i32 0
+}
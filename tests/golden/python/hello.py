@@ -0,0 +1,160 @@
+# Generated by brain-rusted: 30000 cells of type U8, Wrap at the tape ends,
+# Wrap on overflow, Zero at the end of the input.
+
+TAPE_LENGTH = 30000
+MAX_TAPE_LENGTH = None
+BITS = 8
+SIGNED = False
+
+MIN = -(1 << (BITS - 1)) if SIGNED else 0
+MAX = (1 << (BITS - 1)) - 1 if SIGNED else (1 << BITS) - 1
+
+
+class BrainError(Exception):
+    """Error raised when the program fails, with the exit status it ends with."""
+
+    def __init__(self, message, status):
+        super().__init__(message)
+        self.status = status
+
+
+def wrap(value):
+    value &= (1 << BITS) - 1
+    if SIGNED and value > MAX:
+        value -= 1 << BITS
+    return value
+
+
+class Machine:
+    def __init__(self, read, write):
+        self.tape = [0] * max(TAPE_LENGTH, 1)
+        self.pointer = 0
+        self.origin = 0
+        self.read = read
+        self.write = write
+
+    def fail(self, message, status):
+        raise BrainError(message, status)
+
+    def is_nonzero(self):
+        return self.tape[self.pointer] != 0
+
+    def advance(self, offset):
+        self.pointer = self.at(offset)
+
+    def add(self, offset, amount):
+        index = self.at(offset)
+        self.store(index, add(self.tape[index], amount, 1))
+
+    def mul_add(self, offset, factor):
+        value = self.tape[self.pointer]
+        if value == 0:
+            return
+        index = self.at(offset)
+        self.store(index, add(self.tape[index], value, factor))
+
+    def set(self, offset, value):
+        self.store(self.at(offset), wrap(value))
+
+    def store(self, index, value):
+        if value is None:
+            self.fail("Cell overflow", 70)
+        self.tape[index] = value
+
+    def output(self, offset):
+        index = self.at(offset)
+        self.print(self.tape[index] & 0xFF)
+
+    def print(self, byte):
+        self.write(byte)
+
+    def input(self):
+        byte = self.read()
+        if byte is None:
+            self.end_of_input()
+        else:
+            self.store(self.pointer, wrap(byte))
+
+    def at(self, offset):
+        return (self.pointer + offset) % len(self.tape)
+
+    def end_of_input(self):
+        self.store(self.pointer, 0)
+
+
+def add(cell, value, factor):
+    return wrap(cell + value * factor)
+
+
+def run(read, write):
+    """Runs the program. It calls read() whenever it needs a byte of input,
+    which must return an integer from 0 to 255, or None at the end of the
+    input, and write(byte) for every byte of output. Raises a BrainError
+    when the program fails."""
+    machine = Machine(read, write)
+    machine.add(0, 8)
+    while machine.is_nonzero():
+        machine.add(1, 4)
+        machine.advance(1)
+        machine.mul_add(1, 2)
+        machine.mul_add(2, 3)
+        machine.mul_add(3, 3)
+        machine.mul_add(4, 1)
+        machine.set(0, 0)
+        machine.add(1, 1)
+        machine.add(2, 1)
+        machine.add(3, -1)
+        machine.add(5, 1)
+        machine.advance(5)
+        while machine.is_nonzero(): machine.advance(-1)
+        machine.add(-1, -1)
+        machine.advance(-1)
+    machine.output(2)
+    machine.add(3, -3)
+    machine.output(3)
+    machine.add(3, 7)
+    machine.output(3)
+    machine.output(3)
+    machine.add(3, 3)
+    machine.output(3)
+    machine.output(5)
+    machine.add(4, -1)
+    machine.output(4)
+    machine.output(3)
+    machine.add(3, 3)
+    machine.output(3)
+    machine.add(3, -6)
+    machine.output(3)
+    machine.add(3, -8)
+    machine.output(3)
+    machine.add(5, 1)
+    machine.output(5)
+    machine.add(6, 2)
+    machine.output(6)
+    machine.advance(6)
+
+
+if __name__ == "__main__":
+    import sys
+
+    def read():
+        sys.stdout.buffer.flush()
+        byte = sys.stdin.buffer.read(1)
+        return byte[0] if byte else None
+
+    def write(byte):
+        sys.stdout.buffer.write(bytes((byte,)))
+
+    try:
+        run(read, write)
+        sys.stdout.buffer.flush()
+    except BrainError as error:
+        try:
+            sys.stdout.buffer.flush()
+        except OSError:
+            pass
+        print(error, file=sys.stderr)
+        sys.exit(error.status)
+    except OSError:
+        print("Failed to read input or write output", file=sys.stderr)
+        sys.exit(70)
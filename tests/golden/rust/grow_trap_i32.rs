@@ -0,0 +1,148 @@
+//! Generated by brain-rusted: 8 cells of type I32, Grow at the tape ends,
+//! Trap on overflow, Unchanged at the end of the input.
+#![allow(dead_code)]
+
+use std::io::{BufWriter, Read, Stdin, Stdout, Write};
+
+struct Machine {
+    tape: Vec<Cell>,
+    pointer: usize,
+    origin: usize,
+    input: Stdin,
+    output: BufWriter<Stdout>,
+}
+
+impl Machine {
+    fn new() -> Self {
+        Self {
+            tape: vec![0; TAPE_LENGTH.max(1)],
+            pointer: 0,
+            origin: 0,
+            input: std::io::stdin(),
+            output: BufWriter::new(std::io::stdout()),
+        }
+    }
+
+    fn fail(&mut self, message: &str, status: i32) -> ! {
+        let _ = self.output.flush();
+        eprintln!("{}", message);
+        std::process::exit(status)
+    }
+
+    fn is_nonzero(&self) -> bool {
+        self.tape[self.pointer] != 0
+    }
+
+    fn advance(&mut self, offset: isize) {
+        self.pointer = self.at(offset);
+    }
+
+    fn add(&mut self, offset: isize, amount: i64) {
+        let index = self.at(offset);
+        match add(self.tape[index], amount as i128, 1) {
+            Some(value) => self.tape[index] = value,
+            None => self.fail("Cell overflow", 70),
+        }
+    }
+
+    fn mul_add(&mut self, offset: isize, factor: i64) {
+        let value = self.tape[self.pointer];
+        if value == 0 {
+            return;
+        }
+        let index = self.at(offset);
+        match add(self.tape[index], value as i128, factor as i128) {
+            Some(value) => self.tape[index] = value,
+            None => self.fail("Cell overflow", 70),
+        }
+    }
+
+    fn set(&mut self, offset: isize, value: i64) {
+        let index = self.at(offset);
+        self.tape[index] = value as Cell;
+    }
+
+    fn output(&mut self, offset: isize) {
+        let index = self.at(offset);
+        self.print(self.tape[index] as u8);
+    }
+
+    fn print(&mut self, byte: u8) {
+        if self.output.write_all(&[byte]).is_err() {
+            self.fail("Failed to read input or write output", 70);
+        }
+    }
+
+    fn input(&mut self) {
+        let mut byte = [0u8; 1];
+        if self.output.flush().is_err() {
+            self.fail("Failed to read input or write output", 70);
+        }
+        match self.input.read_exact(&mut byte) {
+            Ok(()) => self.tape[self.pointer] = byte[0] as Cell,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => self.end_of_input(),
+            Err(_) => self.fail("Failed to read input or write output", 70),
+        }
+    }
+
+    fn at(&mut self, offset: isize) -> usize {
+        let target = self.pointer as isize + offset;
+        if target < 0 {
+            let extra = self.growth(target.unsigned_abs());
+            let mut tape = vec![0; extra + self.tape.len()];
+            tape[extra..].copy_from_slice(&self.tape);
+            self.tape = tape;
+            self.pointer += extra;
+            self.origin += extra;
+            return (self.pointer as isize + offset) as usize;
+        }
+        if target as usize >= self.tape.len() {
+            let extra = self.growth(target as usize + 1 - self.tape.len());
+            self.tape.resize(self.tape.len() + extra, 0);
+        }
+        target as usize
+    }
+
+    fn growth(&mut self, needed: usize) -> usize {
+        let length = self.tape.len();
+        match MAX_TAPE_LENGTH {
+            Some(limit) if length + needed > limit => {
+                self.fail(&format!("Tape limit exceeded: {} cells", limit), 75)
+            }
+            Some(limit) => needed.max(length).min(limit - length),
+            None => needed.max(length),
+        }
+    }
+
+    fn end_of_input(&mut self) {
+        
+    }
+}
+
+fn add(cell: Cell, value: i128, factor: i128) -> Option<Cell> {
+    let sum = value.checked_mul(factor).and_then(|product| product.checked_add(cell as i128));
+    sum.filter(|&sum| Cell::MIN as i128 <= sum && sum <= Cell::MAX as i128)
+        .map(|sum| sum as Cell)
+}
+
+type Cell = i32;
+const TAPE_LENGTH: usize = 8;
+const MAX_TAPE_LENGTH: Option<usize> = Some(1024);
+
+fn main() {
+    let mut machine = Machine::new();
+    machine.input();
+    while machine.is_nonzero() {
+        machine.add(0, -1);
+        machine.advance(1);
+        machine.add(0, 1);
+        machine.advance(-1);
+    }
+    machine.advance(1);
+    while machine.is_nonzero() { machine.advance(1); }
+    machine.advance(-1);
+    machine.output(0);
+    if machine.output.flush().is_err() {
+        machine.fail("Failed to read input or write output", 70);
+    }
+}
@@ -0,0 +1,151 @@
+//! Generated by brain-rusted: 30000 cells of type U8, Wrap at the tape ends,
+//! Wrap on overflow, Zero at the end of the input.
+#![allow(dead_code)]
+
+use std::io::{BufWriter, Read, Stdin, Stdout, Write};
+
+struct Machine {
+    tape: Vec<Cell>,
+    pointer: usize,
+    origin: usize,
+    input: Stdin,
+    output: BufWriter<Stdout>,
+}
+
+impl Machine {
+    fn new() -> Self {
+        Self {
+            tape: vec![0; TAPE_LENGTH.max(1)],
+            pointer: 0,
+            origin: 0,
+            input: std::io::stdin(),
+            output: BufWriter::new(std::io::stdout()),
+        }
+    }
+
+    fn fail(&mut self, message: &str, status: i32) -> ! {
+        let _ = self.output.flush();
+        eprintln!("{}", message);
+        std::process::exit(status)
+    }
+
+    fn is_nonzero(&self) -> bool {
+        self.tape[self.pointer] != 0
+    }
+
+    fn advance(&mut self, offset: isize) {
+        self.pointer = self.at(offset);
+    }
+
+    fn add(&mut self, offset: isize, amount: i64) {
+        let index = self.at(offset);
+        match add(self.tape[index], amount as i128, 1) {
+            Some(value) => self.tape[index] = value,
+            None => self.fail("Cell overflow", 70),
+        }
+    }
+
+    fn mul_add(&mut self, offset: isize, factor: i64) {
+        let value = self.tape[self.pointer];
+        if value == 0 {
+            return;
+        }
+        let index = self.at(offset);
+        match add(self.tape[index], value as i128, factor as i128) {
+            Some(value) => self.tape[index] = value,
+            None => self.fail("Cell overflow", 70),
+        }
+    }
+
+    fn set(&mut self, offset: isize, value: i64) {
+        let index = self.at(offset);
+        self.tape[index] = value as Cell;
+    }
+
+    fn output(&mut self, offset: isize) {
+        let index = self.at(offset);
+        self.print(self.tape[index] as u8);
+    }
+
+    fn print(&mut self, byte: u8) {
+        if self.output.write_all(&[byte]).is_err() {
+            self.fail("Failed to read input or write output", 70);
+        }
+    }
+
+    fn input(&mut self) {
+        let mut byte = [0u8; 1];
+        if self.output.flush().is_err() {
+            self.fail("Failed to read input or write output", 70);
+        }
+        match self.input.read_exact(&mut byte) {
+            Ok(()) => self.tape[self.pointer] = byte[0] as Cell,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => self.end_of_input(),
+            Err(_) => self.fail("Failed to read input or write output", 70),
+        }
+    }
+
+    fn at(&mut self, offset: isize) -> usize {
+        (self.pointer as isize + offset).rem_euclid(self.tape.len() as isize) as usize
+    }
+
+    fn end_of_input(&mut self) {
+        self.tape[self.pointer] = 0;
+    }
+}
+
+fn add(cell: Cell, value: i128, factor: i128) -> Option<Cell> {
+    Some(value.wrapping_mul(factor).wrapping_add(cell as i128) as Cell)
+}
+
+type Cell = u8;
+const TAPE_LENGTH: usize = 30000;
+const MAX_TAPE_LENGTH: Option<usize> = None;
+
+fn main() {
+    let mut machine = Machine::new();
+    machine.add(0, 8);
+    while machine.is_nonzero() {
+        machine.add(1, 4);
+        machine.advance(1);
+        machine.mul_add(1, 2);
+        machine.mul_add(2, 3);
+        machine.mul_add(3, 3);
+        machine.mul_add(4, 1);
+        machine.set(0, 0);
+        machine.add(1, 1);
+        machine.add(2, 1);
+        machine.add(3, -1);
+        machine.add(5, 1);
+        machine.advance(5);
+        while machine.is_nonzero() { machine.advance(-1); }
+        machine.add(-1, -1);
+        machine.advance(-1);
+    }
+    machine.output(2);
+    machine.add(3, -3);
+    machine.output(3);
+    machine.add(3, 7);
+    machine.output(3);
+    machine.output(3);
+    machine.add(3, 3);
+    machine.output(3);
+    machine.output(5);
+    machine.add(4, -1);
+    machine.output(4);
+    machine.output(3);
+    machine.add(3, 3);
+    machine.output(3);
+    machine.add(3, -6);
+    machine.output(3);
+    machine.add(3, -8);
+    machine.output(3);
+    machine.add(5, 1);
+    machine.output(5);
+    machine.add(6, 2);
+    machine.output(6);
+    machine.advance(6);
+    if machine.output.flush().is_err() {
+        machine.fail("Failed to read input or write output", 70);
+    }
+}
@@ -0,0 +1,208 @@
+//! Cross-checks of the native code against the interpreter. Every program
+//! is run both ways, with the same options and input, and must write the
+//! same output or fail with the same error. On targets without native code
+//! both runs are interpreted, so the tests still pass.
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy};
+
+/// Steps a random program may take in the interpreter. Programs that take
+/// more are skipped, since the native code has no step limit.
+const STEP_LIMIT: u64 = 100_000;
+
+/// Every cell type.
+const CELLS: [CellType; 8] = [
+    CellType::U8,
+    CellType::U16,
+    CellType::U32,
+    CellType::U64,
+    CellType::I8,
+    CellType::I16,
+    CellType::I32,
+    CellType::I64,
+];
+
+/// Every EOF policy.
+const EOFS: [EofPolicy; 4] = [
+    EofPolicy::Zero,
+    EofPolicy::MinusOne,
+    EofPolicy::Unchanged,
+    EofPolicy::Error,
+];
+
+/// Generator of pseudo-random numbers (xorshift), so the tests always
+/// check the same programs.
+struct Random(u64);
+
+impl Random {
+    /// Returns the next number, below the given bound.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+/// Returns a random balanced program, biased towards the loops the
+/// optimizer rewrites.
+fn random_program(random: &mut Random) -> String {
+    const PIECES: [&str; 14] = [
+        "+",
+        "-",
+        ">",
+        "<",
+        ".",
+        ",",
+        "[-]",
+        "[->+<]",
+        "[->>+++<<]",
+        "[>]",
+        "[<]",
+        "+++++",
+        "-----",
+        ">>>",
+    ];
+    let mut source = String::new();
+    let mut open = 0;
+    for _ in 0..random.below(40) + 5 {
+        match random.below(10) {
+            0 => {
+                source.push('[');
+                open += 1;
+            }
+            1 if open > 0 => {
+                source.push(']');
+                open -= 1;
+            }
+            _ => source.push_str(PIECES[random.below(PIECES.len() as u64) as usize]),
+        }
+    }
+    source.push_str(&"]".repeat(open));
+    source
+}
+
+/// Returns the output of the given run, or the message of its error.
+fn outcome(result: Result<Vec<u8>, brain_rusted::RuntimeError>) -> Result<Vec<u8>, String> {
+    result.map_err(|error| error.to_string())
+}
+
+/// Runs the given source, optimized for the given options, both
+/// interpreted and as native code, and checks that both runs agree.
+/// Returns false, without checking, if the interpreter took too many steps.
+fn cross_check(source: &str, input: &[u8], options: &MachineOptions) -> bool {
+    let program = brain_rusted::compile(source).expect("balanced program");
+    let program = brain_rusted::optimize(&program, options);
+    let limited = MachineOptions {
+        max_steps: Some(STEP_LIMIT),
+        ..options.clone()
+    };
+    let interpreted = match brain_rusted::run_with(&program, input, &limited) {
+        Err(error) if error.is_limit_exceeded() => return false,
+        result => outcome(result),
+    };
+    let jit = MachineOptions {
+        jit: true,
+        ..options.clone()
+    };
+    let native = outcome(brain_rusted::run_with(&program, input, &jit));
+
+    assert_eq!(native, interpreted, "program {source:?} with {options:?}");
+    true
+}
+
+#[test]
+fn known_programs_match_the_interpreter() {
+    let hello = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+    for cell in CELLS {
+        let options = MachineOptions {
+            cell,
+            ..MachineOptions::default()
+        };
+        assert!(cross_check(hello, b"", &options));
+        assert!(cross_check(",[.,]", b"echo", &options));
+        assert!(cross_check("+[>,]<[<]>[.>]", b"tape", &options));
+    }
+}
+
+#[test]
+fn errors_match_the_interpreter() {
+    let options = MachineOptions {
+        tape_length: 16,
+        tape_policy: TapePolicy::Error,
+        eof: EofPolicy::Error,
+        ..MachineOptions::default()
+    };
+
+    assert!(cross_check("+[>+]", b"", &options));
+    assert!(cross_check("<", b"", &options));
+    assert!(cross_check("+[[->>>>>+<<<<<]>>>>>]", b"", &options));
+    assert!(cross_check(",.,.,.", b"ab", &options));
+}
+
+#[test]
+fn random_programs_match_the_interpreter() {
+    let mut random = Random(0x9E37_79B9_7F4A_7C15);
+    let mut checked = 0;
+    for _ in 0..2_000 {
+        let source = random_program(&mut random);
+        let options = MachineOptions {
+            tape_length: random.below(40) as usize + 1,
+            tape_policy: match random.below(2) {
+                0 => TapePolicy::Wrap,
+                _ => TapePolicy::Error,
+            },
+            cell: CELLS[random.below(CELLS.len() as u64) as usize],
+            eof: EOFS[random.below(EOFS.len() as u64) as usize],
+            ..MachineOptions::default()
+        };
+        let input: Vec<u8> = (0..random.below(4))
+            .map(|_| random.below(256) as u8)
+            .collect();
+        if cross_check(&source, &input, &options) {
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 1_000, "only {checked} programs were checked");
+}
+
+#[test]
+fn unsupported_options_fall_back_to_the_interpreter() {
+    let run = |source, options: MachineOptions| {
+        let program = brain_rusted::compile(source).expect("valid program");
+        let jit = MachineOptions {
+            jit: true,
+            ..options
+        };
+        outcome(brain_rusted::run_with(&program, b"", &jit))
+    };
+
+    // Only the interpreter grows tapes, saturates and traps, and names the
+    // line and column of the instruction that failed.
+    let grow = MachineOptions {
+        tape_policy: TapePolicy::Grow,
+        tape_length: 2,
+        max_tape_length: Some(4),
+        ..MachineOptions::default()
+    };
+    assert_eq!(run("<<+++.", grow.clone()), Ok(vec![3]));
+    assert_eq!(
+        run("+\n>>>>+", grow),
+        Err("Tape limit exceeded: 4 cells, at line 2, column 4".to_string())
+    );
+
+    let trap = MachineOptions {
+        overflow: OverflowPolicy::Trap,
+        ..MachineOptions::default()
+    };
+    assert_eq!(
+        run("+.\n--", trap),
+        Err("Cell overflow at line 2, column 2".to_string())
+    );
+
+    let saturate = MachineOptions {
+        overflow: OverflowPolicy::Saturate,
+        ..MachineOptions::default()
+    };
+    assert_eq!(run("--.++.", saturate), Ok(vec![0, 2]));
+}
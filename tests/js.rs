@@ -0,0 +1,78 @@
+//! Tests for the JavaScript backend. The emitted files are compared with
+//! the files stored under "tests/golden/js" and, when Node.js is
+//! installed, run and cross-checked against the interpreter. The backend
+//! supports every option, so the cross-checks cover saturating, trapping
+//! and growing tapes too.
+
+mod common;
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use std::process::Command;
+
+/// Returns the given source emitted for JavaScript with the given options.
+fn emit(source: &str, options: &MachineOptions) -> String {
+    let emitted = common::emit(source, Target::Js, options).expect("supported options");
+    String::from_utf8(emitted).expect("UTF-8")
+}
+
+#[test]
+fn hello_world_matches_the_golden_file() {
+    common::check_golden(
+        Target::Js,
+        "hello.js",
+        common::HELLO,
+        &MachineOptions::default(),
+    );
+}
+
+#[test]
+fn wide_cells_and_saturation_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::I64,
+        eof: EofPolicy::MinusOne,
+        tape_policy: TapePolicy::Grow,
+        overflow: OverflowPolicy::Saturate,
+        tape_length: 8,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Js, "saturate_i64.js", ",[->+<]>[>]<.", &options);
+}
+
+#[test]
+fn only_64_bit_cells_use_bigint() {
+    for cell in [CellType::U8, CellType::I16, CellType::U32, CellType::I32] {
+        let options = MachineOptions {
+            cell,
+            ..MachineOptions::default()
+        };
+        assert!(
+            !emit(",[->++<]>.", &options).contains("BigInt"),
+            "{:?}",
+            cell
+        );
+    }
+    for cell in [CellType::U64, CellType::I64] {
+        let options = MachineOptions {
+            cell,
+            ..MachineOptions::default()
+        };
+        assert!(
+            emit(",[->++<]>.", &options).contains("BigInt"),
+            "{:?}",
+            cell
+        );
+    }
+}
+
+#[test]
+fn scripts_behave_like_the_interpreter() {
+    if !common::tools_installed(&["node"]) {
+        return;
+    }
+    let cases = common::CASES.iter().chain(&common::EXTENDED_CASES);
+    common::cross_check(Target::Js, "js", cases, |script| {
+        let mut command = Command::new("node");
+        command.arg(script);
+        command
+    });
+}
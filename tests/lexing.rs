@@ -0,0 +1,62 @@
+//! Tests for the lexical analysis, in its default mode, where anything but
+//! a command is a comment, and in strict mode.
+
+use brain_rusted::{LexError, Lexer, Span, TokenKind};
+
+#[test]
+fn comments_are_ignored_by_default() {
+    let tokens = Lexer::tokenize("add one: +\nand show it. (done)");
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+
+    assert_eq!(kinds, [TokenKind::Increment, TokenKind::Show]);
+    assert_eq!(tokens[0].span, Span::new(9, 1, 1, 10));
+    assert_eq!(tokens[1].span, Span::new(22, 1, 2, 12));
+}
+
+#[test]
+fn commented_programs_run_like_bare_ones() {
+    let commented = "This prints A: ++++++++[>++++++++<-]>+ and now . it";
+    let program = brain_rusted::compile(commented).expect("comments are fine");
+
+    assert_eq!(brain_rusted::run(&program, b"").expect("no error"), b"A");
+}
+
+#[test]
+fn strict_mode_accepts_commands_and_whitespace() {
+    let tokens = Lexer::tokenize_strict("+ +\n\t[-]\r\n.").expect("no comments");
+
+    assert_eq!(tokens.len(), 6);
+}
+
+#[test]
+fn strict_mode_reports_every_offending_character_where_it_is() {
+    let error = Lexer::tokenize_strict("+a+\n\t-é]\nz").expect_err("comments");
+    let LexError::InvalidTokens { tokens, omitted } = &error;
+    let found: Vec<(char, usize, usize)> = tokens
+        .iter()
+        .map(|token| {
+            let span = token.snippet.span();
+            (token.character, span.line, span.column)
+        })
+        .collect();
+
+    assert_eq!(found, [('a', 1, 2), ('é', 2, 3), ('z', 3, 1)]);
+    assert_eq!(*omitted, 0);
+    let rendered = error.to_string();
+    assert!(rendered.contains("error: invalid token found: a\n --> 1:2\n"));
+    assert!(rendered.contains("error: invalid token found: é\n --> 2:3\n"));
+    assert!(rendered.contains("error: invalid token found: z\n --> 3:1\n"));
+}
+
+#[test]
+fn strict_mode_lists_the_first_offending_characters_and_counts_the_rest() {
+    let source = "x".repeat(LexError::REPORTED_TOKENS + 5);
+    let error = Lexer::tokenize_strict(&source).expect_err("comments");
+    let LexError::InvalidTokens { tokens, omitted } = &error;
+
+    assert_eq!(tokens.len(), LexError::REPORTED_TOKENS);
+    assert_eq!(*omitted, 5);
+    assert!(error
+        .to_string()
+        .ends_with("\n\n... and 5 more invalid tokens"));
+}
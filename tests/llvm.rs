@@ -0,0 +1,73 @@
+//! Tests for the LLVM IR backend. The emitted modules are compared with the
+//! files stored under "tests/golden/llvm" and, when lli is installed, run
+//! and cross-checked against the interpreter.
+
+mod common;
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, TapePolicy, Target};
+use std::process::Command;
+
+/// Returns the major version of the installed lli.
+fn lli_version() -> u32 {
+    let output = Command::new("lli")
+        .arg("--version")
+        .output()
+        .expect("lli runs");
+    let output = String::from_utf8_lossy(&output.stdout);
+    output
+        .split("LLVM version ")
+        .nth(1)
+        .and_then(|version| version.split('.').next())
+        .and_then(|major| major.parse().ok())
+        .expect("lli prints its version")
+}
+
+#[test]
+fn hello_world_matches_the_golden_file() {
+    common::check_golden(
+        Target::Llvm,
+        "hello.ll",
+        common::HELLO,
+        &MachineOptions::default(),
+    );
+}
+
+#[test]
+fn wide_cells_and_end_of_input_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::U16,
+        eof: EofPolicy::MinusOne,
+        tape_length: 64,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Llvm, "echo_u16.ll", ",+[-.,+]", &options);
+}
+
+#[test]
+fn errors_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::I64,
+        eof: EofPolicy::Error,
+        tape_policy: TapePolicy::Error,
+        tape_length: 16,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Llvm, "errors_i64.ll", ",[->+<]>[>]<.", &options);
+}
+
+#[test]
+fn modules_behave_like_the_interpreter() {
+    if !common::tools_installed(&["lli"]) {
+        return;
+    }
+    let version = lli_version();
+    assert!(version >= 14, "lli {} cannot read opaque pointers", version);
+    common::cross_check(Target::Llvm, "ll", &common::CASES, |module| {
+        let mut command = Command::new("lli");
+        if version < 15 {
+            command.arg("-opaque-pointers");
+        }
+        command.arg(module);
+        command
+    });
+}
@@ -0,0 +1,69 @@
+//! Tests for the Python backend. The emitted scripts are compared with the
+//! files stored under "tests/golden/python" and, when python3 is
+//! installed, run and cross-checked against the interpreter. The backend
+//! supports every option, so the cross-checks cover saturating, trapping
+//! and growing tapes too.
+
+mod common;
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use common::Case;
+use std::process::Command;
+
+/// Program with loops nested deeper than Python allows in one function,
+/// which prints "A".
+const NESTED: &str =
+    "+[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[>++++++++[>++++++++<-]>+.[-]<<-]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]";
+
+#[test]
+fn hello_world_matches_the_golden_file() {
+    common::check_golden(
+        Target::Python,
+        "hello.py",
+        common::HELLO,
+        &MachineOptions::default(),
+    );
+}
+
+#[test]
+fn traps_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::I16,
+        eof: EofPolicy::Unchanged,
+        tape_policy: TapePolicy::Error,
+        overflow: OverflowPolicy::Trap,
+        tape_length: 16,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Python, "trap_i16.py", ",[->+<]>[>]<.", &options);
+}
+
+#[test]
+fn deep_nesting_matches_the_golden_file() {
+    let options = MachineOptions {
+        tape_length: 4,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Python, "nested.py", NESTED, &options);
+}
+
+#[test]
+fn scripts_behave_like_the_interpreter() {
+    if !common::tools_installed(&["python3"]) {
+        return;
+    }
+    let nested = Case {
+        source: NESTED,
+        args: &["-O0"],
+        input: b"",
+    };
+    let cases = common::CASES
+        .iter()
+        .chain(&common::EXTENDED_CASES)
+        .chain([&nested]);
+    common::cross_check(Target::Python, "py", cases, |script| {
+        let mut command = Command::new("python3");
+        command.arg(script);
+        command
+    });
+}
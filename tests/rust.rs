@@ -0,0 +1,65 @@
+//! Tests for the Rust backend. The emitted sources are compared with the
+//! files stored under "tests/golden/rust" and, when rustc is installed,
+//! compiled, run and cross-checked against the interpreter. The backend
+//! supports every option, so the cross-checks cover saturating, trapping
+//! and growing tapes too.
+
+mod common;
+
+use brain_rusted::{CellType, EofPolicy, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use std::process::Command;
+
+#[test]
+fn hello_world_matches_the_golden_file() {
+    common::check_golden(
+        Target::Rust,
+        "hello.rs",
+        common::HELLO,
+        &MachineOptions::default(),
+    );
+}
+
+#[test]
+fn growing_tapes_and_traps_match_the_golden_file() {
+    let options = MachineOptions {
+        cell: CellType::I32,
+        eof: EofPolicy::Unchanged,
+        tape_policy: TapePolicy::Grow,
+        max_tape_length: Some(1024),
+        overflow: OverflowPolicy::Trap,
+        tape_length: 8,
+        ..MachineOptions::default()
+    };
+    common::check_golden(Target::Rust, "grow_trap_i32.rs", ",[->+<]>[>]<.", &options);
+}
+
+#[test]
+fn every_option_is_supported() {
+    for tape_policy in [TapePolicy::Wrap, TapePolicy::Error, TapePolicy::Grow] {
+        for overflow in [
+            OverflowPolicy::Wrap,
+            OverflowPolicy::Saturate,
+            OverflowPolicy::Trap,
+        ] {
+            let options = MachineOptions {
+                tape_policy,
+                overflow,
+                ..MachineOptions::default()
+            };
+            assert!(common::emit(common::HELLO, Target::Rust, &options).is_ok());
+        }
+    }
+}
+
+#[test]
+fn compiled_programs_behave_like_the_interpreter() {
+    if !common::tools_installed(&["rustc"]) {
+        return;
+    }
+    let cases = common::CASES.iter().chain(&common::EXTENDED_CASES);
+    common::cross_check(Target::Rust, "rs", cases, |source| {
+        let executable = source.with_extension("exe");
+        common::build(Command::new("rustc").arg("-o").arg(&executable).arg(source));
+        Command::new(executable)
+    });
+}
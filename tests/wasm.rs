@@ -0,0 +1,201 @@
+//! Tests for the WebAssembly backend. They read back the emitted modules
+//! just enough to check their layout and, when Node.js is installed, run
+//! them with its WASI implementation and cross-check them against the
+//! interpreter.
+
+mod common;
+
+use brain_rusted::{EmitError, MachineOptions, OverflowPolicy, TapePolicy, Target};
+use std::process::Command;
+
+/// Node.js module that runs the WASI module at the path it is given, on the
+/// standard streams, and exits with its exit code.
+const RUNNER: &str = "\
+import { readFile } from \"node:fs/promises\";
+import { WASI } from \"node:wasi\";
+const wasi = new WASI({ version: \"preview1\", returnOnExit: true });
+const module = await WebAssembly.compile(await readFile(process.argv[2]));
+const instance = await WebAssembly.instantiate(module, wasi.getImportObject());
+process.exitCode = wasi.start(instance);
+";
+
+/// Program that reads, changes, moves and writes, so every helper is used.
+const PROGRAM: &str = ",[->+<]>.[-]++[>+++<-]>.";
+
+/// Returns the given program emitted for the given target with the
+/// default options.
+fn emit(target: Target) -> Vec<u8> {
+    let program = brain_rusted::compile(PROGRAM).expect("valid program");
+    brain_rusted::emit(&program, target, &MachineOptions::default()).expect("supported options")
+}
+
+/// Returns the unsigned LEB128 number at the given position of the given
+/// bytes, moving the position past it.
+fn read_unsigned(bytes: &[u8], position: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*position];
+        *position += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+/// Returns the name at the given position of the given bytes, moving the
+/// position past it.
+fn read_name(bytes: &[u8], position: &mut usize) -> String {
+    let length = read_unsigned(bytes, position) as usize;
+    let name = String::from_utf8(bytes[*position..*position + length].to_vec()).expect("UTF-8");
+    *position += length;
+    name
+}
+
+/// Returns the identifier and contents of every section of the given
+/// module, checking that they fill it exactly.
+fn sections(module: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut sections = vec![];
+    let mut position = 8;
+    while position < module.len() {
+        let id = module[position];
+        position += 1;
+        let size = read_unsigned(module, &mut position) as usize;
+        sections.push((id, module[position..position + size].to_vec()));
+        position += size;
+    }
+    assert_eq!(position, module.len());
+    sections
+}
+
+/// Returns the contents of the section with the given identifier.
+fn section(module: &[u8], id: u8) -> Vec<u8> {
+    sections(module)
+        .into_iter()
+        .find(|(section, _)| *section == id)
+        .map(|(_, contents)| contents)
+        .expect("section present")
+}
+
+#[test]
+fn binary_modules_start_with_the_magic_number_and_version() {
+    let module = emit(Target::Wasm);
+
+    assert_eq!(&module[..4], b"\0asm");
+    assert_eq!(&module[4..8], &[1, 0, 0, 0]);
+}
+
+#[test]
+fn binary_sections_are_in_order() {
+    let ids: Vec<u8> = sections(&emit(Target::Wasm))
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    assert_eq!(ids, [1, 2, 3, 5, 6, 7, 10, 11]);
+}
+
+#[test]
+fn binary_modules_import_wasi_input_and_output() {
+    let imports = section(&emit(Target::Wasm), 2);
+    let mut position = 0;
+    let count = read_unsigned(&imports, &mut position);
+    let mut names = vec![];
+    for _ in 0..count {
+        let module = read_name(&imports, &mut position);
+        let name = read_name(&imports, &mut position);
+        assert_eq!(imports[position], 0x00, "only functions are imported");
+        position += 1;
+        read_unsigned(&imports, &mut position);
+        names.push((module, name));
+    }
+
+    for wanted in ["fd_read", "fd_write"] {
+        assert!(names.contains(&("wasi_snapshot_preview1".to_string(), wanted.to_string())));
+    }
+}
+
+#[test]
+fn binary_modules_export_the_memory_and_the_entry_point() {
+    let exports = section(&emit(Target::Wasm), 7);
+    let mut position = 0;
+    let count = read_unsigned(&exports, &mut position);
+    let mut names = vec![];
+    for _ in 0..count {
+        let name = read_name(&exports, &mut position);
+        let kind = exports[position];
+        position += 1;
+        read_unsigned(&exports, &mut position);
+        names.push((name, kind));
+    }
+
+    assert!(names.contains(&("memory".to_string(), 0x02)));
+    assert!(names.contains(&("_start".to_string(), 0x00)));
+}
+
+#[test]
+fn memory_holds_the_whole_tape() {
+    let program = brain_rusted::compile(PROGRAM).expect("valid program");
+    let options = MachineOptions {
+        tape_length: 100_000,
+        cell: brain_rusted::CellType::U32,
+        ..MachineOptions::default()
+    };
+    let module = brain_rusted::emit(&program, Target::Wasm, &options).expect("supported options");
+    let memories = section(&module, 5);
+    let mut position = 0;
+
+    assert_eq!(read_unsigned(&memories, &mut position), 1);
+    assert_eq!(memories[position], 0x00);
+    position += 1;
+    let pages = read_unsigned(&memories, &mut position);
+    assert!(pages * 65_536 >= 400_000);
+}
+
+#[test]
+fn text_modules_match_binary_modules() {
+    let text = String::from_utf8(emit(Target::Wat)).expect("UTF-8");
+
+    assert!(text.contains("(module"));
+    assert!(text.contains(r#"(import "wasi_snapshot_preview1" "fd_read""#));
+    assert!(text.contains(r#"(import "wasi_snapshot_preview1" "fd_write""#));
+    assert!(text.contains(r#"(memory (export "memory") 1)"#));
+    assert!(text.contains(r#"(func $start (export "_start")"#));
+    assert_eq!(text.matches('(').count(), text.matches(')').count());
+}
+
+#[test]
+fn unsupported_options_are_refused() {
+    let program = brain_rusted::compile(PROGRAM).expect("valid program");
+    for options in [
+        MachineOptions {
+            tape_policy: TapePolicy::Grow,
+            ..MachineOptions::default()
+        },
+        MachineOptions {
+            overflow: OverflowPolicy::Trap,
+            ..MachineOptions::default()
+        },
+    ] {
+        for target in [Target::Wat, Target::Wasm] {
+            let error = brain_rusted::emit(&program, target, &options).expect_err("unsupported");
+            assert!(matches!(error, EmitError::Unsupported { .. }));
+        }
+    }
+}
+
+#[test]
+fn modules_behave_like_the_interpreter() {
+    if !common::tools_installed(&["node"]) {
+        return;
+    }
+    common::cross_check(Target::Wasm, "wasm", &common::CASES, |module| {
+        let runner = module.with_extension("mjs");
+        std::fs::write(&runner, RUNNER).expect("writable runner");
+        let mut command = Command::new("node");
+        command.arg("--no-warnings").arg(runner).arg(module);
+        command
+    });
+}